@@ -8,7 +8,9 @@ use std::time::Duration;
 
 use anyhow::anyhow;
 use chrono::Utc;
-use clap::{Parser, ValueEnum};
+use clap::builder::{PossibleValue, TypedValueParser};
+use clap::error::{ContextKind, ContextValue, ErrorKind};
+use clap::Parser;
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
@@ -123,20 +125,39 @@ pub struct RetryInterval {
 }
 
 impl RetryInterval {
-    pub fn new(time: &str) -> Option<Self> {
-        let values: Vec<&str> = time.split(',').collect();
+    /// Parses 1 or 2 comma-separated durations such as `500ms`, `200ms,5s` or `1s`.
+    /// Bare numbers are interpreted as seconds. A single value sets both bounds.
+    pub fn new(time: &str) -> Result<Self, String> {
+        const EXPECTED: &str = "expected 1 or 2 durations separated by comma \
+            such as '500ms', '200ms,5s' or '1s'";
+        let values: Vec<&str> = time.split(',').map(str::trim).collect();
         if values.len() > 2 {
-            return None;
+            return Err(format!(
+                "Invalid retry interval '{time}': {EXPECTED}, got {} values",
+                values.len()
+            ));
         }
-        let min = parse_duration(values.first().unwrap_or(&"")).ok()?;
-        let max = parse_duration(values.get(1).unwrap_or(&""))
-            .ok()
-            .unwrap_or(min);
+        let parse = |value: &str| {
+            if value.is_empty() {
+                return Err(format!(
+                    "Invalid retry interval '{time}': empty value, {EXPECTED}"
+                ));
+            }
+            parse_duration(value).map_err(|e| {
+                format!("Invalid retry interval '{time}': cannot parse '{value}' ({e}), {EXPECTED}")
+            })
+        };
+        let min = parse(values[0])?;
+        let max = match values.get(1) {
+            Some(value) => parse(value)?,
+            None => min,
+        };
         if min > max {
-            None
-        } else {
-            Some(RetryInterval { min, max })
+            return Err(format!(
+                "Invalid retry interval '{time}': first value cannot be bigger than second one"
+            ));
         }
+        Ok(RetryInterval { min, max })
     }
 }
 
@@ -144,47 +165,58 @@ impl FromStr for RetryInterval {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        if let Some(interval) = RetryInterval::new(s) {
-            Ok(interval)
-        } else {
-            Err(concat!(
-                "Expected 1 or 2 parts separated by comma such as '500ms' or '200ms,5s' or '1s'.",
-                " First value cannot be bigger than second one.",
-            )
-            .to_string())
-        }
+        RetryInterval::new(s)
     }
 }
 
+const DEFAULT_REQUEST_TIMEOUT: &str = "5s";
+const DEFAULT_PAGE_SIZE: &str = "501";
+const DEFAULT_RETRY_NUMBER: &str = "10";
+const DEFAULT_RETRY_INTERVAL: &str = "100ms,5s";
+const DEFAULT_VALIDATION_STRATEGY: &str = "fail-fast";
+
 #[derive(Parser, Debug, Serialize, Deserialize)]
 pub struct ConnectionConf {
     /// List of addresses to connect to.
-    #[clap(name = "addresses", default_value = "localhost")]
+    /// Can also be set in `LATTE_ADDRESSES`, which holds a single address.
+    #[clap(
+        name = "addresses",
+        default_value = "localhost",
+        env = "LATTE_ADDRESSES"
+    )]
     pub addresses: Vec<String>,
 
     #[clap(
         long("request-timeout"),
-        default_value = "5s",
+        env = "LATTE_REQUEST_TIMEOUT",
+        default_value = DEFAULT_REQUEST_TIMEOUT,
         value_name = "DURATION",
         value_parser = parse_duration
     )]
     pub request_timeout: Duration,
 
     /// Page size defines the number of rows to get in a single select-query
-    #[clap(long("page-size"), default_value = "501", value_name = "COUNT")]
+    #[clap(
+        long("page-size"),
+        env = "LATTE_PAGE_SIZE",
+        default_value = DEFAULT_PAGE_SIZE,
+        value_name = "COUNT"
+    )]
     pub page_size: NonZeroUsize,
 
     #[clap(
         long("retry-number"),
         aliases = &["retries"],
-        default_value = "10",
+        env = "LATTE_RETRY_NUMBER",
+        default_value = DEFAULT_RETRY_NUMBER,
         value_name = "COUNT"
     )]
     pub retry_number: u64,
 
     #[clap(
         long("retry-interval"),
-        default_value = "100ms,5s",
+        env = "LATTE_RETRY_INTERVAL",
+        default_value = DEFAULT_RETRY_INTERVAL,
         value_name = "MIN[,MAX]"
     )]
     pub retry_interval: RetryInterval,
@@ -196,10 +228,15 @@ pub struct ConnectionConf {
     /// - Defines the behavior of latte when 'signal_failure' context method
     ///   is executed. So, having "retry" validation strategy it will retry
     ///   whole rune function using common retry configs for count and interval.
+    /// - Having "custom:<function>" validation strategy, result rows of all 'select'
+    ///   queries are passed to the given workload script function, which must return
+    ///   `true` to accept them.
     #[clap(
         long("validation-strategy"),
+        env = "LATTE_VALIDATION_STRATEGY",
         required = false,
-        default_value = "fail-fast"
+        default_value = DEFAULT_VALIDATION_STRATEGY,
+        value_parser = ValidationStrategyParser
     )]
     pub validation_strategy: ValidationStrategy,
 
@@ -208,12 +245,204 @@ pub struct ConnectionConf {
     pub db: db_config::DbConnectionConf,
 }
 
-#[derive(Clone, Copy, Default, Debug, Eq, PartialEq, Serialize, Deserialize, ValueEnum)]
+impl ConnectionConf {
+    pub fn builder() -> ConnectionConfBuilder {
+        ConnectionConfBuilder::default()
+    }
+
+    /// Returns a builder initialized with all the settings of this configuration.
+    pub fn to_builder(&self) -> ConnectionConfBuilder {
+        ConnectionConf::builder()
+            .addresses(self.addresses.clone())
+            .request_timeout(self.request_timeout)
+            .page_size(self.page_size)
+            .retry_number(self.retry_number)
+            .retry_interval(self.retry_interval)
+            .validation_strategy(self.validation_strategy.clone())
+            .db(self.db.clone())
+    }
+}
+
+/// Builds [`ConnectionConf`] from Rust code, without going through command line parsing.
+/// The addresses and the database specific configuration are required,
+/// all other settings default to the same values as the command line options.
+#[derive(Debug, Default)]
+pub struct ConnectionConfBuilder {
+    addresses: Vec<String>,
+    request_timeout: Option<Duration>,
+    page_size: Option<NonZeroUsize>,
+    retry_number: Option<u64>,
+    retry_interval: Option<RetryInterval>,
+    validation_strategy: Option<ValidationStrategy>,
+    db: Option<db_config::DbConnectionConf>,
+}
+
+impl ConnectionConfBuilder {
+    pub fn addresses(mut self, addresses: Vec<String>) -> Self {
+        self.addresses = addresses;
+        self
+    }
+
+    pub fn request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = Some(request_timeout);
+        self
+    }
+
+    pub fn page_size(mut self, page_size: NonZeroUsize) -> Self {
+        self.page_size = Some(page_size);
+        self
+    }
+
+    pub fn retry_number(mut self, retry_number: u64) -> Self {
+        self.retry_number = Some(retry_number);
+        self
+    }
+
+    pub fn retry_interval(mut self, retry_interval: RetryInterval) -> Self {
+        self.retry_interval = Some(retry_interval);
+        self
+    }
+
+    pub fn validation_strategy(mut self, validation_strategy: ValidationStrategy) -> Self {
+        self.validation_strategy = Some(validation_strategy);
+        self
+    }
+
+    pub fn db(mut self, db: db_config::DbConnectionConf) -> Self {
+        self.db = Some(db);
+        self
+    }
+
+    pub fn build(self) -> Result<ConnectionConf, String> {
+        if self.addresses.iter().all(|address| address.is_empty()) {
+            return Err("At least one address is required".to_string());
+        }
+        let db = self
+            .db
+            .ok_or_else(|| "Database connection configuration is required".to_string())?;
+        let request_timeout = match self.request_timeout {
+            Some(request_timeout) => request_timeout,
+            None => parse_duration(DEFAULT_REQUEST_TIMEOUT)?,
+        };
+        let page_size = match self.page_size {
+            Some(page_size) => page_size,
+            None => DEFAULT_PAGE_SIZE.parse().map_err(|e| format!("{e}"))?,
+        };
+        let retry_number = match self.retry_number {
+            Some(retry_number) => retry_number,
+            None => DEFAULT_RETRY_NUMBER.parse().map_err(|e| format!("{e}"))?,
+        };
+        let retry_interval = match self.retry_interval {
+            Some(retry_interval) => retry_interval,
+            None => DEFAULT_RETRY_INTERVAL.parse()?,
+        };
+        let validation_strategy = match self.validation_strategy {
+            Some(validation_strategy) => validation_strategy,
+            None => DEFAULT_VALIDATION_STRATEGY.parse()?,
+        };
+        Ok(ConnectionConf {
+            addresses: self.addresses,
+            request_timeout,
+            page_size,
+            retry_number,
+            retry_interval,
+            validation_strategy,
+            db,
+        })
+    }
+}
+
+#[derive(Clone, Default, Debug, Eq, PartialEq, Serialize, Deserialize)]
 pub enum ValidationStrategy {
     Retry, // Retry 'select' queries if rows number is unexpected.
     #[default]
     FailFast, // Stop stress execution right after any 'select' query validation fails.
     Ignore, // Ignore validation errors - face, print, go on.
+    // Pass the result rows of all 'select' queries to the named workload function,
+    // which must return `true` to accept them. Other validation errors fail fast.
+    Custom {
+        callback: String,
+    },
+}
+
+impl FromStr for ValidationStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "retry" => Ok(ValidationStrategy::Retry),
+            "fail-fast" => Ok(ValidationStrategy::FailFast),
+            "ignore" => Ok(ValidationStrategy::Ignore),
+            _ => match s.strip_prefix("custom:") {
+                Some(callback) if !callback.is_empty() => Ok(ValidationStrategy::Custom {
+                    callback: callback.to_string(),
+                }),
+                _ => Err(format!(
+                    "Invalid validation strategy '{s}'. \
+                     Expected one of: retry, fail-fast, ignore, custom:<function>"
+                )),
+            },
+        }
+    }
+}
+
+impl Display for ValidationStrategy {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationStrategy::Retry => write!(f, "retry"),
+            ValidationStrategy::FailFast => write!(f, "fail-fast"),
+            ValidationStrategy::Ignore => write!(f, "ignore"),
+            ValidationStrategy::Custom { callback } => write!(f, "custom:{callback}"),
+        }
+    }
+}
+
+/// Parses `ValidationStrategy` from the command line.
+/// Unlike a plain `FromStr` parser, it lists the possible values in the help
+/// and in the error message about an invalid value.
+#[derive(Clone)]
+struct ValidationStrategyParser;
+
+impl ValidationStrategyParser {
+    const POSSIBLE_VALUES: [&'static str; 4] =
+        ["retry", "fail-fast", "ignore", "custom:<function>"];
+}
+
+impl TypedValueParser for ValidationStrategyParser {
+    type Value = ValidationStrategy;
+
+    fn parse_ref(
+        &self,
+        cmd: &clap::Command,
+        arg: Option<&clap::Arg>,
+        value: &std::ffi::OsStr,
+    ) -> Result<Self::Value, clap::Error> {
+        let value = value.to_string_lossy();
+        value.parse().map_err(|_| {
+            let mut err = clap::Error::new(ErrorKind::InvalidValue).with_cmd(cmd);
+            if let Some(arg) = arg {
+                err.insert(
+                    ContextKind::InvalidArg,
+                    ContextValue::String(arg.to_string()),
+                );
+            }
+            err.insert(
+                ContextKind::InvalidValue,
+                ContextValue::String(value.to_string()),
+            );
+            err.insert(
+                ContextKind::ValidValue,
+                ContextValue::Strings(Self::POSSIBLE_VALUES.map(String::from).to_vec()),
+            );
+            err
+        })
+    }
+
+    fn possible_values(&self) -> Option<Box<dyn Iterator<Item = PossibleValue> + '_>> {
+        Some(Box::new(
+            Self::POSSIBLE_VALUES.into_iter().map(PossibleValue::new),
+        ))
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -701,6 +930,11 @@ pub struct WorkloadConfig {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::Mutex;
+
+    /// Serializes the tests that parse `ConnectionConf`, because some of them set
+    /// `LATTE_*` environment variables, which are shared by the whole process.
+    static ENV_LOCK: Mutex<()> = Mutex::new(());
 
     mod parse_duration_tests {
         use super::*;
@@ -786,6 +1020,197 @@ mod tests {
         }
     }
 
+    mod validation_strategy_tests {
+        use super::*;
+
+        #[test]
+        fn parses_builtin_strategies() {
+            assert_eq!("retry".parse(), Ok(ValidationStrategy::Retry));
+            assert_eq!("fail-fast".parse(), Ok(ValidationStrategy::FailFast));
+            assert_eq!("ignore".parse(), Ok(ValidationStrategy::Ignore));
+        }
+
+        #[test]
+        fn parses_custom_strategy() {
+            let strategy: ValidationStrategy = "custom:check_rows".parse().unwrap();
+            assert_eq!(
+                strategy,
+                ValidationStrategy::Custom {
+                    callback: "check_rows".to_string()
+                }
+            );
+            assert_eq!(strategy.to_string(), "custom:check_rows");
+        }
+
+        #[test]
+        fn rejects_invalid_strategy() {
+            assert!("custom:".parse::<ValidationStrategy>().is_err());
+            assert!("sometimes".parse::<ValidationStrategy>().is_err());
+        }
+
+        #[test]
+        fn command_line_accepts_custom_strategy() {
+            let conf = ConnectionConf::try_parse_from([
+                "latte",
+                "--validation-strategy",
+                "custom:check_rows",
+            ])
+            .unwrap();
+            assert_eq!(
+                conf.validation_strategy,
+                ValidationStrategy::Custom {
+                    callback: "check_rows".to_string()
+                }
+            );
+        }
+
+        #[test]
+        fn help_lists_possible_values() {
+            use clap::CommandFactory;
+            let help = ConnectionConf::command().render_long_help().to_string();
+            assert!(
+                help.contains("[possible values: retry, fail-fast, ignore, custom:<function>]"),
+                "{help}"
+            );
+        }
+
+        #[test]
+        fn invalid_value_error_lists_possible_values() {
+            let err =
+                ConnectionConf::try_parse_from(["latte", "--validation-strategy", "sometimes"])
+                    .unwrap_err();
+            assert_eq!(err.kind(), clap::error::ErrorKind::InvalidValue);
+            let message = err.to_string();
+            assert!(message.contains("'sometimes'"), "{message}");
+            assert!(message.contains("--validation-strategy"), "{message}");
+            assert!(
+                message.contains("[possible values: retry, fail-fast, ignore, custom:<function>]"),
+                "{message}"
+            );
+        }
+    }
+
+    mod connection_conf_builder_tests {
+        use super::*;
+
+        fn db_conf() -> db_config::DbConnectionConf {
+            db_config::DbConnectionConf::try_parse_from(["latte"]).unwrap()
+        }
+
+        #[test]
+        fn defaults_match_command_line() {
+            let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let built = ConnectionConf::builder()
+                .addresses(vec!["localhost".to_string()])
+                .db(db_conf())
+                .build()
+                .unwrap();
+            let parsed = ConnectionConf::try_parse_from(["latte"]).unwrap();
+            assert_eq!(built.addresses, parsed.addresses);
+            assert_eq!(built.request_timeout, parsed.request_timeout);
+            assert_eq!(built.page_size, parsed.page_size);
+            assert_eq!(built.retry_number, parsed.retry_number);
+            assert_eq!(built.retry_interval, parsed.retry_interval);
+            assert_eq!(built.validation_strategy, parsed.validation_strategy);
+        }
+
+        #[test]
+        fn setters_override_defaults() {
+            let conf = ConnectionConf::builder()
+                .addresses(vec!["a".to_string(), "b".to_string()])
+                .retry_number(3)
+                .validation_strategy(ValidationStrategy::Ignore)
+                .db(db_conf())
+                .build()
+                .unwrap();
+            assert_eq!(conf.addresses, vec!["a", "b"]);
+            assert_eq!(conf.retry_number, 3);
+            assert_eq!(conf.validation_strategy, ValidationStrategy::Ignore);
+        }
+
+        #[test]
+        fn to_builder_keeps_settings() {
+            let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let parsed =
+                ConnectionConf::try_parse_from(["latte", "host1", "--retry-number", "2"]).unwrap();
+            let rebuilt = parsed.to_builder().build().unwrap();
+            assert_eq!(rebuilt.addresses, vec!["host1"]);
+            assert_eq!(rebuilt.retry_number, 2);
+            assert_eq!(rebuilt.page_size, parsed.page_size);
+        }
+
+        #[test]
+        fn missing_addresses_is_error() {
+            assert!(ConnectionConf::builder().db(db_conf()).build().is_err());
+            assert!(ConnectionConf::builder()
+                .addresses(vec![String::new()])
+                .db(db_conf())
+                .build()
+                .is_err());
+        }
+
+        #[test]
+        fn missing_db_conf_is_error() {
+            assert!(ConnectionConf::builder()
+                .addresses(vec!["localhost".to_string()])
+                .build()
+                .is_err());
+        }
+    }
+
+    mod connection_conf_env_tests {
+        use super::*;
+
+        const VARS: &[(&str, &str)] = &[
+            ("LATTE_ADDRESSES", "host1"),
+            ("LATTE_REQUEST_TIMEOUT", "7s"),
+            ("LATTE_PAGE_SIZE", "100"),
+            ("LATTE_RETRY_NUMBER", "3"),
+            ("LATTE_RETRY_INTERVAL", "1s,2s"),
+            ("LATTE_VALIDATION_STRATEGY", "ignore"),
+        ];
+
+        /// Parses the command line with the `LATTE_*` variables set to the values in `VARS`.
+        fn parse_with_env(args: &[&str]) -> ConnectionConf {
+            let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            for (name, value) in VARS {
+                std::env::set_var(name, value);
+            }
+            let conf = ConnectionConf::try_parse_from(args);
+            for (name, _) in VARS {
+                std::env::remove_var(name);
+            }
+            conf.unwrap()
+        }
+
+        #[test]
+        fn options_are_read_from_env() {
+            let conf = parse_with_env(&["latte"]);
+            assert_eq!(conf.addresses, vec!["host1"]);
+            assert_eq!(conf.request_timeout, Duration::from_secs(7));
+            assert_eq!(conf.page_size.get(), 100);
+            assert_eq!(conf.retry_number, 3);
+            assert_eq!(conf.retry_interval.min, Duration::from_secs(1));
+            assert_eq!(conf.retry_interval.max, Duration::from_secs(2));
+            assert_eq!(conf.validation_strategy, ValidationStrategy::Ignore);
+        }
+
+        #[test]
+        fn command_line_overrides_env() {
+            let conf = parse_with_env(&["latte", "host2", "host3", "--retry-number", "5"]);
+            assert_eq!(conf.addresses, vec!["host2", "host3"]);
+            assert_eq!(conf.retry_number, 5);
+            assert_eq!(conf.page_size.get(), 100);
+        }
+
+        #[test]
+        fn addresses_are_not_split_on_commas() {
+            let _guard = ENV_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            let conf = ConnectionConf::try_parse_from(["latte", "host1,host2"]).unwrap();
+            assert_eq!(conf.addresses, vec!["host1,host2"]);
+        }
+    }
+
     mod retry_interval_tests {
         use super::*;
 
@@ -818,18 +1243,26 @@ mod tests {
         }
 
         #[test]
-        fn min_greater_than_max_returns_none() {
-            assert!(RetryInterval::new("10s,2s").is_none());
+        fn min_greater_than_max_is_rejected() {
+            assert!(RetryInterval::new("10s,2s").is_err());
+        }
+
+        #[test]
+        fn too_many_values_are_rejected() {
+            assert!(RetryInterval::new("1s,2s,3s").is_err());
         }
 
         #[test]
-        fn too_many_values_returns_none() {
-            assert!(RetryInterval::new("1s,2s,3s").is_none());
+        fn empty_string_is_rejected() {
+            let err = RetryInterval::new("").unwrap_err();
+            assert!(err.contains("empty value"), "{err}");
         }
 
         #[test]
-        fn empty_string_returns_none() {
-            assert!(RetryInterval::new("").is_none());
+        fn non_numeric_value_is_rejected() {
+            let err = RetryInterval::new("1s,abc").unwrap_err();
+            assert!(err.contains("cannot parse 'abc'"), "{err}");
+            assert!(RetryInterval::new("fast").is_err());
         }
     }
 }
@@ -1,6 +1,7 @@
 use crate::scripting::db_error::DbError;
 use hdrhistogram::serialization::interval_log::IntervalLogWriterError;
 use hdrhistogram::serialization::V2DeflateSerializeError;
+use hdrhistogram::SubtractionError;
 use rune::alloc;
 use rune::runtime::{AccessError, RuntimeError, VmError};
 use std::path::PathBuf;
@@ -41,6 +42,9 @@ pub enum LatteError {
     #[error("Error writing HDR log: {0}")]
     HdrLogWrite(#[from] IntervalLogWriterError<V2DeflateSerializeError>),
 
+    #[error("Failed to compute stats of the sample: {0}")]
+    StatsSubtraction(#[from] SubtractionError),
+
     #[error("Failed to launch external editor {0}: {1}")]
     ExternalEditorLaunch(String, std::io::Error),
 
@@ -176,7 +176,7 @@ async fn run_stream(
 
     workload.reset(Instant::now());
     while let Some((stats, errors)) = stats_stream.next().await {
-        if out.send(Ok(stats)).await.is_err() {
+        if out.send(stats).await.is_err() {
             return;
         }
         for err in errors {
@@ -350,7 +350,7 @@ pub async fn par_execute(
         }
 
         let aggregate = stats.record(&partial_stats);
-        if sampling.is_bounded() {
+        if let Some(aggregate) = aggregate.filter(|_| sampling.is_bounded()) {
             progress.set_visible(false);
             println!("{aggregate}");
             progress.set_visible(show_progress);
@@ -415,6 +415,114 @@ mod test {
         assert_eq!(s.collect::<Vec<_>>().await, vec![Ok(1), Ok(2), Err(3)])
     }
 
+    mod measurement_window_test {
+        use crate::config::{Interval, RetryInterval, ValidationStrategy};
+        use crate::exec::workload::{FnRef, Program, Workload};
+        use crate::exec::{par_execute, ExecutionOptions};
+        use crate::scripting::context::Context;
+        use rune::Source;
+        use std::collections::HashMap;
+        use std::num::NonZeroUsize;
+        use std::time::Duration;
+
+        #[cfg(feature = "cql")]
+        fn test_context() -> Context {
+            Context::new(
+                None,
+                501,
+                "dc".to_string(),
+                "rack".to_string(),
+                1,
+                RetryInterval::new("1,2").expect("failed to parse retry interval"),
+                ValidationStrategy::Ignore,
+            )
+        }
+
+        #[cfg(all(feature = "alternator", not(feature = "cql")))]
+        fn test_context() -> Context {
+            Context::new(
+                None,
+                1,
+                RetryInterval::new("1,2").expect("failed to parse retry interval"),
+                ValidationStrategy::Ignore,
+                501,
+            )
+        }
+
+        /// Runs 100 cycles in samples of 10 cycles. Each cycle records its number as a metric.
+        async fn run_workload(script: &str) -> crate::BenchmarkStats {
+            let program = Program::new(Source::memory(script).unwrap(), HashMap::new()).unwrap();
+            let workload = Workload::new(test_context(), program, &[(FnRef::new("run"), 1.0)]);
+            let options = ExecutionOptions {
+                duration: Interval::Count(100),
+                cycle_range: (0, i64::MAX),
+                rate: None,
+                rate_sine_amplitude: None,
+                rate_sine_period: Duration::from_secs(1),
+                threads: NonZeroUsize::new(1).unwrap(),
+                concurrency: NonZeroUsize::new(1).unwrap(),
+            };
+            par_execute(
+                "test",
+                &options,
+                Interval::Count(10),
+                workload,
+                false,
+                true,
+                &mut None,
+            )
+            .await
+            .unwrap()
+        }
+
+        fn recorded_cycles(stats: &crate::BenchmarkStats) -> (u64, f64) {
+            let distribution = &stats.custom_metrics["cycle"].distribution;
+            (distribution.histogram.0.len(), distribution.mean.value)
+        }
+
+        #[tokio::test]
+        async fn samples_outside_measurement_window_are_dropped() {
+            let stats = run_workload(
+                r#"
+                pub async fn run(ctx, i) {
+                    if i == 50 {
+                        ctx.begin_measurement();
+                    }
+                    ctx.record_metric("cycle", i as f64);
+                    if i == 79 {
+                        ctx.end_measurement();
+                    }
+                }
+                "#,
+            )
+            .await;
+            assert_eq!(stats.cycle_count, 30);
+            assert_eq!(stats.log.len(), 3);
+            let (count, mean) = recorded_cycles(&stats);
+            assert_eq!(count, 30);
+            assert!((mean - 64.5).abs() < 1e-3, "mean = {mean}");
+        }
+
+        #[tokio::test]
+        async fn samples_before_discarding_stats_are_dropped() {
+            let stats = run_workload(
+                r#"
+                pub async fn run(ctx, i) {
+                    if i == 20 {
+                        ctx.discard_stats();
+                    }
+                    ctx.record_metric("cycle", i as f64);
+                }
+                "#,
+            )
+            .await;
+            assert_eq!(stats.cycle_count, 80);
+            let (count, mean) = recorded_cycles(&stats);
+            assert_eq!(count, 80);
+            assert!((mean - 59.5).abs() < 1e-3, "mean = {mean}");
+        }
+    }
+
     mod sinusoidal_interval_stream_test {
         use crate::exec::SinusoidalIntervalStream;
         use futures::StreamExt;
@@ -21,7 +21,7 @@ use rand::{Rng, SeedableRng};
 use rune::alloc::clone::TryClone;
 use rune::compile::meta::Kind;
 use rune::compile::{CompileVisitor, MetaError, MetaRef};
-use rune::runtime::{Args, RuntimeContext, RuntimeError, VmError};
+use rune::runtime::{Args, RuntimeContext, RuntimeError, SyncFunction, VmError};
 use rune::termcolor::{ColorChoice, StandardStream};
 use rune::{Diagnostics, Source, Sources, ToValue, Unit, Value, Vm};
 use serde::{Deserialize, Serialize};
@@ -234,6 +234,17 @@ impl Program {
         self.meta.functions.contains(function)
     }
 
+    /// Looks up a script function by name, so it can be called outside of the program's VM,
+    /// e.g. from database calls running on any thread.
+    pub fn sync_function(&self, name: &str) -> Result<SyncFunction, LatteError> {
+        if !self.has_function(&FnRef::new(name)) {
+            return Err(LatteError::Configuration(format!(
+                "Function {name} not found in the workload script"
+            )));
+        }
+        Ok(self.vm().lookup_function([name])?.into_sync()?)
+    }
+
     /// Calls the script's `init` function.
     /// Called once at the beginning of the benchmark.
     /// Typically used to prepare statements.
@@ -449,7 +460,7 @@ impl Workload {
                         break;
                     }
                     Err(LatteError::Database(boxed_err))
-                        if matches!(boxed_err.0, DbErrorKind::Overloaded(..)) =>
+                        if matches!(boxed_err.root_cause(), DbErrorKind::Overloaded(..)) =>
                     {
                         // don't stop on overload errors;
                         // they are being counted by the context stats anyways
@@ -461,15 +472,20 @@ impl Workload {
                     //       May be used for data validation and other needs which require re-run
                     //       of a rune function.
                     Err(LatteError::Database(boxed_err))
-                        if matches!(boxed_err.0, DbErrorKind::CustomError(_)) =>
+                        if matches!(boxed_err.root_cause(), DbErrorKind::CustomError(_)) =>
                     {
                         state.operation_failed(function, duration);
                         match &self.context.validation_strategy {
                             ValidationStrategy::Retry => {
                                 current_err = *boxed_err;
                             }
-                            ValidationStrategy::FailFast => {
-                                return Err(LatteError::Database(boxed_err))
+                            ValidationStrategy::FailFast | ValidationStrategy::Custom { .. } => {
+                                self.context
+                                    .stats
+                                    .try_lock()
+                                    .unwrap()
+                                    .record_error(boxed_err.category());
+                                return Err(LatteError::Database(boxed_err));
                             }
                             ValidationStrategy::Ignore => {
                                 current_err = *boxed_err;
@@ -510,17 +526,17 @@ impl Workload {
         self.context.reset();
     }
 
-    /// Returns statistics of the operations invoked by this workload so far.
-    /// Resets the internal statistic counters.
-    pub fn take_stats(&self, end_time: Instant) -> WorkloadStats {
+    /// Returns statistics of the operations invoked by this workload
+    /// since the previous call.
+    pub fn take_stats(&self, end_time: Instant) -> Result<WorkloadStats, LatteError> {
         let state = self.state.try_lock().unwrap().take(end_time);
         let result = WorkloadStats {
             start_time: state.start_time,
             end_time,
             function_stats: state.fn_stats.clone(),
-            session_stats: self.context().take_session_stats(),
+            session_stats: self.context().take_session_stats()?,
         };
-        result
+        Ok(result)
     }
 }
 
@@ -544,3 +560,53 @@ impl FunctionRouter {
         &self.functions[self.selector.sample(rng)]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RetryInterval;
+
+    #[cfg(feature = "cql")]
+    fn test_context(validation_strategy: ValidationStrategy) -> Context {
+        Context::new(
+            None,
+            501,
+            "dc".to_string(),
+            "rack".to_string(),
+            1,
+            RetryInterval::new("1,2").expect("failed to parse retry interval"),
+            validation_strategy,
+        )
+    }
+
+    #[cfg(all(feature = "alternator", not(feature = "cql")))]
+    fn test_context(validation_strategy: ValidationStrategy) -> Context {
+        Context::new(
+            None,
+            1,
+            RetryInterval::new("1,2").expect("failed to parse retry interval"),
+            validation_strategy,
+            501,
+        )
+    }
+
+    #[tokio::test]
+    async fn failing_fast_records_the_error() {
+        let script = r#"
+            pub async fn run(ctx, i) {
+                ctx.signal_failure("rejected").await?;
+            }
+        "#;
+        let program = Program::new(Source::memory(script).unwrap(), HashMap::new()).unwrap();
+        let workload = Workload::new(
+            test_context(ValidationStrategy::FailFast),
+            program,
+            &[(FnRef::new("run"), 1.0)],
+        );
+        assert!(workload.run(0, Instant::now()).await.is_err());
+
+        let stats = workload.take_stats(Instant::now()).unwrap().session_stats;
+        assert_eq!(stats.req_error_count, 1);
+        assert_eq!(stats.errors_by_category.other, 1);
+    }
+}
@@ -3,6 +3,7 @@ use std::fs::File;
 use std::io::{stdout, Write};
 use std::path::{Path, PathBuf};
 use std::process::exit;
+use std::sync::Arc;
 use std::time::{Duration, SystemTime};
 use std::{env, fs};
 
@@ -24,7 +25,7 @@ use walkdir::WalkDir;
 
 use crate::config::{
     AppConfig, Command, ConnectionConf, EditCommand, HdrCommand, Interval, ListCommand,
-    LoadCommand, SchemaCommand, ShowCommand, VersionCommand,
+    LoadCommand, SchemaCommand, ShowCommand, ValidationStrategy, VersionCommand,
 };
 use crate::error::{LatteError, Result};
 use crate::exec::{par_execute, ExecutionOptions};
@@ -133,11 +134,42 @@ async fn connect(conf: &ConnectionConf) -> Result<(Context, Option<ClusterInfo>)
     Ok((session, cluster_info))
 }
 
+/// Resolves the workload function used by the `custom:<function>` validation strategy.
+fn set_validation_callback(session: &mut Context, program: &Program) -> Result<()> {
+    if let ValidationStrategy::Custom { callback } = &session.validation_strategy {
+        session.validation_callback = Some(Arc::new(program.sync_function(callback)?));
+    }
+    Ok(())
+}
+
+/// Compares the current cluster topology with the one seen at connection time
+/// and prints a warning if it has changed.
+async fn check_topology(session: &Context, initial: Option<&ClusterInfo>, phase: &str) {
+    let Some(initial) = initial else {
+        return;
+    };
+    match session.cluster_info().await {
+        Ok(Some(current)) if initial.has_changed(&current) => {
+            eprintln!(
+                "warning: Cluster topology changed before {phase}: \
+                 {} nodes {:?} -> {} nodes {:?}",
+                initial.node_count,
+                initial.datacenter_map,
+                current.node_count,
+                current.datacenter_map
+            );
+        }
+        Ok(_) => {}
+        Err(e) => eprintln!("warning: Failed to check cluster topology before {phase}: {e}"),
+    }
+}
+
 /// Runs the `schema` function of the workload script.
 /// Exits with error if the `schema` function is not present or fails.
 async fn schema(conf: SchemaCommand) -> Result<()> {
     let mut program = load_workload_script(&conf.workload, &conf.params)?;
-    let (session, _) = connect(&conf.connection).await?;
+    let (mut session, _) = connect(&conf.connection).await?;
+    set_validation_callback(&mut session, &program)?;
     if !program.has_schema() {
         eprintln!("error: Function `schema` not found in the workload script.");
         exit(255);
@@ -155,7 +187,8 @@ async fn schema(conf: SchemaCommand) -> Result<()> {
 /// Exits with error if the `load` function is not present or fails.
 async fn load(conf: LoadCommand) -> Result<()> {
     let mut program = load_workload_script(&conf.workload, &conf.params)?;
-    let (session, _) = connect(&conf.connection).await?;
+    let (mut session, _) = connect(&conf.connection).await?;
+    set_validation_callback(&mut session, &program)?;
 
     if program.has_prepare() {
         eprintln!("info: Preparing...");
@@ -236,7 +269,8 @@ async fn run(conf: RunCommand) -> Result<()> {
         functions.push((function, f.weight))
     }
 
-    let (session, cluster_info) = connect(&conf.connection).await?;
+    let (mut session, cluster_info) = connect(&conf.connection).await?;
+    set_validation_callback(&mut session, &program)?;
 
     // NOTE: Add info about the target rune functions to the context
     //       for the more flexible tweaking of the 'prepare' rune function.
@@ -253,9 +287,9 @@ async fn run(conf: RunCommand) -> Result<()> {
         }
     }
 
-    if let Some(cluster_info) = cluster_info {
-        conf.cluster_name = Some(cluster_info.name);
-        conf.db_version = Some(cluster_info.db_version);
+    if let Some(cluster_info) = &cluster_info {
+        conf.cluster_name = Some(cluster_info.name.clone());
+        conf.db_version = Some(cluster_info.db_version.clone());
     }
 
     if program.has_prepare() {
@@ -268,6 +302,7 @@ async fn run(conf: RunCommand) -> Result<()> {
 
     let runner = Workload::new(session.clone()?, program.clone(), &functions);
     if conf.warmup_duration.is_not_zero() {
+        check_topology(&session, cluster_info.as_ref(), "warmup").await;
         eprintln!("info: Warming up...");
         let warmup_options = ExecutionOptions {
             duration: conf.warmup_duration,
@@ -290,6 +325,7 @@ async fn run(conf: RunCommand) -> Result<()> {
         .await?;
     }
 
+    check_topology(&session, cluster_info.as_ref(), "benchmark").await;
     eprintln!("info: Running benchmark...");
 
     println!(
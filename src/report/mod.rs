@@ -717,6 +717,9 @@ impl Display for BenchmarkCmp<'_> {
             self.line("└─", "ret/req", |s| {
                 Quantity::from(s.request_retry_per_request).with_precision(5)
             }),
+            self.line("Validation failures", "op", |s| {
+                Quantity::from(s.validation_failure_count)
+            }),
             self.line("Rows", "row", |s| Quantity::from(s.row_count)),
             self.line("└─", "row/req", |s| {
                 Quantity::from(s.row_count_per_req).with_precision(1)
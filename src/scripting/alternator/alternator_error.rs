@@ -1,8 +1,26 @@
+use crate::scripting::retry_error::RetryError;
+use crate::stats::session::ErrorCategory;
 use aws_sdk_dynamodb::error::{ProvideErrorMetadata, SdkError};
 use rune::alloc::fmt::TryWrite;
 use rune::runtime::{VmError, VmResult};
 use rune::{vm_write, Any};
+use std::convert::Infallible;
 use std::fmt::{Debug, Display, Formatter};
+use std::str::FromStr;
+
+/// Error code returned when a table's provisioned throughput is exceeded.
+const THROTTLING_ERROR_CODE: &str = "ProvisionedThroughputExceededException";
+
+/// Error code returned when the condition expression of a write is not satisfied.
+const CONDITIONAL_CHECK_FAILED_CODE: &str = "ConditionalCheckFailedException";
+const CONDITIONAL_CHECK_FAILED_MESSAGE: &str = "Conditional check failed";
+/// Error code returned when a transaction is canceled, and the reason code of its
+/// items whose condition was not satisfied.
+const TRANSACTION_CANCELED_CODE: &str = "TransactionCanceledException";
+const CONDITIONAL_CHECK_FAILED_REASON: &str = "ConditionalCheckFailed";
+/// Key of the error metadata field listing the comma-separated cancellation reason
+/// codes of a transaction, one per item.
+pub(super) const CANCELLATION_REASONS_KEY: &str = "cancellation_reasons";
 
 #[derive(Any, Debug)]
 pub struct AlternatorError(pub AlternatorErrorKind);
@@ -10,15 +28,28 @@ pub struct AlternatorError(pub AlternatorErrorKind);
 #[derive(Debug)]
 pub enum AlternatorErrorKind {
     FailedToConnect(String, String),
-    QueryRetriesExceeded(String),
+    QueryRetriesExceeded(u64),
     Overloaded(String),
+    /// Transaction was canceled; holds the cancellation reason code of each of its items.
+    TransactionCanceled(Vec<String>),
+    /// Request rate exceeded the provisioned throughput of a table.
+    /// The table is known only for requests addressing a single table.
+    Throttled {
+        table: Option<String>,
+        message: String,
+    },
     PartitionRowPresetNotFound(String),
+    InvalidPartitionRowPreset(String),
     CustomError(String),
     Error(String),
     SdkError(String),
     BadInput(String),
     ConversionError(String),
     ValidationError(String),
+    ContextualError {
+        context: String,
+        cause: Box<AlternatorErrorKind>,
+    },
 }
 
 impl AlternatorError {
@@ -26,10 +57,98 @@ impl AlternatorError {
         AlternatorError(kind)
     }
 
+    /// Wraps a plain error message.
+    pub fn from_str(message: impl Into<String>) -> AlternatorError {
+        AlternatorError(AlternatorErrorKind::Error(message.into()))
+    }
+
     pub fn query_retries_exceeded(retry_number: u64) -> AlternatorError {
-        AlternatorError(AlternatorErrorKind::QueryRetriesExceeded(format!(
-            "Max retry attempts ({retry_number}) reached"
-        )))
+        AlternatorError(AlternatorErrorKind::QueryRetriesExceeded(retry_number))
+    }
+
+    /// Annotates the error with information about the operation that failed.
+    /// The context is prepended to the original error message.
+    pub fn with_context(self, context: impl Into<String>) -> Self {
+        AlternatorError(AlternatorErrorKind::ContextualError {
+            context: context.into(),
+            cause: Box::new(self.0),
+        })
+    }
+
+    /// Annotates the error with the expression of the request that failed.
+    pub fn with_query(self, expression: &str) -> Self {
+        self.with_context(format!("Error executing '{expression}'"))
+    }
+
+    /// Returns the kind of the original error, skipping any context annotations.
+    pub fn root_cause(&self) -> &AlternatorErrorKind {
+        let mut kind = &self.0;
+        while let AlternatorErrorKind::ContextualError { cause, .. } = kind {
+            kind = cause;
+        }
+        kind
+    }
+
+    /// Sets the table of a throttling error converted from an SDK error,
+    /// which does not say which table was throttled.
+    pub fn with_throttled_table(mut self, table_name: &str) -> Self {
+        if let AlternatorErrorKind::Throttled {
+            table: table @ None,
+            ..
+        } = &mut self.0
+        {
+            *table = Some(table_name.to_string());
+        }
+        self
+    }
+
+    /// Returns true if the request was rejected because the table's
+    /// provisioned throughput was exceeded.
+    pub fn is_throttled(&self) -> bool {
+        matches!(self.root_cause(), AlternatorErrorKind::Throttled { .. })
+    }
+
+    /// Returns true if a write was rejected because its condition expression
+    /// was not satisfied.
+    pub fn is_conditional_check_failed(&self) -> bool {
+        match self.root_cause() {
+            AlternatorErrorKind::ValidationError(s) => {
+                s.starts_with(CONDITIONAL_CHECK_FAILED_MESSAGE)
+            }
+            AlternatorErrorKind::TransactionCanceled(reasons) => {
+                reasons.iter().any(|r| r == CONDITIONAL_CHECK_FAILED_REASON)
+            }
+            _ => false,
+        }
+    }
+
+    /// Classifies the error for the per-category error counts.
+    pub fn category(&self) -> ErrorCategory {
+        match self.root_cause() {
+            AlternatorErrorKind::Overloaded(_) | AlternatorErrorKind::Throttled { .. } => {
+                ErrorCategory::Overloaded
+            }
+            AlternatorErrorKind::ValidationError(_) => ErrorCategory::Validation,
+            _ => ErrorCategory::Other,
+        }
+    }
+
+    /// Returns the retry-oriented view of the error for inspection in workload scripts.
+    pub fn to_retry_error(&self) -> RetryError {
+        let kind = self.root_cause();
+        let attempts = match kind {
+            AlternatorErrorKind::QueryRetriesExceeded(n) => *n,
+            _ => 0,
+        };
+        RetryError::new(
+            attempts,
+            false,
+            matches!(
+                kind,
+                AlternatorErrorKind::Overloaded(_) | AlternatorErrorKind::Throttled { .. }
+            ),
+            self.to_string(),
+        )
     }
 
     #[rune::function(protocol = DISPLAY_FMT)]
@@ -41,27 +160,62 @@ impl AlternatorError {
 
 impl Display for AlternatorError {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
-        match &self.0 {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl Display for AlternatorErrorKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
             AlternatorErrorKind::FailedToConnect(addr, e) => {
                 write!(f, "Failed to connect to Alternator at {}: {}", addr, e)
             }
-            AlternatorErrorKind::QueryRetriesExceeded(s) => write!(f, "QueryRetriesExceeded: {s}"),
+            AlternatorErrorKind::QueryRetriesExceeded(n) => {
+                write!(f, "QueryRetriesExceeded: Max retry attempts ({n}) reached")
+            }
             AlternatorErrorKind::Overloaded(s) => write!(f, "Overloaded: {s}"),
+            AlternatorErrorKind::TransactionCanceled(reasons) => write!(
+                f,
+                "TransactionCanceled: cancellation reasons [{}]",
+                reasons.join(", ")
+            ),
+            AlternatorErrorKind::Throttled {
+                table: Some(table),
+                message,
+            } => write!(f, "Throttled: table '{table}': {message}"),
+            AlternatorErrorKind::Throttled {
+                table: None,
+                message,
+            } => write!(f, "Throttled: {message}"),
             AlternatorErrorKind::CustomError(s) => write!(f, "{s}"),
             AlternatorErrorKind::Error(s) => write!(f, "{s}"),
             AlternatorErrorKind::PartitionRowPresetNotFound(s) => {
                 write!(f, "Partition row preset not found: {s}")
             }
+            AlternatorErrorKind::InvalidPartitionRowPreset(s) => {
+                write!(f, "Invalid partition row preset: {s}")
+            }
             AlternatorErrorKind::BadInput(s) => write!(f, "BadInput: {s}"),
             AlternatorErrorKind::SdkError(s) => write!(f, "SdkError: {s}"),
             AlternatorErrorKind::ConversionError(s) => write!(f, "ConversionError: {s}"),
             AlternatorErrorKind::ValidationError(s) => write!(f, "ValidationError: {s}"),
+            AlternatorErrorKind::ContextualError { context, cause } => {
+                write!(f, "{context}: {cause}")
+            }
         }
     }
 }
 
 impl std::error::Error for AlternatorError {}
 
+impl FromStr for AlternatorError {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(AlternatorError::from_str(s))
+    }
+}
+
 impl From<rune::runtime::AccessError> for AlternatorError {
     fn from(error: rune::runtime::AccessError) -> Self {
         AlternatorError::new(AlternatorErrorKind::Error(error.to_string()))
@@ -85,6 +239,26 @@ where
     E: ProvideErrorMetadata,
 {
     fn from(err: SdkError<E, R>) -> Self {
+        let meta = err.meta();
+        if meta.code() == Some(THROTTLING_ERROR_CODE) {
+            return AlternatorError::new(AlternatorErrorKind::Throttled {
+                table: None,
+                message: err.message().unwrap_or("No message").to_string(),
+            });
+        }
+        if meta.code() == Some(TRANSACTION_CANCELED_CODE) {
+            let reasons = meta
+                .extra(CANCELLATION_REASONS_KEY)
+                .map(|r| r.split(',').map(str::to_string).collect())
+                .unwrap_or_default();
+            return AlternatorError::new(AlternatorErrorKind::TransactionCanceled(reasons));
+        }
+        if meta.code() == Some(CONDITIONAL_CHECK_FAILED_CODE) {
+            return AlternatorError::new(AlternatorErrorKind::ValidationError(format!(
+                "{CONDITIONAL_CHECK_FAILED_MESSAGE}: {}",
+                err.message().unwrap_or("No message")
+            )));
+        }
         AlternatorError::new(AlternatorErrorKind::SdkError(
             err.message().unwrap_or("No message").to_string(),
         ))
@@ -111,3 +285,136 @@ impl From<rune::alloc::Error> for AlternatorError {
 
 pub type DbError = AlternatorError;
 pub type DbErrorKind = AlternatorErrorKind;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn with_context_prepends_message() {
+        let err = AlternatorError(AlternatorErrorKind::BadInput("boom".to_string()))
+            .with_context("inner op")
+            .with_context("outer op");
+        assert_eq!(err.to_string(), "outer op: inner op: BadInput: boom");
+        assert!(matches!(err.root_cause(), AlternatorErrorKind::BadInput(_)));
+    }
+
+    #[test]
+    fn with_query_shows_expression() {
+        let err = AlternatorError::query_retries_exceeded(2).with_query("pk = :pk");
+        assert_eq!(
+            err.to_string(),
+            "Error executing 'pk = :pk': QueryRetriesExceeded: Max retry attempts (2) reached"
+        );
+        assert!(matches!(
+            err.root_cause(),
+            AlternatorErrorKind::QueryRetriesExceeded(2)
+        ));
+    }
+
+    #[test]
+    fn throttling_error_is_parsed() {
+        use aws_sdk_dynamodb::error::ErrorMetadata;
+
+        let meta = ErrorMetadata::builder()
+            .code(THROTTLING_ERROR_CODE)
+            .message("Throughput exceeds the current capacity of your table")
+            .build();
+        let err = AlternatorError::from(SdkError::service_error(meta, ()));
+        assert!(err.is_throttled());
+        assert!(err.to_retry_error().overloaded);
+        assert_eq!(err.category(), ErrorCategory::Overloaded);
+        assert!(matches!(
+            err.root_cause(),
+            AlternatorErrorKind::Throttled { table: None, .. }
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Throttled: Throughput exceeds the current capacity of your table"
+        );
+
+        let err = err
+            .with_throttled_table("users")
+            .with_throttled_table("other");
+        assert!(matches!(
+            err.root_cause(),
+            AlternatorErrorKind::Throttled { table: Some(t), .. } if t == "users"
+        ));
+        assert_eq!(
+            err.to_string(),
+            "Throttled: table 'users': Throughput exceeds the current capacity of your table"
+        );
+        assert!(err.with_context("put").is_throttled());
+
+        let err = AlternatorError::from_str("boom").with_throttled_table("users");
+        assert_eq!(err.to_string(), "boom");
+
+        let meta = ErrorMetadata::builder()
+            .code("ValidationException")
+            .message("bad key")
+            .build();
+        let err = AlternatorError::from(SdkError::service_error(meta, ()));
+        assert!(!err.is_throttled());
+        assert_eq!(err.category(), ErrorCategory::Other);
+        assert_eq!(err.to_string(), "SdkError: bad key");
+    }
+
+    #[test]
+    fn conditional_check_failure_is_parsed() {
+        use aws_sdk_dynamodb::error::ErrorMetadata;
+
+        let meta = ErrorMetadata::builder()
+            .code(CONDITIONAL_CHECK_FAILED_CODE)
+            .message("The conditional request failed")
+            .build();
+        let err = AlternatorError::from(SdkError::service_error(meta, ()));
+        assert!(err.is_conditional_check_failed());
+        assert_eq!(err.category(), ErrorCategory::Validation);
+        assert_eq!(
+            err.to_string(),
+            "ValidationError: Conditional check failed: The conditional request failed"
+        );
+        assert!(err.with_context("delete").is_conditional_check_failed());
+
+        let err = AlternatorError::new(AlternatorErrorKind::ValidationError(
+            "query returned 0 items".to_string(),
+        ));
+        assert!(!err.is_conditional_check_failed());
+    }
+
+    #[test]
+    fn transaction_cancellation_reasons_are_parsed() {
+        use aws_sdk_dynamodb::error::ErrorMetadata;
+
+        let meta = ErrorMetadata::builder()
+            .code(TRANSACTION_CANCELED_CODE)
+            .custom(CANCELLATION_REASONS_KEY, "None,TransactionConflict")
+            .build();
+        let err = AlternatorError::from(SdkError::service_error(meta, ()));
+        assert!(matches!(
+            err.root_cause(),
+            AlternatorErrorKind::TransactionCanceled(reasons) if reasons == &["None", "TransactionConflict"]
+        ));
+        assert!(!err.is_conditional_check_failed());
+        assert_eq!(
+            err.to_string(),
+            "TransactionCanceled: cancellation reasons [None, TransactionConflict]"
+        );
+
+        let meta = ErrorMetadata::builder()
+            .code(TRANSACTION_CANCELED_CODE)
+            .custom(CANCELLATION_REASONS_KEY, "ConditionalCheckFailed,None")
+            .build();
+        let err = AlternatorError::from(SdkError::service_error(meta, ()));
+        assert!(err.is_conditional_check_failed());
+    }
+
+    #[test]
+    fn from_str_wraps_message() {
+        let err = AlternatorError::from_str(format!("bad {}", "thing"));
+        assert!(matches!(err.0, AlternatorErrorKind::Error(_)));
+        assert_eq!(err.to_string(), "bad thing");
+        let err: AlternatorError = "bad thing".parse().unwrap();
+        assert_eq!(err.to_string(), "bad thing");
+    }
+}
@@ -1,7 +1,7 @@
 use clap::Parser;
 use serde::{Deserialize, Serialize};
 
-#[derive(Parser, Debug, Default, Serialize, Deserialize)]
+#[derive(Parser, Clone, Debug, Default, Serialize, Deserialize)]
 pub struct DbConnectionConf {
     /// Use AWS credentials and region from the environment.
     /// Mutually exclusive with `access-key-id`, `secret-access-key` and `region`.
@@ -8,6 +8,10 @@ use aws_sdk_dynamodb::error::DisplayErrorContext;
 use aws_sdk_dynamodb::Client;
 
 pub async fn connect(conf: &ConnectionConf) -> Result<Context, AlternatorError> {
+    let conf = &conf
+        .to_builder()
+        .build()
+        .map_err(|e| AlternatorError(AlternatorErrorKind::Error(e)))?;
     let address = conf.addresses.first().cloned().unwrap_or_default();
 
     let mut config_loader = aws_config::defaults(BehaviorVersion::latest())
@@ -51,7 +55,7 @@ pub async fn connect(conf: &ConnectionConf) -> Result<Context, AlternatorError>
         Some(client),
         conf.retry_number,
         conf.retry_interval,
-        conf.validation_strategy,
+        conf.validation_strategy.clone(),
         conf.page_size.get() as u64,
     ))
 }
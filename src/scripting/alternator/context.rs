@@ -2,28 +2,43 @@ use super::alternator_error::{AlternatorError, AlternatorErrorKind};
 use crate::config::{RetryInterval, ValidationStrategy};
 use crate::error::LatteError;
 use crate::scripting::cluster_info::ClusterInfo;
+use crate::scripting::functions_common::SharedState;
 use crate::scripting::row_distribution::RowDistributionPreset;
 use crate::stats::session::SessionStats;
 use aws_sdk_dynamodb::Client;
-use rune::runtime::Object;
+use hdrhistogram::SubtractionError;
+use rune::runtime::{Object, SyncFunction};
 use rune::{Any, Value};
 use std::collections::HashMap;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Instant;
 use try_lock::TryLock;
 
 #[derive(Any)]
 pub struct Context {
     client: Option<Client>,
-    page_size: u64,
+    /// Clients made by `switch_endpoint`, by endpoint URL.
+    /// Shared by all clones, so each endpoint gets a single HTTP connector.
+    endpoint_clients: Arc<Mutex<HashMap<String, Client>>>,
+    /// Shared with shallow clones so a size set in `prepare` carries over to the workers.
+    page_size: Arc<AtomicU64>,
     pub stats: Arc<TryLock<SessionStats>>,
-    pub report_metadata: Arc<TryLock<HashMap<String, String>>>,
-    pub metric_orientations: Arc<TryLock<HashMap<String, i8>>>,
+    /// Snapshot of `stats` at the last `take_session_stats`.
+    stats_baseline: Arc<TryLock<SessionStats>>,
+    pub report_metadata: Arc<Mutex<HashMap<String, String>>>,
+    pub metric_orientations: Arc<Mutex<HashMap<String, i8>>>,
     pub start_time: TryLock<Instant>,
+    /// Number of operations paced by `rate_limit` since the start time.
+    pub paced_op_count: Arc<AtomicU64>,
     pub retry_number: u64,
     pub retry_interval: RetryInterval,
     pub validation_strategy: ValidationStrategy,
-    pub partition_row_presets: Arc<TryLock<HashMap<String, RowDistributionPreset>>>,
+    /// Workload function resolved for the `custom:<function>` validation strategy.
+    pub validation_callback: Option<Arc<SyncFunction>>,
+    pub partition_row_presets: Arc<Mutex<HashMap<String, RowDistributionPreset>>>,
+    /// Shared by all clones, including worker copies, unlike `data`.
+    pub shared_state: SharedState,
     #[rune(get, set, add_assign, copy)]
     pub load_cycle_count: u64,
     /// True on per-worker deep copies made by [`Context::clone`].
@@ -47,41 +62,51 @@ impl Context {
     ) -> Context {
         Context {
             client,
-            page_size,
+            endpoint_clients: Arc::new(Mutex::new(HashMap::new())),
+            page_size: Arc::new(AtomicU64::new(page_size)),
             stats: Arc::new(TryLock::new(SessionStats::new())),
-            report_metadata: Arc::new(TryLock::new(HashMap::new())),
-            metric_orientations: Arc::new(TryLock::new(HashMap::new())),
+            stats_baseline: Arc::new(TryLock::new(SessionStats::new())),
+            report_metadata: Arc::new(Mutex::new(HashMap::new())),
+            metric_orientations: Arc::new(Mutex::new(HashMap::new())),
             start_time: TryLock::new(Instant::now()),
+            paced_op_count: Arc::new(AtomicU64::new(0)),
             retry_number,
             retry_interval,
             validation_strategy,
-            partition_row_presets: Arc::new(TryLock::new(HashMap::new())),
+            validation_callback: None,
+            partition_row_presets: Arc::new(Mutex::new(HashMap::new())),
+            shared_state: SharedState::default(),
             load_cycle_count: 0,
             is_worker_clone: false,
             data: Value::new(Object::new()).unwrap(),
         }
     }
 
+    /// Clones the context for use by another thread.
+    /// The new clone gets fresh statistics and start time.
     pub fn clone(&self) -> Result<Self, LatteError> {
         let serialized = rmp_serde::to_vec(&self.data)?;
         let deserialized: Value = rmp_serde::from_slice(&serialized)?;
         Ok(Context {
             client: self.client.clone(),
-            page_size: self.page_size,
-            stats: Arc::new(TryLock::new(SessionStats::default())),
-            report_metadata: Arc::new(TryLock::new(
-                self.report_metadata.try_lock().unwrap().clone(),
-            )),
-            metric_orientations: Arc::new(TryLock::new(
-                self.metric_orientations.try_lock().unwrap().clone(),
+            endpoint_clients: Arc::clone(&self.endpoint_clients),
+            page_size: Arc::new(AtomicU64::new(self.get_page_size())),
+            stats: Arc::new(TryLock::new(SessionStats::new())),
+            stats_baseline: Arc::new(TryLock::new(SessionStats::new())),
+            report_metadata: Arc::new(Mutex::new(self.report_metadata.lock().unwrap().clone())),
+            metric_orientations: Arc::new(Mutex::new(
+                self.metric_orientations.lock().unwrap().clone(),
             )),
-            start_time: TryLock::new(*self.start_time.try_lock().unwrap()),
+            start_time: TryLock::new(Instant::now()),
+            paced_op_count: Arc::new(AtomicU64::new(0)),
             retry_number: self.retry_number,
             retry_interval: self.retry_interval,
-            validation_strategy: self.validation_strategy,
-            partition_row_presets: Arc::new(TryLock::new(
-                self.partition_row_presets.try_lock().unwrap().clone(),
+            validation_strategy: self.validation_strategy.clone(),
+            validation_callback: self.validation_callback.clone(),
+            partition_row_presets: Arc::new(Mutex::new(
+                self.partition_row_presets.lock().unwrap().clone(),
             )),
+            shared_state: self.shared_state.clone(),
             load_cycle_count: self.load_cycle_count,
             is_worker_clone: true,
             data: deserialized,
@@ -93,15 +118,20 @@ impl Context {
     pub fn shallow_clone(&self) -> Self {
         Context {
             client: self.client.clone(),
-            page_size: self.page_size,
+            endpoint_clients: Arc::clone(&self.endpoint_clients),
+            page_size: Arc::clone(&self.page_size),
             stats: Arc::clone(&self.stats),
+            stats_baseline: Arc::clone(&self.stats_baseline),
             report_metadata: Arc::clone(&self.report_metadata),
             metric_orientations: Arc::clone(&self.metric_orientations),
             start_time: TryLock::new(*self.start_time.try_lock().unwrap()),
+            paced_op_count: Arc::clone(&self.paced_op_count),
             retry_number: self.retry_number,
             retry_interval: self.retry_interval,
-            validation_strategy: self.validation_strategy,
+            validation_strategy: self.validation_strategy.clone(),
+            validation_callback: self.validation_callback.clone(),
             partition_row_presets: Arc::clone(&self.partition_row_presets),
+            shared_state: self.shared_state.clone(),
             load_cycle_count: self.load_cycle_count,
             is_worker_clone: self.is_worker_clone,
             data: self.data.clone(),
@@ -136,6 +166,7 @@ impl Context {
                     return Ok(Some(ClusterInfo {
                         name: "".to_string(),
                         db_version: format!("ScyllaDB {version} with build-id {build_id}"),
+                        ..Default::default()
                     }));
                 }
             }
@@ -154,6 +185,7 @@ impl Context {
                 return Ok(Some(ClusterInfo {
                     name: endpoint.address().to_string(),
                     db_version: "AWS DynamoDB".to_string(),
+                    ..Default::default()
                 }));
             }
         }
@@ -164,13 +196,13 @@ impl Context {
 
     pub fn set_report_field(&self, key: &str, value: &str) {
         self.report_metadata
-            .try_lock()
+            .lock()
             .unwrap()
             .insert(key.to_string(), value.to_string());
     }
 
     pub fn report_metadata_snapshot(&self) -> HashMap<String, String> {
-        self.report_metadata.try_lock().unwrap().clone()
+        self.report_metadata.lock().unwrap().clone()
     }
 
     pub fn record_metric(&self, name: &str, value: f64) {
@@ -179,25 +211,31 @@ impl Context {
 
     pub fn declare_metric(&self, name: &str, orientation: i8) {
         self.metric_orientations
-            .try_lock()
+            .lock()
             .unwrap()
             .insert(name.to_string(), orientation);
     }
 
     pub fn metric_orientations_snapshot(&self) -> HashMap<String, i8> {
-        self.metric_orientations.try_lock().unwrap().clone()
+        self.metric_orientations.lock().unwrap().clone()
     }
 
-    pub fn take_session_stats(&self) -> SessionStats {
+    pub fn take_session_stats(&self) -> Result<SessionStats, SubtractionError> {
         let mut stats = self.stats.try_lock().unwrap();
-        let result = stats.clone();
-        stats.reset();
-        result
+        stats.take_interval(&mut self.stats_baseline.try_lock().unwrap())
+    }
+
+    /// Returns the request stats accumulated since the previous `take_session_stats`,
+    /// without taking them.
+    pub fn interval_session_stats(&self) -> Result<SessionStats, SubtractionError> {
+        let stats = self.stats.try_lock().unwrap();
+        stats.since(&self.stats_baseline.try_lock().unwrap())
     }
 
     pub fn reset(&self) {
         self.stats.try_lock().unwrap().reset();
         *self.start_time.try_lock().unwrap() = Instant::now();
+        self.paced_op_count.store(0, Ordering::Relaxed);
     }
 
     pub fn get_client(&self) -> Result<&Client, AlternatorError> {
@@ -208,7 +246,140 @@ impl Context {
             )))
     }
 
+    /// Returns a context sending requests through the given client, e.g. to another region.
+    /// It shares the stats and presets with this context, so the requests it makes are
+    /// included in the report.
+    pub fn with_client(&self, client: Client) -> Context {
+        Context {
+            client: Some(client),
+            ..self.shallow_clone()
+        }
+    }
+
+    /// Returns a context sending requests to the given endpoint, with the credentials,
+    /// region and timeouts of this context's client.
+    /// The client of each endpoint is created once and reused by later calls.
+    pub fn switch_endpoint(&self, endpoint_url: &str) -> Result<Context, AlternatorError> {
+        let client = self.get_client()?;
+        let client = self
+            .endpoint_clients
+            .lock()
+            .unwrap()
+            .entry(endpoint_url.to_string())
+            .or_insert_with(|| {
+                let config = client
+                    .config()
+                    .to_builder()
+                    .endpoint_url(endpoint_url)
+                    .build();
+                Client::from_conf(config)
+            })
+            .clone();
+        Ok(self.with_client(client))
+    }
+
     pub fn get_page_size(&self) -> u64 {
-        self.page_size
+        self.page_size.load(Ordering::Relaxed)
+    }
+
+    pub fn set_page_size(&self, size: u64) -> Result<(), AlternatorError> {
+        if size == 0 {
+            return Err(AlternatorError::new(AlternatorErrorKind::Error(
+                "Page size must be greater than 0".to_string(),
+            )));
+        }
+        self.page_size.store(size, Ordering::Relaxed);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_test_context() -> Context {
+        Context::new(
+            None,
+            0,
+            RetryInterval::new("1,2").unwrap(),
+            ValidationStrategy::Ignore,
+            1000,
+        )
+    }
+
+    #[test]
+    fn set_page_size_is_shared_with_shallow_clones() {
+        let ctx = create_test_context();
+        let shallow = ctx.shallow_clone();
+        shallow.set_page_size(50).unwrap();
+        assert_eq!(shallow.get_page_size(), 50);
+        assert_eq!(ctx.get_page_size(), 50);
+
+        let worker = ctx.clone().unwrap();
+        worker.set_page_size(10).unwrap();
+        assert_eq!(worker.get_page_size(), 10);
+        assert_eq!(ctx.get_page_size(), 50);
+    }
+
+    #[test]
+    fn switch_endpoint_replaces_client_endpoint() {
+        let config = aws_sdk_dynamodb::Config::builder()
+            .behavior_version(aws_config::BehaviorVersion::latest())
+            .region(aws_sdk_dynamodb::config::Region::new("us-east-1"))
+            .endpoint_url("http://first-region:8000")
+            .build();
+        let ctx = Context {
+            client: Some(Client::from_conf(config)),
+            ..create_test_context()
+        };
+
+        let switched = ctx.switch_endpoint("http://second-region:8000").unwrap();
+        let switched_config = format!("{:?}", switched.get_client().unwrap().config());
+        assert!(switched_config.contains("http://second-region:8000"));
+        assert!(!switched_config.contains("http://first-region:8000"));
+        assert!(Arc::ptr_eq(&ctx.stats, &switched.stats));
+    }
+
+    #[test]
+    fn switch_endpoint_reuses_client_of_endpoint() {
+        let config = aws_sdk_dynamodb::Config::builder()
+            .behavior_version(aws_config::BehaviorVersion::latest())
+            .region(aws_sdk_dynamodb::config::Region::new("us-east-1"))
+            .endpoint_url("http://first-region:8000")
+            .build();
+        let ctx = Context {
+            client: Some(Client::from_conf(config)),
+            ..create_test_context()
+        };
+        let worker = ctx.clone().unwrap();
+
+        ctx.switch_endpoint("http://second-region:8000").unwrap();
+        worker.switch_endpoint("http://second-region:8000").unwrap();
+        let switched = worker.switch_endpoint("http://second-region:8000").unwrap();
+        switched
+            .switch_endpoint("http://second-region:8000")
+            .unwrap();
+        assert_eq!(ctx.endpoint_clients.lock().unwrap().len(), 1);
+
+        let switched_back = switched
+            .switch_endpoint("http://first-region:8000")
+            .unwrap();
+        let switched_back_config = format!("{:?}", switched_back.get_client().unwrap().config());
+        assert!(switched_back_config.contains("http://first-region:8000"));
+        assert_eq!(ctx.endpoint_clients.lock().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn switch_endpoint_requires_client() {
+        assert!(create_test_context()
+            .switch_endpoint("http://localhost:8000")
+            .is_err());
+    }
+
+    #[test]
+    fn zero_page_size_is_rejected() {
+        let ctx = create_test_context();
+        assert!(ctx.set_page_size(0).is_err());
+        assert_eq!(ctx.get_page_size(), 1000);
     }
 }
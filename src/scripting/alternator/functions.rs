@@ -2,7 +2,9 @@ use crate::config::ValidationStrategy;
 use crate::scripting::alternator::traits::{
     AlternatorRequest, IntoAlternatorOutput, PaginationToken,
 };
-use crate::scripting::functions_common::{extract_validation_args, ValidationArgs};
+use crate::scripting::functions_common::{
+    call_validation_callback, extract_validation_args, ValidationArgs,
+};
 use crate::scripting::retry_error::handle_retry_error;
 
 use super::alternator_error::{AlternatorError, AlternatorErrorKind};
@@ -10,8 +12,9 @@ use super::context::Context;
 use super::types::*;
 use aws_sdk_dynamodb::client::Waiters;
 use aws_sdk_dynamodb::types::{
-    AttributeDefinition, DeleteRequest, KeySchemaElement, KeyType, KeysAndAttributes, PutRequest,
-    ScalarAttributeType, WriteRequest,
+    AttributeDefinition, AttributeValue, ConditionCheck, Delete, DeleteRequest, KeySchemaElement,
+    KeyType, KeysAndAttributes, Put, PutRequest, ScalarAttributeType, TransactWriteItem, Update,
+    WriteRequest,
 };
 use rune::runtime::{Object, Ref, VmResult};
 use rune::{ToValue, Value};
@@ -95,9 +98,169 @@ fn extract_attribute_names(object: &Object) -> Result<HashMap<String, String>, A
         .collect::<Result<_, _>>()
 }
 
-async fn handle_request_with_pagination(
+/// Gets the attribute name and value of an object with a single key attribute, e.g. `#{ pk: 1 }`.
+fn extract_single_key_attribute(
+    object: &Object,
+    what: &str,
+) -> Result<(String, AttributeValue), AlternatorError> {
+    let mut iter = object.iter();
+    match (iter.next(), iter.next()) {
+        (Some((name, value)), None) => Ok((
+            name.to_string(),
+            rune_value_to_alternator_attribute(value.clone())?,
+        )),
+        _ => bad_input(format!(
+            "'{what}' must be an object with exactly one attribute"
+        )),
+    }
+}
+
+/// Maximum number of operations DynamoDB accepts in a single transaction.
+const MAX_TRANSACTION_ITEMS: usize = 100;
+
+fn string_field(object: &Object, key: &str) -> Result<Option<String>, AlternatorError> {
+    match object.get(key) {
+        None => Ok(None),
+        Some(v) => match v.borrow_ref::<rune::alloc::String>() {
+            Ok(s) => Ok(Some(s.as_str().to_string())),
+            Err(_) => bad_input(format!("'{key}' must be a string")),
+        },
+    }
+}
+
+fn map_field(
+    object: &Object,
+    key: &str,
+) -> Result<Option<HashMap<String, AttributeValue>>, AlternatorError> {
+    match object.get(key) {
+        None => Ok(None),
+        Some(v) => match v.borrow_ref::<Object>() {
+            Ok(obj) => Ok(Some(rune_object_to_alternator_map(&obj)?)),
+            Err(_) => bad_input(format!("'{key}' must be an object")),
+        },
+    }
+}
+
+/// Converts an operation object passed to `transact_write` into a `TransactWriteItem`.
+fn transact_write_item(op: &Object) -> Result<TransactWriteItem, AlternatorError> {
+    check_invalid_params(
+        op,
+        "transact_write",
+        &[
+            "type",
+            TABLE_KEY,
+            ITEM_KEY,
+            KEY_KEY,
+            UPDATE_EXPRESSION_KEY,
+            CONDITION_EXPRESSION_KEY,
+            ATTRIBUTE_NAMES_KEY,
+            ATTRIBUTE_VALUES_KEY,
+        ],
+    )?;
+    let Some(op_type) = string_field(op, "type")? else {
+        return bad_input(
+            "Transaction operation must have a 'type' field (put, update, delete or condition_check)",
+        );
+    };
+    let Some(table) = string_field(op, TABLE_KEY)? else {
+        return bad_input("Transaction operation must have a 'table' field");
+    };
+    let condition = string_field(op, CONDITION_EXPRESSION_KEY)?;
+    let names = match op.get(ATTRIBUTE_NAMES_KEY) {
+        None => None,
+        Some(v) => match v.borrow_ref::<Object>() {
+            Ok(obj) => Some(extract_attribute_names(&obj)?),
+            Err(_) => return bad_input(format!("'{}' must be an object", ATTRIBUTE_NAMES_KEY)),
+        },
+    };
+    let values = map_field(op, ATTRIBUTE_VALUES_KEY)?;
+    let key = || match map_field(op, KEY_KEY)? {
+        Some(key) => Ok(key),
+        None => bad_input(format!("Transaction {op_type} must have a 'key' field")),
+    };
+
+    let item = TransactWriteItem::builder();
+    let item = match op_type.as_str() {
+        "put" => {
+            let Some(put_item) = map_field(op, ITEM_KEY)? else {
+                return bad_input("Transaction put must have an 'item' field");
+            };
+            item.put(
+                Put::builder()
+                    .table_name(table)
+                    .set_item(Some(put_item))
+                    .set_condition_expression(condition)
+                    .set_expression_attribute_names(names)
+                    .set_expression_attribute_values(values)
+                    .build()?,
+            )
+        }
+        "update" => {
+            let Some(update) = string_field(op, UPDATE_EXPRESSION_KEY)? else {
+                return bad_input("Transaction update must have an 'update' field");
+            };
+            item.update(
+                Update::builder()
+                    .table_name(table)
+                    .set_key(Some(key()?))
+                    .update_expression(update)
+                    .set_condition_expression(condition)
+                    .set_expression_attribute_names(names)
+                    .set_expression_attribute_values(values)
+                    .build()?,
+            )
+        }
+        "delete" => item.delete(
+            Delete::builder()
+                .table_name(table)
+                .set_key(Some(key()?))
+                .set_condition_expression(condition)
+                .set_expression_attribute_names(names)
+                .set_expression_attribute_values(values)
+                .build()?,
+        ),
+        "condition_check" => {
+            let Some(condition) = condition else {
+                return bad_input(
+                    "Transaction condition_check must have a 'condition_expression' field",
+                );
+            };
+            item.condition_check(
+                ConditionCheck::builder()
+                    .table_name(table)
+                    .set_key(Some(key()?))
+                    .condition_expression(condition)
+                    .set_expression_attribute_names(names)
+                    .set_expression_attribute_values(values)
+                    .build()?,
+            )
+        }
+        _ => {
+            return bad_input(format!(
+                "Invalid transaction operation type: {op_type}, \
+                 must be 'put', 'update', 'delete' or 'condition_check'"
+            ))
+        }
+    };
+    Ok(item.build())
+}
+
+/// Returns the `Limit` of the next page request: the page size, but no more than the items
+/// remaining to reach the `limit` given by the script, if any.
+fn page_limit(page_size: u64, query_limit: Option<i32>, fetched_items: u64) -> i32 {
+    let page_size = i32::try_from(page_size).unwrap_or(i32::MAX);
+    match query_limit {
+        Some(limit) => min(
+            page_size,
+            limit.saturating_sub(i32::try_from(fetched_items).unwrap_or(i32::MAX)),
+        ),
+        None => page_size,
+    }
+}
+
+async fn handle_request_with_pagination<B: AlternatorRequest>(
     ctx: &Context,
-    builder: impl AlternatorRequest,
+    builder: B,
     auto_paginate: bool,
 ) -> Result<(Vec<Value>, Option<PaginationToken>), AlternatorError> {
     let mut token: Option<PaginationToken> = None;
@@ -110,18 +273,18 @@ async fn handle_request_with_pagination(
     while current_attempt_num <= ctx.retry_number {
         let mut current_builder = builder.clone();
         if builder.has_pagination() {
-            let page_size = match query_limit {
-                Some(limit) => min(ctx.get_page_size() as i32, limit - total_item_count as i32),
-                None => ctx.get_page_size() as i32,
-            };
-            current_builder = current_builder.set_pagination(token.clone(), Some(page_size));
+            let page_limit = page_limit(ctx.get_page_size(), query_limit, total_item_count);
+            current_builder = current_builder.set_pagination(token.clone(), Some(page_limit));
         }
 
         let start_time = ctx.stats.try_lock().unwrap().start_request();
         let resp = current_builder.send().await;
         let duration = Instant::now() - start_time;
 
-        match resp.into_output() {
+        match resp.into_output().map_err(|e| match builder.table_name() {
+            Some(table) => e.with_throttled_table(&table),
+            None => e,
+        }) {
             Ok((page_items, item_count, next_token)) => {
                 all_pages_duration += duration;
                 all_items.extend(page_items);
@@ -130,10 +293,11 @@ async fn handle_request_with_pagination(
 
                 if let Some(limit) = query_limit {
                     if total_item_count as i32 >= limit {
-                        ctx.stats
-                            .try_lock()
-                            .unwrap()
-                            .complete_request(all_pages_duration, total_item_count);
+                        ctx.stats.try_lock().unwrap().complete_request(
+                            B::OPERATION,
+                            all_pages_duration,
+                            total_item_count,
+                        );
                         return Ok((all_items, token));
                     }
                 }
@@ -143,20 +307,27 @@ async fn handle_request_with_pagination(
                         current_attempt_num = 0; // reset retries for next page
                         continue;
                     } else {
-                        ctx.stats
-                            .try_lock()
-                            .unwrap()
-                            .complete_request(all_pages_duration, total_item_count);
+                        ctx.stats.try_lock().unwrap().complete_request(
+                            B::OPERATION,
+                            all_pages_duration,
+                            total_item_count,
+                        );
                         return Ok((all_items, token));
                     }
                 }
 
-                ctx.stats
-                    .try_lock()
-                    .unwrap()
-                    .complete_request(all_pages_duration, total_item_count);
+                ctx.stats.try_lock().unwrap().complete_request(
+                    B::OPERATION,
+                    all_pages_duration,
+                    total_item_count,
+                );
                 return Ok((all_items, token));
             }
+            Err(e) if e.is_conditional_check_failed() => {
+                // The condition would fail the same way on every retry
+                ctx.stats.try_lock().unwrap().record_error(e.category());
+                return Err(e);
+            }
             Err(e) => {
                 let current_error = e;
                 handle_retry_error(ctx, current_attempt_num, current_error).await;
@@ -165,7 +336,11 @@ async fn handle_request_with_pagination(
             }
         };
     }
-    Err(AlternatorError::query_retries_exceeded(ctx.retry_number))
+    let err = AlternatorError::query_retries_exceeded(ctx.retry_number);
+    Err(match builder.expression() {
+        Some(expression) => err.with_query(&expression),
+        None => err,
+    })
 }
 
 async fn handle_request(
@@ -185,30 +360,58 @@ async fn handle_request_with_validation(
     loop {
         let (result, _) = handle_request_with_pagination(ctx, builder.clone(), true).await?;
 
-        let validation = match validation {
-            None => return Ok(result),
-            Some(ref v) => v,
-        };
-
         let item_count = result.len() as u64;
-        if item_count >= validation.expected_min && item_count <= validation.expected_max {
-            return Ok(result);
-        }
-
-        let current_error = AlternatorError::new(AlternatorErrorKind::ValidationError(format!(
-            "{operation_name} returned {item_count} items, expected between {} and {} {}",
-            validation.expected_min, validation.expected_max, validation.custom_err_msg
-        )));
+        let count_error = match &validation {
+            Some(v) if item_count < v.expected_min || item_count > v.expected_max => Some(
+                AlternatorError::new(AlternatorErrorKind::ValidationError(format!(
+                    "{operation_name} returned {item_count} items, expected between {} and {} {}",
+                    v.expected_min, v.expected_max, v.custom_err_msg
+                ))),
+            ),
+            _ => None,
+        };
+        // The custom validation function checks every result, with or without validation args
+        let current_error = match (count_error, &ctx.validation_strategy) {
+            (Some(error), _) => error,
+            (None, ValidationStrategy::Custom { callback }) => {
+                let accepted = call_validation_callback(
+                    ctx.validation_callback.as_deref(),
+                    callback,
+                    result.clone().to_value()?,
+                )
+                .map_err(|e| {
+                    let err = AlternatorError::new(AlternatorErrorKind::Error(e));
+                    ctx.stats.try_lock().unwrap().record_error(err.category());
+                    err
+                })?;
+                if accepted {
+                    return Ok(result);
+                }
+                AlternatorError::new(AlternatorErrorKind::ValidationError(format!(
+                    "Validation function '{callback}' rejected the result of {operation_name}"
+                )))
+            }
+            (None, _) => return Ok(result),
+        };
+        ctx.stats.try_lock().unwrap().record_validation_failure();
 
-        match ctx.validation_strategy {
+        match &ctx.validation_strategy {
             ValidationStrategy::Retry => {
                 if current_attempt_num >= ctx.retry_number {
+                    ctx.stats
+                        .try_lock()
+                        .unwrap()
+                        .record_error(current_error.category());
                     return Err(current_error);
                 }
                 handle_retry_error(ctx, current_attempt_num, current_error).await;
                 current_attempt_num += 1;
             }
-            ValidationStrategy::FailFast => {
+            ValidationStrategy::FailFast | ValidationStrategy::Custom { .. } => {
+                ctx.stats
+                    .try_lock()
+                    .unwrap()
+                    .record_error(current_error.category());
                 return Err(current_error);
             }
             ValidationStrategy::Ignore => {
@@ -642,6 +845,72 @@ pub async fn get(
     Ok(Value::from(()))
 }
 
+/// Deletes an item only if one of its attributes has the expected value.
+///
+/// Returns true if the item was deleted and false if the condition was not satisfied,
+/// e.g. because the attribute was changed concurrently or the item does not exist.
+///
+/// # Arguments
+/// * `table_name` - The name of the table.
+/// * `key` - The primary key of the item to delete. An object containing the partition key
+///   (and sort key if the table has one).
+/// * `condition_attr` - The name of the attribute to compare.
+/// * `expected_value` - The value the attribute must have for the item to be deleted.
+#[rune::function(instance)]
+pub async fn delete_if(
+    ctx: Ref<Context>,
+    table_name: Ref<str>,
+    key: Ref<Object>,
+    condition_attr: Ref<str>,
+    expected_value: Value,
+) -> Result<bool, AlternatorError> {
+    let client = ctx.get_client()?;
+
+    let builder = client
+        .delete_item()
+        .table_name(table_name.deref())
+        .set_key(Some(rune_object_to_alternator_map(&key)?))
+        .condition_expression("#attr = :expected")
+        .expression_attribute_names("#attr", condition_attr.deref())
+        .expression_attribute_values(
+            ":expected",
+            rune_value_to_alternator_attribute(expected_value)?,
+        );
+
+    match handle_request(&ctx, builder).await {
+        Ok(_) => Ok(true),
+        Err(e) if e.is_conditional_check_failed() => Ok(false),
+        Err(e) => Err(e),
+    }
+}
+
+/// Gets an item from the table with a strongly consistent read.
+///
+/// Same as `get` with `consistent_read` and `with_result` set to true:
+/// returns an `Option<Value>` containing the item if present.
+///
+/// # Arguments
+/// * `table_name` - The name of the table.
+/// * `key` - The primary key of the item to get. An object containing the partition key
+///   (and sort key if the table has one).
+#[rune::function(instance)]
+pub async fn get_consistent(
+    ctx: Ref<Context>,
+    table_name: Ref<str>,
+    key: Ref<Object>,
+) -> Result<Value, AlternatorError> {
+    let client = ctx.get_client()?;
+
+    let builder = client
+        .get_item()
+        .table_name(table_name.deref())
+        .set_key(Some(rune_object_to_alternator_map(&key)?))
+        .consistent_read(true);
+
+    let result = handle_request(&ctx, builder).await?;
+    Ok(result.into_iter().next().to_value()?)
+}
+
 /// Updates an item in the table.
 ///
 /// # Arguments
@@ -997,6 +1266,48 @@ pub async fn batch_write_item(
     format_batch_result(result_items, token, !get_unprocessed, get_unprocessed)
 }
 
+/// Writes items to one or multiple tables in a single all-or-nothing transaction.
+///
+/// If a condition of any operation is not satisfied, no operation is applied and
+/// a `TransactionCanceled` error listing the cancellation reason of each operation is raised.
+///
+/// # Arguments
+/// * `operations` - A list of up to 100 operations. Each operation is an object containing:
+///   - `type`: One of "put", "update", "delete" or "condition_check".
+///   - `table`: The name of the table.
+///   - `item`: For put operations, the item object to insert.
+///   - `key`: For other operations, the primary key of the item.
+///   - `update`: For update operations, the update expression string.
+///   - `condition_expression`: A condition that must be satisfied for the transaction to succeed
+///     (required for condition_check).
+///   - `attribute_names`: A map of attribute name placeholders (starting with #) to actual names.
+///   - `attribute_values`: A map of attribute value placeholders (starting with :) to values.
+#[rune::function(instance)]
+pub async fn transact_write(
+    ctx: Ref<Context>,
+    operations: Vec<Object>,
+) -> Result<(), AlternatorError> {
+    let client = ctx.get_client()?;
+
+    if operations.is_empty() || operations.len() > MAX_TRANSACTION_ITEMS {
+        return bad_input(format!(
+            "transact_write requires between 1 and {MAX_TRANSACTION_ITEMS} operations, got {}",
+            operations.len()
+        ));
+    }
+    let items = operations
+        .iter()
+        .map(transact_write_item)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let builder = client
+        .transact_write_items()
+        .set_transact_items(Some(items));
+    handle_request(&ctx, builder).await?;
+
+    Ok(())
+}
+
 /// Queries items from the table.
 ///
 /// Unlike `get`, which retrieves a single item by its exact primary key,
@@ -1133,6 +1444,143 @@ pub async fn query(
     Ok(Value::from(()))
 }
 
+/// Queries the items of a partition with a sort key in the given range, inclusive.
+///
+/// Returns an object with the matching `items` and their `count`.
+///
+/// # Arguments
+/// * `table_name` - The name of the table.
+/// * `pk` - The partition key, e.g. `#{ sensor: "s1" }`.
+/// * `sk_low` - The lower bound of the sort key, e.g. `#{ ts: 100 }`.
+/// * `sk_high` - The upper bound of the sort key, e.g. `#{ ts: 200 }`.
+#[rune::function(instance)]
+pub async fn query_range(
+    ctx: Ref<Context>,
+    table_name: Ref<str>,
+    pk: Ref<Object>,
+    sk_low: Ref<Object>,
+    sk_high: Ref<Object>,
+) -> Result<Value, AlternatorError> {
+    let client = ctx.get_client()?;
+
+    let (pk_name, pk_value) = extract_single_key_attribute(&pk, "pk")?;
+    let (sk_name, sk_low) = extract_single_key_attribute(&sk_low, "sk_low")?;
+    let (sk_high_name, sk_high) = extract_single_key_attribute(&sk_high, "sk_high")?;
+    if sk_name != sk_high_name {
+        return bad_input(format!(
+            "'sk_low' and 'sk_high' must use the same sort key, got '{sk_name}' and '{sk_high_name}'"
+        ));
+    }
+
+    // Placeholders avoid conflicts of key names with reserved words
+    let builder = client
+        .query()
+        .table_name(table_name.deref())
+        .key_condition_expression("#pk = :pk AND #sk BETWEEN :lo AND :hi")
+        .expression_attribute_names("#pk", pk_name)
+        .expression_attribute_names("#sk", sk_name)
+        .expression_attribute_values(":pk", pk_value)
+        .expression_attribute_values(":lo", sk_low)
+        .expression_attribute_values(":hi", sk_high);
+
+    let items = handle_request(&ctx, builder).await?;
+
+    let mut res_map = HashMap::new();
+    res_map.insert("count".to_string(), (items.len() as i64).to_value()?);
+    res_map.insert("items".to_string(), items.to_value()?);
+    hashmap_to_rune_object(res_map)
+}
+
+/// Queries all items of a partition, following the pages until the end of the partition.
+///
+/// All items are kept in memory, so `max_items` guards against unexpectedly large partitions:
+/// an error is raised if the partition has more items than that.
+///
+/// # Arguments
+/// * `table_name` - The name of the table.
+/// * `pk` - The partition key, e.g. `#{ user: "u1" }`.
+/// * `max_items` - The maximum number of items expected in the partition.
+#[rune::function(instance)]
+pub async fn query_all(
+    ctx: Ref<Context>,
+    table_name: Ref<str>,
+    pk: Ref<Object>,
+    max_items: i64,
+) -> Result<Value, AlternatorError> {
+    let client = ctx.get_client()?;
+
+    let max_items = match i32::try_from(max_items) {
+        Ok(val) if val > 0 && val < i32::MAX => val,
+        _ => {
+            return bad_input(format!(
+                "'max_items' must be between 1 and {}",
+                i32::MAX - 1
+            ))
+        }
+    };
+    let (pk_name, pk_value) = extract_single_key_attribute(&pk, "pk")?;
+
+    // Fetching one item more than allowed tells if the partition exceeds the cap
+    let builder = client
+        .query()
+        .table_name(table_name.deref())
+        .key_condition_expression("#pk = :pk")
+        .expression_attribute_names("#pk", pk_name)
+        .expression_attribute_values(":pk", pk_value)
+        .limit(max_items + 1);
+
+    let items = handle_request(&ctx, builder).await?;
+    if items.len() > max_items as usize {
+        return Err(AlternatorError::new(AlternatorErrorKind::Error(format!(
+            "query_all: the partition has more than {max_items} items"
+        ))));
+    }
+    Ok(items.to_value()?)
+}
+
+/// Queries the items of a partition, retrieving only the given attributes.
+///
+/// Returns the matching items as a `Vec<Object>`.
+///
+/// # Arguments
+/// * `table_name` - The name of the table.
+/// * `pk` - The partition key, e.g. `#{ user: "u1" }`.
+/// * `attributes` - The names of the attributes to retrieve. Reserved words are allowed.
+#[rune::function(instance)]
+pub async fn query_projected(
+    ctx: Ref<Context>,
+    table_name: Ref<str>,
+    pk: Ref<Object>,
+    attributes: Vec<String>,
+) -> Result<Value, AlternatorError> {
+    let client = ctx.get_client()?;
+
+    if attributes.is_empty() {
+        return bad_input("'attributes' must not be empty");
+    }
+    let (pk_name, pk_value) = extract_single_key_attribute(&pk, "pk")?;
+
+    // Placeholders avoid conflicts of attribute names with reserved words
+    let mut builder = client
+        .query()
+        .table_name(table_name.deref())
+        .key_condition_expression("#pk = :pk")
+        .expression_attribute_names("#pk", pk_name)
+        .expression_attribute_values(":pk", pk_value)
+        .projection_expression(
+            (0..attributes.len())
+                .map(|i| format!("#p{i}"))
+                .collect::<Vec<_>>()
+                .join(", "),
+        );
+    for (i, attribute) in attributes.into_iter().enumerate() {
+        builder = builder.expression_attribute_names(format!("#p{i}"), attribute);
+    }
+
+    let items = handle_request(&ctx, builder).await?;
+    Ok(items.to_value()?)
+}
+
 /// Scans items from the table.
 ///
 /// If `with_result` is set to true, the scan result is returned as a `Vec<Object>`.
@@ -1281,6 +1729,93 @@ pub async fn scan(
     Ok(Value::from(()))
 }
 
+/// Scans a single segment of a parallel scan, one page at a time.
+///
+/// Returns an object with the scanned `items` and the `last_evaluated_key`, which is missing
+/// once the end of the segment is reached. Pass the returned `last_evaluated_key` as
+/// `start_key` to fetch the next page, or `()` to start from the beginning of the segment.
+/// The page size is set by `set_page_size`.
+///
+/// # Arguments
+/// * `table_name` - The name of the table.
+/// * `segment` - The segment to scan, from 0 to `total_segments - 1`.
+/// * `total_segments` - The number of segments the table is divided into.
+/// * `start_key` - The key to continue the scan from, or `()`.
+#[rune::function(instance)]
+pub async fn scan_segment(
+    ctx: Ref<Context>,
+    table_name: Ref<str>,
+    segment: i64,
+    total_segments: i64,
+    start_key: Value,
+) -> Result<Value, AlternatorError> {
+    let client = ctx.get_client()?;
+
+    let total_segments = match i32::try_from(total_segments) {
+        Ok(val) if val > 0 => val,
+        _ => return bad_input(format!("'{}' must be positive", TOTAL_SEGMENTS_KEY)),
+    };
+    let segment = match i32::try_from(segment) {
+        Ok(val) if (0..total_segments).contains(&val) => val,
+        _ => {
+            return bad_input(format!(
+                "'{}' must be between 0 and {}",
+                SEGMENT_KEY,
+                total_segments - 1
+            ))
+        }
+    };
+
+    let mut builder = client
+        .scan()
+        .table_name(table_name.deref())
+        .segment(segment)
+        .total_segments(total_segments);
+
+    if let Ok(obj) = start_key.borrow_ref::<Object>() {
+        builder = builder.set_exclusive_start_key(Some(rune_object_to_alternator_map(&obj)?));
+    } else if start_key.clone().into_unit().is_err() {
+        return bad_input("'start_key' must be an object or ()");
+    }
+
+    let (items, token) = handle_request_with_pagination(&ctx, builder, false).await?;
+
+    let mut res_map = HashMap::new();
+    res_map.insert("items".to_string(), items.to_value()?);
+    if let Some(PaginationToken::LastEvaluatedKey(key)) = token {
+        res_map.insert(
+            "last_evaluated_key".to_string(),
+            alternator_map_to_rune_object(key)?,
+        );
+    }
+    hashmap_to_rune_object(res_map)
+}
+
+/// Returns a context sending requests to the given endpoint, sharing the stats of this one.
+/// Useful for multi-region benchmarks. The client of each endpoint is created on the first
+/// switch to it, so later switches are cheap.
+#[rune::function(instance)]
+pub fn switch_endpoint(ctx: &Context, endpoint_url: &str) -> Result<Context, AlternatorError> {
+    ctx.switch_endpoint(endpoint_url)
+}
+
+/// Returns the number of items requested per page by paginated queries and scans.
+#[rune::function(instance)]
+pub fn get_page_size(ctx: &Context) -> u64 {
+    ctx.get_page_size()
+}
+
+/// Changes the number of items requested per page by paginated queries and scans.
+/// The new size is visible to every call sharing this context, so setting it in
+/// `prepare` applies it to all workers.
+#[rune::function(instance)]
+pub fn set_page_size(ctx: &Context, size: u64) -> VmResult<()> {
+    match ctx.set_page_size(size) {
+        Ok(()) => VmResult::Ok(()),
+        Err(e) => VmResult::panic(e.to_string()),
+    }
+}
+
 /// Marks a list of items as an Alternator string set.
 #[rune::function]
 pub fn string_set(items: Vec<Value>) -> VmResult<Value> {
@@ -1310,3 +1845,118 @@ pub fn binary_set(items: Vec<Value>) -> VmResult<Value> {
     rune::vm_try!(obj.insert(rune_key, items_val));
     VmResult::Ok(rune::vm_try!(Value::new(obj)))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn object_value(fields: Vec<(&str, Value)>) -> Value {
+        let map = fields
+            .into_iter()
+            .map(|(k, v)| (k.to_string(), v))
+            .collect::<HashMap<_, _>>();
+        hashmap_to_rune_object(map).unwrap()
+    }
+
+    fn object(fields: Vec<(&str, Value)>) -> Object {
+        rune::from_value(object_value(fields)).unwrap()
+    }
+
+    fn string(s: &str) -> Value {
+        s.to_value().unwrap()
+    }
+
+    #[test]
+    fn transact_write_item_maps_operations() {
+        let key = || object_value(vec![("pk", 1i64.to_value().unwrap())]);
+
+        let put = transact_write_item(&object(vec![
+            ("type", string("put")),
+            ("table", string("t")),
+            ("item", key()),
+            ("condition_expression", string("attribute_not_exists(pk)")),
+        ]))
+        .unwrap();
+        let put = put.put().unwrap();
+        assert_eq!(put.table_name(), "t");
+        assert_eq!(put.item()["pk"], AttributeValue::N("1".to_string()));
+        assert_eq!(put.condition_expression(), Some("attribute_not_exists(pk)"));
+
+        let update = transact_write_item(&object(vec![
+            ("type", string("update")),
+            ("table", string("t")),
+            ("key", key()),
+            ("update", string("SET v = :v")),
+            ("attribute_values", object_value(vec![(":v", string("x"))])),
+        ]))
+        .unwrap();
+        let update = update.update().unwrap();
+        assert_eq!(update.update_expression(), "SET v = :v");
+        assert_eq!(
+            update.expression_attribute_values().unwrap()[":v"],
+            AttributeValue::S("x".to_string())
+        );
+
+        let delete = transact_write_item(&object(vec![
+            ("type", string("delete")),
+            ("table", string("t")),
+            ("key", key()),
+        ]))
+        .unwrap();
+        assert!(delete.delete().is_some());
+
+        let check = transact_write_item(&object(vec![
+            ("type", string("condition_check")),
+            ("table", string("t")),
+            ("key", key()),
+            ("condition_expression", string("attribute_exists(pk)")),
+        ]))
+        .unwrap();
+        assert!(check.condition_check().is_some());
+    }
+
+    #[test]
+    fn transact_write_item_rejects_invalid_operations() {
+        let key = || object_value(vec![("pk", 1i64.to_value().unwrap())]);
+
+        for op in [
+            vec![("table", string("t")), ("key", key())],
+            vec![("type", string("put")), ("table", string("t"))],
+            vec![("type", string("delete")), ("key", key())],
+            vec![
+                ("type", string("condition_check")),
+                ("table", string("t")),
+                ("key", key()),
+            ],
+            vec![
+                ("type", string("merge")),
+                ("table", string("t")),
+                ("key", key()),
+            ],
+            vec![
+                ("type", string("delete")),
+                ("table", string("t")),
+                ("key", key()),
+                ("ttl", string("1")),
+            ],
+        ] {
+            let err = transact_write_item(&object(op)).unwrap_err();
+            assert!(matches!(err.0, AlternatorErrorKind::BadInput(_)), "{err}");
+        }
+    }
+
+    #[test]
+    fn page_limit_defaults_to_page_size() {
+        assert_eq!(page_limit(501, None, 0), 501);
+        assert_eq!(page_limit(501, None, 1000), 501);
+        assert_eq!(page_limit(u64::MAX, None, 0), i32::MAX);
+    }
+
+    #[test]
+    fn page_limit_stops_at_explicit_limit() {
+        assert_eq!(page_limit(501, Some(10), 0), 10);
+        assert_eq!(page_limit(100, Some(250), 0), 100);
+        assert_eq!(page_limit(100, Some(250), 200), 50);
+        assert_eq!(page_limit(u64::MAX, Some(250), 0), 250);
+    }
+}
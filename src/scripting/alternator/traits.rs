@@ -1,11 +1,21 @@
-use super::alternator_error::AlternatorError;
+use super::alternator_error::{AlternatorError, CANCELLATION_REASONS_KEY};
 use super::types::alternator_map_to_rune_object;
-use aws_sdk_dynamodb::error::{ProvideErrorMetadata, SdkError};
+use aws_sdk_dynamodb::error::{ErrorMetadata, ProvideErrorMetadata, SdkError};
 use aws_sdk_dynamodb::operation::{
-    batch_get_item::BatchGetItemOutput, batch_write_item::BatchWriteItemOutput,
-    create_table::CreateTableOutput, delete_item::DeleteItemOutput,
-    delete_table::DeleteTableOutput, get_item::GetItemOutput, put_item::PutItemOutput,
-    query::QueryOutput, scan::ScanOutput, update_item::UpdateItemOutput,
+    batch_get_item::BatchGetItemOutput,
+    batch_write_item::BatchWriteItemOutput,
+    create_table::CreateTableOutput,
+    delete_item::DeleteItemOutput,
+    delete_table::DeleteTableOutput,
+    get_item::GetItemOutput,
+    put_item::PutItemOutput,
+    query::QueryOutput,
+    scan::ScanOutput,
+    transact_write_items::{
+        builders::TransactWriteItemsFluentBuilder, TransactWriteItemsError,
+        TransactWriteItemsOutput,
+    },
+    update_item::UpdateItemOutput,
 };
 use aws_sdk_dynamodb::types::{AttributeValue, KeysAndAttributes, WriteRequest};
 use rune::Value;
@@ -119,7 +129,8 @@ impl_into_alternator_output_empty!(
     UpdateItemOutput,
     DeleteItemOutput,
     CreateTableOutput,
-    DeleteTableOutput
+    DeleteTableOutput,
+    TransactWriteItemsOutput
 );
 
 impl<T, E, R> IntoAlternatorOutput for Result<T, SdkError<E, R>>
@@ -144,9 +155,19 @@ pub(super) trait SendRequest {
 }
 
 pub(super) trait AlternatorRequest: SendRequest + Clone {
+    /// Name under which the latency of the request is recorded.
+    const OPERATION: &'static str;
     fn set_pagination(self, token: Option<PaginationToken>, limit: Option<i32>) -> Self;
     fn has_pagination(&self) -> bool;
     fn get_limit_val(&self) -> Option<i32>;
+    /// Name of the table addressed by the request, if there is a single one.
+    fn table_name(&self) -> Option<String> {
+        None
+    }
+    /// Expression describing the request in error messages, if it has one.
+    fn expression(&self) -> Option<String> {
+        None
+    }
 }
 
 macro_rules! impl_send_request {
@@ -166,25 +187,27 @@ macro_rules! impl_send_request {
 }
 
 macro_rules! impl_alternator_request_no_pagination {
-    ($($t:ty),*) => {
+    ($($t:ty => $op:literal),*) => {
         $(
             impl_send_request!($t);
             impl AlternatorRequest for $t {
+                const OPERATION: &'static str = $op;
                 fn set_pagination(self, _: Option<PaginationToken>, _: Option<i32>) -> Self { self }
                 fn has_pagination(&self) -> bool { false }
                 fn get_limit_val(&self) -> Option<i32> { None }
+                fn table_name(&self) -> Option<String> { self.get_table_name().clone() }
             }
         )*
     };
 }
 
 impl_alternator_request_no_pagination!(
-    aws_sdk_dynamodb::operation::create_table::builders::CreateTableFluentBuilder,
-    aws_sdk_dynamodb::operation::delete_table::builders::DeleteTableFluentBuilder,
-    aws_sdk_dynamodb::operation::put_item::builders::PutItemFluentBuilder,
-    aws_sdk_dynamodb::operation::delete_item::builders::DeleteItemFluentBuilder,
-    aws_sdk_dynamodb::operation::get_item::builders::GetItemFluentBuilder,
-    aws_sdk_dynamodb::operation::update_item::builders::UpdateItemFluentBuilder
+    aws_sdk_dynamodb::operation::create_table::builders::CreateTableFluentBuilder => "create_table",
+    aws_sdk_dynamodb::operation::delete_table::builders::DeleteTableFluentBuilder => "delete_table",
+    aws_sdk_dynamodb::operation::put_item::builders::PutItemFluentBuilder => "put",
+    aws_sdk_dynamodb::operation::delete_item::builders::DeleteItemFluentBuilder => "delete",
+    aws_sdk_dynamodb::operation::get_item::builders::GetItemFluentBuilder => "get",
+    aws_sdk_dynamodb::operation::update_item::builders::UpdateItemFluentBuilder => "update"
 );
 
 impl_send_request!(
@@ -194,12 +217,67 @@ impl_send_request!(
     aws_sdk_dynamodb::operation::batch_write_item::builders::BatchWriteItemFluentBuilder
 );
 
+impl SendRequest for TransactWriteItemsFluentBuilder {
+    #[allow(clippy::manual_async_fn)]
+    fn send(
+        self,
+    ) -> impl Future<
+        Output = Result<impl IntoAlternatorOutput, SdkError<impl ProvideErrorMetadata, impl Send>>,
+    > {
+        async move {
+            self.send()
+                .await
+                .map_err(|err| err.map_service_error(transaction_error_metadata))
+        }
+    }
+}
+
+impl AlternatorRequest for TransactWriteItemsFluentBuilder {
+    const OPERATION: &'static str = "transact_write";
+    fn set_pagination(self, _: Option<PaginationToken>, _: Option<i32>) -> Self {
+        self
+    }
+    fn has_pagination(&self) -> bool {
+        false
+    }
+    fn get_limit_val(&self) -> Option<i32> {
+        None
+    }
+}
+
+/// Returns the metadata of a transaction error, with the cancellation reasons of
+/// a canceled transaction added, since they are not part of the metadata by default.
+fn transaction_error_metadata(err: TransactWriteItemsError) -> ErrorMetadata {
+    match err {
+        TransactWriteItemsError::TransactionCanceledException(e) => {
+            let reasons = e
+                .cancellation_reasons()
+                .iter()
+                .map(|r| r.code().unwrap_or("None"))
+                .collect::<Vec<_>>()
+                .join(",");
+            let mut meta = ErrorMetadata::builder().custom(CANCELLATION_REASONS_KEY, reasons);
+            if let Some(code) = e.code() {
+                meta = meta.code(code);
+            }
+            if let Some(message) = e.message() {
+                meta = meta.message(message);
+            }
+            meta.build()
+        }
+        e => e.meta().clone(),
+    }
+}
+
 impl AlternatorRequest for aws_sdk_dynamodb::operation::query::builders::QueryFluentBuilder {
+    const OPERATION: &'static str = "query";
+
     fn set_pagination(self, token: Option<PaginationToken>, limit: Option<i32>) -> Self {
-        let mut b = self.set_exclusive_start_key(match token {
-            Some(PaginationToken::LastEvaluatedKey(key)) => Some(key),
-            _ => None,
-        });
+        // Without a token, keep the start key possibly set by the caller
+        let mut b = match token {
+            Some(PaginationToken::LastEvaluatedKey(key)) => self.set_exclusive_start_key(Some(key)),
+            _ => self,
+        };
         if let Some(limit) = limit {
             b = b.limit(limit);
         }
@@ -211,14 +289,23 @@ impl AlternatorRequest for aws_sdk_dynamodb::operation::query::builders::QueryFl
     fn get_limit_val(&self) -> Option<i32> {
         *self.get_limit()
     }
+    fn table_name(&self) -> Option<String> {
+        self.get_table_name().clone()
+    }
+    fn expression(&self) -> Option<String> {
+        self.get_key_condition_expression().clone()
+    }
 }
 
 impl AlternatorRequest for aws_sdk_dynamodb::operation::scan::builders::ScanFluentBuilder {
+    const OPERATION: &'static str = "scan";
+
     fn set_pagination(self, token: Option<PaginationToken>, limit: Option<i32>) -> Self {
-        let mut b = self.set_exclusive_start_key(match token {
-            Some(PaginationToken::LastEvaluatedKey(key)) => Some(key),
-            _ => None,
-        });
+        // Without a token, keep the start key possibly set by the caller
+        let mut b = match token {
+            Some(PaginationToken::LastEvaluatedKey(key)) => self.set_exclusive_start_key(Some(key)),
+            _ => self,
+        };
         if let Some(limit) = limit {
             b = b.limit(limit);
         }
@@ -230,11 +317,19 @@ impl AlternatorRequest for aws_sdk_dynamodb::operation::scan::builders::ScanFlue
     fn get_limit_val(&self) -> Option<i32> {
         *self.get_limit()
     }
+    fn table_name(&self) -> Option<String> {
+        self.get_table_name().clone()
+    }
+    fn expression(&self) -> Option<String> {
+        self.get_filter_expression().clone()
+    }
 }
 
 impl AlternatorRequest
     for aws_sdk_dynamodb::operation::batch_get_item::builders::BatchGetItemFluentBuilder
 {
+    const OPERATION: &'static str = "batch_get";
+
     fn set_pagination(self, token: Option<PaginationToken>, _limit: Option<i32>) -> Self {
         if let Some(PaginationToken::UnprocessedKeys(keys)) = token {
             self.set_request_items(Some(keys))
@@ -253,6 +348,8 @@ impl AlternatorRequest
 impl AlternatorRequest
     for aws_sdk_dynamodb::operation::batch_write_item::builders::BatchWriteItemFluentBuilder
 {
+    const OPERATION: &'static str = "batch_write";
+
     fn set_pagination(self, token: Option<PaginationToken>, _limit: Option<i32>) -> Self {
         if let Some(PaginationToken::UnprocessedItems(items)) = token {
             self.set_request_items(Some(items))
@@ -24,6 +24,9 @@ pub const QUERY_EXPRESSION_KEY: &str = "query";
 pub const FILTER_EXPRESSION_KEY: &str = "filter";
 pub const SEGMENT_KEY: &str = "segment";
 pub const TOTAL_SEGMENTS_KEY: &str = "total_segments";
+pub const TABLE_KEY: &str = "table";
+pub const ITEM_KEY: &str = "item";
+pub const KEY_KEY: &str = "key";
 
 fn alternator_set_to_rune<I, T, F>(key: &str, iter: I, wrapper: F) -> Result<Value, AlternatorError>
 where
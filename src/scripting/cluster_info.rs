@@ -1,4 +1,59 @@
+use std::collections::HashMap;
+
+#[derive(Clone, Debug, Default)]
 pub struct ClusterInfo {
     pub name: String,
     pub db_version: String,
+    /// Number of known nodes; 0 if the topology could not be determined.
+    pub node_count: usize,
+    /// Number of nodes in each datacenter.
+    pub datacenter_map: HashMap<String, usize>,
+}
+
+impl ClusterInfo {
+    /// Returns true if the cluster topology (number of nodes or their
+    /// distribution among datacenters) differs between `self` and `other`.
+    pub fn has_changed(&self, other: &ClusterInfo) -> bool {
+        self.node_count != other.node_count || self.datacenter_map != other.datacenter_map
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cluster_info(datacenters: &[(&str, usize)]) -> ClusterInfo {
+        let datacenter_map: HashMap<String, usize> = datacenters
+            .iter()
+            .map(|(dc, n)| (dc.to_string(), *n))
+            .collect();
+        ClusterInfo {
+            name: "test".to_string(),
+            db_version: "1.0".to_string(),
+            node_count: datacenter_map.values().sum(),
+            datacenter_map,
+        }
+    }
+
+    #[test]
+    fn same_topology_is_not_a_change() {
+        let a = cluster_info(&[("dc1", 3), ("dc2", 3)]);
+        let mut b = cluster_info(&[("dc2", 3), ("dc1", 3)]);
+        b.db_version = "2.0".to_string();
+        assert!(!a.has_changed(&b));
+    }
+
+    #[test]
+    fn node_count_change_is_detected() {
+        let a = cluster_info(&[("dc1", 3)]);
+        let b = cluster_info(&[("dc1", 4)]);
+        assert!(a.has_changed(&b));
+    }
+
+    #[test]
+    fn datacenter_change_is_detected() {
+        let a = cluster_info(&[("dc1", 3), ("dc2", 3)]);
+        let b = cluster_info(&[("dc1", 3), ("dc3", 3)]);
+        assert!(a.has_changed(&b));
+    }
 }
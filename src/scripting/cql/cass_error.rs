@@ -1,14 +1,19 @@
+use crate::scripting::retry_error::RetryError;
+use crate::stats::session::ErrorCategory;
 use openssl::error::ErrorStack;
 use rune::alloc::error::Error as RuneAllocError;
 use rune::alloc::fmt::TryWrite;
 use rune::runtime::{TypeInfo, VmResult};
 use rune::{vm_write, Any, Value};
 use scylla::errors::{
-    DeserializationError, ExecutionError, NewSessionError, PrepareError, RowsError,
+    DbError as ScyllaDbError, DeserializationError, ExecutionError, NewSessionError, PrepareError,
+    RequestAttemptError, RowsError,
 };
 use scylla::response::query_result::{FirstRowError, IntoRowsResultError};
 use scylla::value::ValueOverflow;
+use std::convert::Infallible;
 use std::fmt::{Display, Formatter};
+use std::str::FromStr;
 
 #[derive(Any, Debug)]
 pub struct CassError(pub CassErrorKind);
@@ -18,6 +23,11 @@ impl CassError {
         CassError(kind)
     }
 
+    /// Wraps a plain error message.
+    pub fn from_str(message: impl Into<String>) -> CassError {
+        CassError(CassErrorKind::Error(message.into()))
+    }
+
     pub fn prepare_error(cql: &str, err: PrepareError) -> CassError {
         CassError(CassErrorKind::Prepare(cql.to_string(), err))
     }
@@ -33,6 +43,38 @@ impl CassError {
         };
         let kind = match err {
             ExecutionError::RequestTimeout(_) => CassErrorKind::Overloaded(query, err),
+            ExecutionError::LastAttemptError(RequestAttemptError::DbError(
+                ScyllaDbError::WriteTimeout {
+                    consistency,
+                    received,
+                    required,
+                    ..
+                },
+                _,
+            )) => {
+                return CassError(CassErrorKind::WriteTimeout {
+                    consistency: consistency.to_string(),
+                    received,
+                    required,
+                })
+                .with_query(cql)
+            }
+            ExecutionError::LastAttemptError(RequestAttemptError::DbError(
+                ScyllaDbError::ReadTimeout {
+                    consistency,
+                    received,
+                    required,
+                    ..
+                },
+                _,
+            )) => {
+                return CassError(CassErrorKind::ReadTimeout {
+                    consistency: consistency.to_string(),
+                    received,
+                    required,
+                })
+                .with_query(cql)
+            }
             _ => CassErrorKind::QueryExecution(query, err),
         };
         CassError(kind)
@@ -72,10 +114,89 @@ impl CassError {
         ))
     }
 
+    pub fn value_validation_error(key: &str, expected: &Value, actual: &Value) -> CassError {
+        CassError(CassErrorKind::ValueValidationError(
+            key.to_string(),
+            format!("{expected:?}"),
+            format!("{actual:?}"),
+        ))
+    }
+
     pub fn query_retries_exceeded(retry_number: u64) -> CassError {
-        CassError(CassErrorKind::QueryRetriesExceeded(format!(
-            "Max retry attempts ({retry_number}) reached",
-        )))
+        CassError(CassErrorKind::QueryRetriesExceeded(retry_number))
+    }
+
+    /// Annotates the error with information about the operation that failed.
+    /// The context is prepended to the original error message.
+    pub fn with_context(self, context: impl Into<String>) -> Self {
+        CassError(CassErrorKind::ContextualError {
+            context: context.into(),
+            cause: Box::new(self.0),
+        })
+    }
+
+    /// Annotates the error with the query whose execution failed.
+    pub fn with_query(self, cql: &str) -> Self {
+        self.with_context(format!("Error executing '{cql}'"))
+    }
+
+    /// Returns the kind of the original error, skipping any context annotations.
+    pub fn root_cause(&self) -> &CassErrorKind {
+        let mut kind = &self.0;
+        while let CassErrorKind::ContextualError { cause, .. } = kind {
+            kind = cause;
+        }
+        kind
+    }
+
+    /// Returns true if the request timed out, either on the client side
+    /// or because not enough replicas responded in time.
+    pub fn is_timeout(&self) -> bool {
+        matches!(
+            self.root_cause(),
+            CassErrorKind::Overloaded(_, ExecutionError::RequestTimeout(_))
+                | CassErrorKind::WriteTimeout { .. }
+                | CassErrorKind::ReadTimeout { .. }
+        )
+    }
+
+    /// Classifies the error for the per-category error counts.
+    pub fn category(&self) -> ErrorCategory {
+        if self.is_timeout() {
+            return ErrorCategory::Timeout;
+        }
+        match self.root_cause() {
+            CassErrorKind::Overloaded(..) => ErrorCategory::Overloaded,
+            CassErrorKind::QueryExecution(
+                _,
+                ExecutionError::LastAttemptError(RequestAttemptError::DbError(e, _)),
+            ) => match e {
+                ScyllaDbError::Unavailable { .. } => ErrorCategory::Unavailable,
+                ScyllaDbError::Overloaded | ScyllaDbError::RateLimitReached { .. } => {
+                    ErrorCategory::Overloaded
+                }
+                _ => ErrorCategory::Other,
+            },
+            CassErrorKind::QueryResponseValidationError(..)
+            | CassErrorKind::QueryResponseValidationNotApplicableError(_)
+            | CassErrorKind::ValueValidationError(..) => ErrorCategory::Validation,
+            _ => ErrorCategory::Other,
+        }
+    }
+
+    /// Returns the retry-oriented view of the error for inspection in workload scripts.
+    pub fn to_retry_error(&self) -> RetryError {
+        let kind = self.root_cause();
+        let attempts = match kind {
+            CassErrorKind::QueryRetriesExceeded(n) => *n,
+            _ => 0,
+        };
+        RetryError::new(
+            attempts,
+            self.is_timeout(),
+            matches!(kind, CassErrorKind::Overloaded(..)),
+            self.to_string(),
+        )
     }
 }
 
@@ -93,20 +214,38 @@ pub enum CassErrorKind {
     FailedToConnect(Vec<String>, NewSessionError),
     PreparedStatementNotFound(String),
     PartitionRowPresetNotFound(String),
-    QueryRetriesExceeded(String),
+    InvalidPartitionRowPreset(String),
+    QueryRetriesExceeded(u64),
     QueryParamConversion(String, String, Option<String>),
     ValueOutOfRange(String, String),
     InvalidNumberOfQueryParams,
     InvalidQueryParamsObject(TypeInfo),
     Prepare(String, PrepareError),
     Overloaded(QueryInfo, ExecutionError),
+    /// Not enough replicas acknowledged a write in time to satisfy the consistency level.
+    WriteTimeout {
+        consistency: String,
+        received: i32,
+        required: i32,
+    },
+    /// Not enough replicas responded to a read in time to satisfy the consistency level.
+    ReadTimeout {
+        consistency: String,
+        received: i32,
+        required: i32,
+    },
 
     QueryExecution(QueryInfo, ExecutionError),
     QueryResponseValidationError(QueryInfo, u64, u64, u64, String),
     QueryResponseValidationNotApplicableError(QueryInfo),
+    ValueValidationError(String, String, String),
 
     Error(String),
     CustomError(String),
+    ContextualError {
+        context: String,
+        cause: Box<CassErrorKind>,
+    },
 }
 
 #[derive(Debug)]
@@ -123,8 +262,12 @@ impl CassError {
     }
 
     pub fn display(&self, buf: &mut String) -> std::fmt::Result {
+        Self::display_kind(&self.0, buf)
+    }
+
+    fn display_kind(kind: &CassErrorKind, buf: &mut String) -> std::fmt::Result {
         use std::fmt::Write;
-        match &self.0 {
+        match kind {
             CassErrorKind::SslConfiguration(e) => {
                 write!(buf, "SSL configuration error: {e}")
             }
@@ -137,8 +280,14 @@ impl CassError {
             CassErrorKind::PartitionRowPresetNotFound(s) => {
                 write!(buf, "Partition-row preset not found: {s}")
             }
-            CassErrorKind::QueryRetriesExceeded(s) => {
-                write!(buf, "QueryRetriesExceeded: {s}")
+            CassErrorKind::InvalidPartitionRowPreset(s) => {
+                write!(buf, "Invalid partition-row preset: {s}")
+            }
+            CassErrorKind::QueryRetriesExceeded(n) => {
+                write!(
+                    buf,
+                    "QueryRetriesExceeded: Max retry attempts ({n}) reached"
+                )
             }
             CassErrorKind::ValueOutOfRange(v, t) => {
                 write!(buf, "Value {v} out of range for CQL type {t:?}")
@@ -161,6 +310,26 @@ impl CassError {
             CassErrorKind::Overloaded(q, e) => {
                 write!(buf, "Overloaded when executing query {q}: {e}")
             }
+            CassErrorKind::WriteTimeout {
+                consistency,
+                received,
+                required,
+            } => {
+                write!(
+                    buf,
+                    "Write timeout at consistency {consistency}: {received} of {required} required replicas responded"
+                )
+            }
+            CassErrorKind::ReadTimeout {
+                consistency,
+                received,
+                required,
+            } => {
+                write!(
+                    buf,
+                    "Read timeout at consistency {consistency}: {received} of {required} required replicas responded"
+                )
+            }
             CassErrorKind::QueryExecution(q, e) => {
                 write!(buf, "Failed to execute query {q}: {e}")
             }
@@ -186,12 +355,22 @@ impl CassError {
                     "Response rows can be validated only for 'SELECT' queries, Query: {q}"
                 )
             }
+            CassErrorKind::ValueValidationError(key, expected, actual) => {
+                write!(
+                    buf,
+                    "Expected {expected} in the response of prepared statement '{key}', but got {actual}"
+                )
+            }
             CassErrorKind::Error(s) => {
                 write!(buf, "Error: {s}")
             }
             CassErrorKind::CustomError(s) => {
                 write!(buf, "CustomError: {s}")
             }
+            CassErrorKind::ContextualError { context, cause } => {
+                write!(buf, "{context}: ")?;
+                Self::display_kind(cause, buf)
+            }
         }
     }
 }
@@ -311,3 +490,154 @@ impl Display for QueryInfo {
 
 pub type DbError = CassError;
 pub type DbErrorKind = CassErrorKind;
+
+impl FromStr for CassError {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(CassError::from_str(s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn with_context_prepends_message() {
+        let err = CassError(CassErrorKind::Error("boom".to_string()))
+            .with_context("inner op")
+            .with_context("outer op");
+        assert_eq!(err.to_string(), "outer op: inner op: Error: boom");
+        assert!(matches!(err.root_cause(), CassErrorKind::Error(_)));
+    }
+
+    #[test]
+    fn with_query_shows_query_text() {
+        let err = CassError::query_retries_exceeded(3).with_query("SELECT * FROM t WHERE pk = ?");
+        assert_eq!(
+            err.to_string(),
+            "Error executing 'SELECT * FROM t WHERE pk = ?': \
+             QueryRetriesExceeded: Max retry attempts (3) reached"
+        );
+        assert!(matches!(
+            err.root_cause(),
+            CassErrorKind::QueryRetriesExceeded(3)
+        ));
+    }
+
+    #[test]
+    fn replica_timeouts_are_detected() {
+        use scylla::errors::WriteType;
+        use scylla::statement::Consistency;
+
+        let write_timeout = ExecutionError::LastAttemptError(RequestAttemptError::DbError(
+            ScyllaDbError::WriteTimeout {
+                consistency: Consistency::Quorum,
+                received: 1,
+                required: 2,
+                write_type: WriteType::Simple,
+            },
+            "timed out".to_string(),
+        ));
+        let err =
+            CassError::query_execution_error("INSERT INTO t (pk) VALUES (?)", None, write_timeout);
+        assert!(err.is_timeout());
+        assert!(err.to_retry_error().timeout);
+        match err.root_cause() {
+            CassErrorKind::WriteTimeout {
+                consistency,
+                received,
+                required,
+            } => {
+                assert_eq!(consistency, "Quorum");
+                assert_eq!((*received, *required), (1, 2));
+            }
+            other => panic!("unexpected error kind: {other:?}"),
+        }
+        assert_eq!(
+            err.to_string(),
+            "Error executing 'INSERT INTO t (pk) VALUES (?)': \
+             Write timeout at consistency Quorum: 1 of 2 required replicas responded"
+        );
+
+        let read_timeout = ExecutionError::LastAttemptError(RequestAttemptError::DbError(
+            ScyllaDbError::ReadTimeout {
+                consistency: Consistency::LocalOne,
+                received: 0,
+                required: 1,
+                data_present: false,
+            },
+            "timed out".to_string(),
+        ));
+        let err = CassError::query_execution_error("SELECT * FROM t", None, read_timeout);
+        assert!(err.is_timeout());
+        assert!(matches!(
+            err.root_cause(),
+            CassErrorKind::ReadTimeout {
+                received: 0,
+                required: 1,
+                ..
+            }
+        ));
+
+        let other = ExecutionError::LastAttemptError(RequestAttemptError::DbError(
+            ScyllaDbError::Overloaded,
+            "busy".to_string(),
+        ));
+        let err = CassError::query_execution_error("SELECT * FROM t", None, other);
+        assert!(!err.is_timeout());
+        assert!(!CassError::from_str("boom").is_timeout());
+    }
+
+    #[test]
+    fn errors_are_categorized() {
+        use scylla::statement::Consistency;
+
+        let db_error = |e: ScyllaDbError| {
+            CassError::query_execution_error(
+                "SELECT * FROM t",
+                None,
+                ExecutionError::LastAttemptError(RequestAttemptError::DbError(e, String::new())),
+            )
+        };
+        let unavailable = db_error(ScyllaDbError::Unavailable {
+            consistency: Consistency::Quorum,
+            required: 2,
+            alive: 1,
+        });
+        assert_eq!(unavailable.category(), ErrorCategory::Unavailable);
+        let read_timeout = db_error(ScyllaDbError::ReadTimeout {
+            consistency: Consistency::One,
+            received: 0,
+            required: 1,
+            data_present: false,
+        });
+        assert_eq!(read_timeout.category(), ErrorCategory::Timeout);
+        assert_eq!(
+            db_error(ScyllaDbError::Overloaded).category(),
+            ErrorCategory::Overloaded
+        );
+        assert_eq!(
+            db_error(ScyllaDbError::SyntaxError).category(),
+            ErrorCategory::Other
+        );
+        let validation = CassError(CassErrorKind::ValueValidationError(
+            "v".to_string(),
+            "1".to_string(),
+            "2".to_string(),
+        ))
+        .with_context("select");
+        assert_eq!(validation.category(), ErrorCategory::Validation);
+        assert_eq!(CassError::from_str("boom").category(), ErrorCategory::Other);
+    }
+
+    #[test]
+    fn from_str_wraps_message() {
+        let err = CassError::from_str(format!("bad {}", "thing"));
+        assert!(matches!(err.0, CassErrorKind::Error(_)));
+        assert_eq!(err.to_string(), "Error: bad thing");
+        let err: CassError = "bad thing".parse().unwrap();
+        assert_eq!(err.to_string(), "Error: bad thing");
+    }
+}
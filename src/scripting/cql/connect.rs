@@ -32,6 +32,10 @@ fn tls_context(conf: &&ConnectionConf) -> Result<Option<TlsContext>, Box<CassErr
 
 /// Configures connection to Cassandra.
 pub async fn connect(conf: &ConnectionConf) -> Result<Context, CassError> {
+    let conf = &conf
+        .to_builder()
+        .build()
+        .map_err(|e| CassError(CassErrorKind::Error(e)))?;
     let mut policy_builder = DefaultPolicy::builder().token_aware(true);
     let mut datacenter: String = "".to_string();
     let mut rack: String = "".to_string();
@@ -73,6 +77,6 @@ pub async fn connect(conf: &ConnectionConf) -> Result<Context, CassError> {
         rack,
         conf.retry_number,
         conf.retry_interval,
-        conf.validation_strategy,
+        conf.validation_strategy.clone(),
     ))
 }
@@ -4,22 +4,31 @@ use super::serialize::RuneQueryParams;
 use crate::config::{RetryInterval, ValidationStrategy};
 use crate::error::LatteError;
 use crate::scripting::cluster_info::ClusterInfo;
+use crate::scripting::functions_common::{call_validation_callback, SharedState};
 use crate::scripting::retry_error::handle_retry_error;
 use crate::scripting::row_distribution::RowDistributionPreset;
+use crate::scripting::rune_uuid::Uuid;
 use crate::stats::session::SessionStats;
+use hdrhistogram::SubtractionError;
 
 use once_cell::sync::Lazy;
 use regex::Regex;
-use rune::runtime::{Object, Vec as RuneVec};
+use rune::alloc::clone::TryClone;
+use rune::runtime::{Object, OwnedTuple, SyncFunction, Vec as RuneVec};
 use rune::{Any, Value};
 use scylla::client::session::Session;
+use scylla::cluster::metadata::{CollectionType, ColumnType, NativeType};
+use scylla::errors::ExecutionError;
+use scylla::response::query_result::QueryResult;
 use scylla::response::PagingState;
 use scylla::statement::batch::{Batch, BatchType};
 use scylla::statement::prepared::PreparedStatement;
 use scylla::statement::unprepared::Statement;
 use std::collections::{HashMap, HashSet};
+use std::future::Future;
 use std::ops::ControlFlow;
-use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::time::Instant;
 use try_lock::TryLock;
@@ -27,24 +36,100 @@ use try_lock::TryLock;
 static IS_SELECT_QUERY: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?i)^\s*select\b").unwrap());
 static IS_SELECT_COUNT_QUERY: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"(?i)^\s*select\s+count\s*\(\s*[^)]*\s*\)").unwrap());
+static SELECT_COLUMNS: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?is)^\s*select\s+.+?\s+from\b").unwrap());
+static COUNT_CHANGING_CLAUSE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"(?i)\b(distinct|per\s+partition\s+limit|limit|group\s+by)\b").unwrap()
+});
+
+/// Rewrites a `SELECT <columns> FROM ...` query into `SELECT count(*) FROM ...`,
+/// so the server returns only the number of matching rows.
+/// Queries which already select a count are returned unchanged.
+/// Queries with `DISTINCT`, `LIMIT`, `PER PARTITION LIMIT` or `GROUP BY` are rejected,
+/// as the count of their rewritten form differs from the number of rows they return.
+#[allow(clippy::result_large_err)]
+fn to_count_query(cql: &str) -> Result<String, CassError> {
+    if IS_SELECT_COUNT_QUERY.is_match(cql) {
+        return Ok(cql.to_string());
+    }
+    let columns = SELECT_COLUMNS
+        .find(cql)
+        .ok_or_else(|| CassError::query_response_validation_not_applicable_error(cql, None))?;
+    if let Some(clause) = COUNT_CHANGING_CLAUSE.find(cql) {
+        return Err(CassError(CassErrorKind::Error(format!(
+            "Cannot count the rows of query \"{cql}\" with count(*), \
+             because '{}' changes the count; pass a 'SELECT count(...)' query instead",
+            clause.as_str()
+        ))));
+    }
+    Ok(format!("SELECT count(*) FROM{}", &cql[columns.end()..]))
+}
+
+/// Name of the bind variable of the `USING TTL ?` clause, as reported in the statement metadata.
+const TTL_BIND_MARKER: &str = "[ttl]";
+
+/// Returns a copy of the bind parameters extended with one more value.
+/// Positional parameters get the value appended at the end,
+/// named parameters get it under the given bind variable name.
+fn with_extra_bind_value(
+    params: &Value,
+    name: &str,
+    value: Value,
+) -> Result<Value, Box<CassError>> {
+    let extend = |values: &[Value]| -> Result<Value, rune::alloc::Error> {
+        let mut extended = RuneVec::with_capacity(values.len() + 1)?;
+        for v in values {
+            extended.push(v.clone())?;
+        }
+        extended.push(value.clone())?;
+        Value::vec(extended.into_inner())
+    };
+    if let Ok(vec) = params.borrow_ref::<RuneVec>() {
+        return Ok(extend(&vec).map_err(CassError::from)?);
+    }
+    if let Ok(tuple) = params.borrow_ref::<OwnedTuple>() {
+        return Ok(extend(&tuple).map_err(CassError::from)?);
+    }
+    if let Ok(obj) = params.borrow_ref::<Object>() {
+        let mut extended = obj.try_clone().map_err(CassError::from)?;
+        extended
+            .insert(
+                rune::alloc::String::try_from(name).map_err(CassError::from)?,
+                value,
+            )
+            .map_err(CassError::from)?;
+        return Ok(Value::new(extended).map_err(CassError::from)?);
+    }
+    Err(Box::new(CassError(CassErrorKind::Error(format!(
+        "Cannot bind '{name}': parameters must be a vector, a tuple or an object"
+    )))))
+}
 
 /// This is the main object that a workload script uses to interface with the outside world.
 /// It also tracks query execution metrics such as number of requests, rows, response times etc.
 #[derive(Any)]
 pub struct Context {
     pub start_time: TryLock<Instant>,
+    /// Number of operations paced by `rate_limit` since the start time.
+    pub paced_op_count: Arc<AtomicU64>,
     // NOTE: 'session' is defined as optional for being able to test methods
     // which don't 'depend on'/'use' the 'session' object.
     session: Option<Arc<Session>>,
     page_size: u64,
-    statements: Arc<TryLock<HashMap<String, Arc<PreparedStatement>>>>,
+    statements: Arc<Mutex<HashMap<String, Arc<PreparedStatement>>>>,
     pub stats: Arc<TryLock<SessionStats>>,
-    pub report_metadata: Arc<TryLock<HashMap<String, String>>>,
-    pub metric_orientations: Arc<TryLock<HashMap<String, i8>>>,
+    /// Snapshot of `stats` at the last `take_session_stats`.
+    stats_baseline: Arc<TryLock<SessionStats>>,
+    pub report_metadata: Arc<Mutex<HashMap<String, String>>>,
+    pub metric_orientations: Arc<Mutex<HashMap<String, i8>>>,
     pub retry_number: u64,
     pub retry_interval: RetryInterval,
     pub validation_strategy: ValidationStrategy,
-    pub partition_row_presets: Arc<TryLock<HashMap<String, RowDistributionPreset>>>,
+    /// Workload function resolved for the `custom:<function>` validation strategy.
+    pub validation_callback: Option<Arc<SyncFunction>>,
+    pub partition_row_presets: Arc<Mutex<HashMap<String, RowDistributionPreset>>>,
+    /// Shared by all clones, including worker copies, unlike `data`.
+    pub shared_state: SharedState,
     #[rune(get, set, add_assign, copy)]
     pub load_cycle_count: u64,
     #[rune(get)]
@@ -81,16 +166,20 @@ impl Context {
         let data = Value::new(Object::new()).unwrap();
         Context {
             start_time: TryLock::new(Instant::now()),
+            paced_op_count: Arc::new(AtomicU64::new(0)),
             session: session.map(Arc::new),
             page_size,
-            statements: Arc::new(TryLock::new(HashMap::new())),
+            statements: Arc::new(Mutex::new(HashMap::new())),
             stats: Arc::new(TryLock::new(SessionStats::new())),
-            report_metadata: Arc::new(TryLock::new(HashMap::new())),
-            metric_orientations: Arc::new(TryLock::new(HashMap::new())),
+            stats_baseline: Arc::new(TryLock::new(SessionStats::new())),
+            report_metadata: Arc::new(Mutex::new(HashMap::new())),
+            metric_orientations: Arc::new(Mutex::new(HashMap::new())),
             retry_number,
             retry_interval,
             validation_strategy,
-            partition_row_presets: Arc::new(TryLock::new(HashMap::new())),
+            validation_callback: None,
+            partition_row_presets: Arc::new(Mutex::new(HashMap::new())),
+            shared_state: SharedState::default(),
             load_cycle_count: 0,
             preferred_datacenter,
             preferred_rack,
@@ -100,7 +189,7 @@ impl Context {
     }
 
     /// Clones the context for use by another thread.
-    /// The new clone gets fresh statistics.
+    /// The new clone gets fresh statistics and start time.
     /// The user data gets passed through serialization and deserialization to avoid
     /// accidental data sharing.
     pub fn clone(&self) -> Result<Self, LatteError> {
@@ -109,26 +198,28 @@ impl Context {
         Ok(Context {
             session: self.session.clone(),
             page_size: self.page_size,
-            statements: Arc::new(TryLock::new(self.statements.try_lock().unwrap().clone())),
-            stats: Arc::new(TryLock::new(SessionStats::default())),
-            report_metadata: Arc::new(TryLock::new(
-                self.report_metadata.try_lock().unwrap().clone(),
-            )),
-            metric_orientations: Arc::new(TryLock::new(
-                self.metric_orientations.try_lock().unwrap().clone(),
+            statements: Arc::new(Mutex::new(self.statements.lock().unwrap().clone())),
+            stats: Arc::new(TryLock::new(SessionStats::new())),
+            stats_baseline: Arc::new(TryLock::new(SessionStats::new())),
+            report_metadata: Arc::new(Mutex::new(self.report_metadata.lock().unwrap().clone())),
+            metric_orientations: Arc::new(Mutex::new(
+                self.metric_orientations.lock().unwrap().clone(),
             )),
             retry_number: self.retry_number,
             retry_interval: self.retry_interval,
-            validation_strategy: self.validation_strategy,
-            partition_row_presets: Arc::new(TryLock::new(
-                self.partition_row_presets.try_lock().unwrap().clone(),
+            validation_strategy: self.validation_strategy.clone(),
+            validation_callback: self.validation_callback.clone(),
+            partition_row_presets: Arc::new(Mutex::new(
+                self.partition_row_presets.lock().unwrap().clone(),
             )),
+            shared_state: self.shared_state.clone(),
             load_cycle_count: self.load_cycle_count,
             preferred_datacenter: self.preferred_datacenter.clone(),
             preferred_rack: self.preferred_rack.clone(),
             is_worker_clone: true,
             data: deserialized,
-            start_time: TryLock::new(*self.start_time.try_lock().unwrap()),
+            start_time: TryLock::new(Instant::now()),
+            paced_op_count: Arc::new(AtomicU64::new(0)),
         })
     }
 
@@ -138,16 +229,20 @@ impl Context {
     pub fn shallow_clone(&self) -> Self {
         Context {
             start_time: TryLock::new(*self.start_time.try_lock().unwrap()),
+            paced_op_count: Arc::clone(&self.paced_op_count),
             session: self.session.clone(),
             page_size: self.page_size,
             statements: Arc::clone(&self.statements),
             stats: Arc::clone(&self.stats),
+            stats_baseline: Arc::clone(&self.stats_baseline),
             report_metadata: Arc::clone(&self.report_metadata),
             metric_orientations: Arc::clone(&self.metric_orientations),
             retry_number: self.retry_number,
             retry_interval: self.retry_interval,
-            validation_strategy: self.validation_strategy,
+            validation_strategy: self.validation_strategy.clone(),
+            validation_callback: self.validation_callback.clone(),
             partition_row_presets: Arc::clone(&self.partition_row_presets),
+            shared_state: self.shared_state.clone(),
             load_cycle_count: self.load_cycle_count,
             preferred_datacenter: self.preferred_datacenter.clone(),
             preferred_rack: self.preferred_rack.clone(),
@@ -166,6 +261,13 @@ impl Context {
                 )))
             }
         };
+        let mut node_count = 0;
+        let mut datacenter_map = HashMap::new();
+        for node in session.get_cluster_state().get_nodes_info() {
+            node_count += 1;
+            let dc = node.datacenter.clone().unwrap_or_default();
+            *datacenter_map.entry(dc).or_insert(0) += 1;
+        }
         let scylla_cql = "SELECT version, build_id FROM system.versions";
         let rs = session
             .query_unpaged(scylla_cql, ())
@@ -181,6 +283,8 @@ impl Context {
                             db_version: format!(
                                 "ScyllaDB {scylla_version} with build-id {build_id}",
                             ),
+                            node_count,
+                            datacenter_map,
                         }));
                     }
                 }
@@ -202,6 +306,8 @@ impl Context {
                                 return Ok(Some(ClusterInfo {
                                     name: name.to_string(),
                                     db_version: format!("Cassandra {cass_version}"),
+                                    node_count,
+                                    datacenter_map,
                                 }));
                             }
                         }
@@ -237,6 +343,14 @@ impl Context {
         }
     }
 
+    /// Returns the number of connections currently open to all nodes of the cluster,
+    /// as reported by the driver metrics. Returns 0 when there is no session.
+    pub fn connection_count(&self) -> u64 {
+        self.session
+            .as_ref()
+            .map_or(0, |session| session.get_metrics().get_total_connections())
+    }
+
     /// Prepares a statement and stores it in an internal statement map for future use.
     pub async fn prepare(&self, key: &str, cql: &str) -> Result<(), CassError> {
         match &self.session {
@@ -246,7 +360,7 @@ impl Context {
                     .await
                     .map_err(|e| CassError::prepare_error(cql, e))?;
                 self.statements
-                    .try_lock()
+                    .lock()
                     .unwrap()
                     .insert(key.to_string(), Arc::new(statement));
                 Ok(())
@@ -290,6 +404,42 @@ impl Context {
         .await
     }
 
+    /// Executes an ad-hoc CQL statement with positional bind parameters.
+    /// The statement is not registered as a prepared statement. Note that the driver still needs
+    /// to learn the types of the bound columns, so each call with non-empty `params`
+    /// performs an additional round trip to a single node to prepare the statement.
+    pub async fn execute_unprepared(&self, cql: &str, params: Value) -> Result<Value, CassError> {
+        let session = match &self.session {
+            Some(session) => session,
+            None => {
+                return Err(CassError(CassErrorKind::Error(
+                    "'session' is not defined".to_string(),
+                )))
+            }
+        };
+        let stmt = Statement::new(cql).with_page_size(self.page_size as i32);
+        let query_params = RuneQueryParams::new(Some(&params));
+        self._execute_unpaged("execute", cql, &params, || {
+            session.query_unpaged(stmt.clone(), &query_params)
+        })
+        .await?;
+        Ok(Value::vec(Default::default())?)
+    }
+
+    /// Validates the number of rows matching a `select` query without fetching the rows.
+    /// The query is rewritten to the `SELECT count(*) FROM ...` form, so only the count
+    /// is transferred, and the count is validated the same way as in `execute_with_validation`.
+    pub async fn execute_count_validation(
+        &self,
+        cql: &str,
+        expected_rows_num_min: u64,
+        expected_rows_num_max: u64,
+    ) -> Result<Value, CassError> {
+        let count_cql = to_count_query(cql)?;
+        self.execute_with_validation(&count_cql, expected_rows_num_min, expected_rows_num_max, "")
+            .await
+    }
+
     /// Executes a statement prepared and registered earlier by a call to `prepare`.
     pub async fn execute_prepared(&self, key: &str, params: Value) -> Result<Value, CassError> {
         self._execute(None, Some(key), Some(params), None, None, None, false)
@@ -305,6 +455,28 @@ impl Context {
         expected_rows_num_min: u64,
         expected_rows_num_max: u64,
         custom_err_msg: &str,
+    ) -> Result<Value, CassError> {
+        self.execute_prepared_with_validation_strategy(
+            key,
+            params,
+            expected_rows_num_min,
+            expected_rows_num_max,
+            custom_err_msg,
+            &self.validation_strategy,
+        )
+        .await
+    }
+
+    /// Same as `execute_prepared_with_validation`, but validation errors are handled
+    /// according to the given strategy instead of the one configured for the whole run.
+    pub async fn execute_prepared_with_validation_strategy(
+        &self,
+        key: &str,
+        params: Value,
+        expected_rows_num_min: u64,
+        expected_rows_num_max: u64,
+        custom_err_msg: &str,
+        validation_strategy: &ValidationStrategy,
     ) -> Result<Value, CassError> {
         if expected_rows_num_min > expected_rows_num_max {
             return Err(CassError(CassErrorKind::Error(format!(
@@ -312,7 +484,7 @@ impl Context {
                      cannot be less than 'maximum' ({expected_rows_num_max})"
             ))));
         }
-        self._execute(
+        self._execute_with_strategy(
             None,
             Some(key),
             Some(params),
@@ -320,17 +492,149 @@ impl Context {
             Some(expected_rows_num_max),
             Some(custom_err_msg),
             false,
+            validation_strategy,
         )
         .await
     }
 
+    /// Executes a statement prepared and registered earlier by a call to `prepare`,
+    /// setting the time to live of the written data to `ttl_secs`.
+    /// The statement must end its bind variables with a `USING TTL ?` marker, e.g.
+    /// `INSERT INTO t (pk, v) VALUES (?, ?) USING TTL ?`, so a single prepared statement
+    /// serves any TTL. Positional parameters should not include the TTL value;
+    /// it is appended by this function. TTL is not supported by counter tables,
+    /// so such statements fail to prepare.
+    pub async fn execute_prepared_with_ttl(
+        &self,
+        key: &str,
+        params: Value,
+        ttl_secs: i32,
+    ) -> Result<Value, CassError> {
+        if ttl_secs < 0 {
+            return Err(CassError(CassErrorKind::Error(format!(
+                "TTL must not be negative, got {ttl_secs}"
+            ))));
+        }
+        let has_ttl_marker = self
+            .statements
+            .lock()
+            .unwrap()
+            .get(key)
+            .ok_or_else(|| CassError(CassErrorKind::PreparedStatementNotFound(key.to_string())))?
+            .get_variable_col_specs()
+            .iter()
+            .last()
+            .is_some_and(|col| col.name() == TTL_BIND_MARKER);
+        if !has_ttl_marker {
+            return Err(CassError(CassErrorKind::Error(format!(
+                "Statement '{key}' must be prepared with a 'USING TTL ?' bind marker \
+                 as its last bind variable"
+            ))));
+        }
+        let params = with_extra_bind_value(&params, TTL_BIND_MARKER, Value::from(ttl_secs as i64))
+            .map_err(|e| *e)?;
+        self.execute_prepared(key, params).await
+    }
+
+    /// Executes a statement prepared and registered earlier by a call to `prepare`
+    /// with the given client-side write timestamp, in microseconds since the Unix epoch.
+    /// The timestamp is set on a copy of the prepared statement, so other executions
+    /// of it keep using the default timestamps.
+    /// Meant for writes: like `execute_prepared`, it does not return the result rows.
+    pub async fn execute_prepared_with_timestamp(
+        &self,
+        key: &str,
+        params: Value,
+        timestamp_micros: i64,
+    ) -> Result<Value, CassError> {
+        self._execute_prepared_with(key, &params, |stmt| {
+            stmt.set_timestamp(Some(timestamp_micros))
+        })
+        .await?;
+        Ok(Value::vec(Default::default())?)
+    }
+
+    /// Returns the token the driver would route a prepared statement bound with `params` to,
+    /// without executing the statement.
+    pub fn compute_token(&self, key: &str, params: Value) -> Result<i64, Box<CassError>> {
+        let stmt = self
+            .statements
+            .lock()
+            .unwrap()
+            .get(key)
+            .cloned()
+            .ok_or_else(|| {
+                Box::new(CassError(CassErrorKind::PreparedStatementNotFound(
+                    key.to_string(),
+                )))
+            })?;
+        let query_params = RuneQueryParams::new(Some(&params));
+        let token = stmt.calculate_token(&query_params).map_err(|e| {
+            Box::new(CassError(CassErrorKind::Error(format!(
+                "Failed to compute token for statement '{key}': {e}"
+            ))))
+        })?;
+        match token {
+            Some(token) => Ok(token.value()),
+            None => Err(Box::new(CassError(CassErrorKind::Error(format!(
+                "Statement '{key}' does not bind the full partition key"
+            ))))),
+        }
+    }
+
+    /// Returns the CQL types of the bind variables of a prepared statement, in bind order.
+    pub fn prepared_variable_types(&self, key: &str) -> Result<Vec<String>, Box<CassError>> {
+        let statements = self.statements.lock().unwrap();
+        let stmt = statements.get(key).ok_or_else(|| {
+            Box::new(CassError(CassErrorKind::PreparedStatementNotFound(
+                key.to_string(),
+            )))
+        })?;
+        Ok(stmt
+            .get_variable_col_specs()
+            .iter()
+            .map(|col| cql_type_name(col.typ()))
+            .collect())
+    }
+
+    /// Executes a statement prepared and registered earlier by a call to `prepare` with
+    /// server-side tracing enabled for this single execution.
+    /// Returns the result data together with the id of the tracing session,
+    /// which can be looked up later in the `system_traces` keyspace.
+    /// Tracing is set on a copy of the prepared statement, so other executions of it
+    /// are not affected.
+    pub async fn execute_prepared_traced(
+        &self,
+        key: &str,
+        params: Value,
+    ) -> Result<(Value, Uuid), CassError> {
+        let result = self
+            ._execute_prepared_with(key, &params, |stmt| stmt.set_tracing(true))
+            .await?;
+        let tracing_id = result.tracing_id().ok_or_else(|| {
+            CassError(CassErrorKind::Error(format!(
+                "No tracing session id returned for statement '{key}'"
+            )))
+        })?;
+        let mut rune_rows = RuneVec::new();
+        if result.is_rows() {
+            for row in result.into_rows_result()?.rows::<RuneRow>()? {
+                let RuneRow(row_obj) = row?;
+                rune_rows.push(Value::new(row_obj)?)?;
+            }
+        }
+        Ok((Value::vec(rune_rows.into_inner())?, Uuid(tracing_id)))
+    }
+
     /// Executes an ad-hoc CQL statement and returns the result data.
+    /// Each row is returned as an object mapping column names to values; NULLs become `None`.
     pub async fn execute_with_result(&self, cql: &str) -> Result<Value, CassError> {
         self._execute(Some(cql), None, None, None, None, None, true)
             .await
     }
 
     /// Executes a statement prepared and registered earlier by a call to `prepare` and returns the result data.
+    /// Rows are returned in the same form as in `execute_with_result`.
     pub async fn execute_prepared_with_result(
         &self,
         key: &str,
@@ -340,6 +644,91 @@ impl Context {
             .await
     }
 
+    /// Executes a statement prepared and registered earlier by a call to `prepare` and checks
+    /// that it returned a single row with a single column equal to `expected`.
+    /// Returns the read value.
+    pub async fn execute_prepared_verify(
+        &self,
+        key: &str,
+        params: Value,
+        expected: Value,
+    ) -> Result<Value, CassError> {
+        let rows = self.execute_prepared_with_result(key, params).await?;
+        verify_single_value(key, rows, &expected).map_err(|e| *e)
+    }
+
+    /// Executes a copy of the prepared statement registered under `key`,
+    /// adjusted by `configure`, e.g. to set its timestamp or enable tracing.
+    async fn _execute_prepared_with(
+        &self,
+        key: &str,
+        params: &Value,
+        configure: impl FnOnce(&mut PreparedStatement),
+    ) -> Result<QueryResult, CassError> {
+        let session = match &self.session {
+            Some(session) => session,
+            None => {
+                return Err(CassError(CassErrorKind::Error(
+                    "'session' is not defined".to_string(),
+                )))
+            }
+        };
+        let mut stmt = self
+            .statements
+            .lock()
+            .unwrap()
+            .get(key)
+            .map(|stmt| (**stmt).clone())
+            .ok_or_else(|| CassError(CassErrorKind::PreparedStatementNotFound(key.to_string())))?;
+        configure(&mut stmt);
+        let query_params = RuneQueryParams::new(Some(params));
+        self._execute_unpaged("execute_prepared", stmt.get_statement(), params, || {
+            session.execute_unpaged(&stmt, &query_params)
+        })
+        .await
+    }
+
+    /// Runs a single unpaged request with `execute`, retrying failed attempts
+    /// with backoff, and records the result in the session stats as `op`.
+    async fn _execute_unpaged<F, Fut>(
+        &self,
+        op: &str,
+        cql: &str,
+        params: &Value,
+        execute: F,
+    ) -> Result<QueryResult, CassError>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<QueryResult, ExecutionError>>,
+    {
+        let mut current_attempt_num = 0;
+        while current_attempt_num <= self.retry_number {
+            let start_time = self.stats.try_lock().unwrap().start_request();
+            let rs = execute().await;
+            let duration = Instant::now() - start_time;
+            match rs {
+                Ok(result) => {
+                    let rows_num = result
+                        .clone()
+                        .into_rows_result()
+                        .map(|rows| rows.rows_num() as u64)
+                        .unwrap_or(0);
+                    self.stats
+                        .try_lock()
+                        .unwrap()
+                        .complete_request(op, duration, rows_num);
+                    return Ok(result);
+                }
+                Err(e) => {
+                    let current_error = CassError::query_execution_error(cql, Some(params), e);
+                    handle_retry_error(self, current_attempt_num, current_error).await;
+                    current_attempt_num += 1;
+                }
+            }
+        }
+        Err(CassError::query_retries_exceeded(self.retry_number).with_query(cql))
+    }
+
     #[allow(clippy::too_many_arguments)]
     async fn _execute(
         &self,
@@ -350,6 +739,31 @@ impl Context {
         expected_rows_num_max: Option<u64>,
         custom_err_msg: Option<&str>,
         process_and_return_data: bool,
+    ) -> Result<Value, CassError> {
+        self._execute_with_strategy(
+            cql,
+            key,
+            params,
+            expected_rows_num_min,
+            expected_rows_num_max,
+            custom_err_msg,
+            process_and_return_data,
+            &self.validation_strategy,
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn _execute_with_strategy(
+        &self,
+        cql: Option<&str>,
+        key: Option<&str>,
+        params: Option<Value>,
+        expected_rows_num_min: Option<u64>,
+        expected_rows_num_max: Option<u64>,
+        custom_err_msg: Option<&str>,
+        process_and_return_data: bool,
+        validation_strategy: &ValidationStrategy,
     ) -> Result<Value, CassError> {
         let session = match &self.session {
             Some(session) => session,
@@ -364,9 +778,14 @@ impl Context {
                 "Either 'cql' or 'key' is allowed, not both".to_string(),
             )));
         }
+        let op = if key.is_some() {
+            "execute_prepared"
+        } else {
+            "execute"
+        };
         let stmt = if let Some(key) = key {
             self.statements
-                .try_lock()
+                .lock()
                 .unwrap()
                 .get(key)
                 .cloned()
@@ -401,6 +820,16 @@ impl Context {
             )));
         }
         let is_select_count = IS_SELECT_COUNT_QUERY.is_match(cql);
+        // The custom validation function checks the rows of every `select` query,
+        // whether or not row count validation was requested for it
+        let custom_validation = match validation_strategy {
+            ValidationStrategy::Custom { callback } if IS_SELECT_QUERY.is_match(cql) => {
+                Some(callback.as_str())
+            }
+            _ => None,
+        };
+        // Rows are also collected when they need to be passed to a custom validation function
+        let collect_rows = process_and_return_data || custom_validation.is_some();
         let mut all_pages_duration = Duration::ZERO;
         let mut paging_state = PagingState::start();
         let mut rune_rows = RuneVec::new();
@@ -424,7 +853,7 @@ impl Context {
                 }
             };
             let rows_result = page.into_rows_result();
-            if process_and_return_data {
+            if collect_rows {
                 let rows_result = rows_result?;
                 let row_iterator = rows_result.rows::<RuneRow>()?;
                 for row_result in row_iterator {
@@ -448,6 +877,9 @@ impl Context {
                     }
                 }
                 rows_num = rune_rows.len() as u64;
+                if is_select_count {
+                    last_rows_result = Some(Ok(rows_result));
+                }
             } else {
                 if let Ok(ref rr) = rows_result {
                     rows_num += rr.rows_num() as u64;
@@ -459,60 +891,86 @@ impl Context {
             all_pages_duration += current_duration;
             match paging_state_response.into_paging_control_flow() {
                 ControlFlow::Break(()) => {
-                    self.stats
-                        .try_lock()
-                        .unwrap()
-                        .complete_request(all_pages_duration, rows_num);
-                    if process_and_return_data {
-                        return Value::vec(rune_rows.into_inner()).map_err(|_| {
-                            CassError(CassErrorKind::Error(
-                                "Failed to create shared result vector".to_string(),
-                            ))
-                        });
-                    } else {
-                        let empty_rune_vec = Value::vec(Default::default())?;
-                        let rows_min = match expected_rows_num_min {
-                            None => return Ok(empty_rune_vec),
-                            Some(rows_min) => rows_min,
-                        };
+                    self.stats.try_lock().unwrap().complete_request(
+                        op,
+                        all_pages_duration,
+                        rows_num,
+                    );
+                    let mut count_error = None;
+                    if let Some(rows_min) = expected_rows_num_min {
                         let (rows_max, mut rows_cnt) = (expected_rows_num_max.unwrap(), rows_num);
-                        if is_select_count {
+                        let rows_cnt_is_expected = if is_select_count {
                             rows_cnt = last_rows_result
                                 .take()
                                 .expect("SELECT COUNT should have rows_result")?
                                 .first_row::<(i64,)>()?
                                 .0 as u64;
-                            if rows_num == 1 && rows_min <= rows_cnt && rows_cnt <= rows_max {
-                                return Ok(empty_rune_vec); // SELECT COUNT(...) returned expected rows number
-                            }
-                        } else if rows_min <= rows_num && rows_num <= rows_max {
-                            return Ok(empty_rune_vec); // Common 'SELECT' returned expected number of rows in total
+                            // SELECT COUNT(...) returned expected rows number
+                            rows_num == 1 && rows_min <= rows_cnt && rows_cnt <= rows_max
+                        } else {
+                            // Common 'SELECT' returned expected number of rows in total
+                            rows_min <= rows_num && rows_num <= rows_max
+                        };
+                        if !rows_cnt_is_expected {
+                            count_error = Some(CassError::query_validation_error(
+                                cql,
+                                params.as_ref(),
+                                rows_min,
+                                rows_max,
+                                rows_cnt,
+                                custom_err_msg.unwrap_or("").to_string(),
+                            ));
                         }
-                        let current_error = CassError::query_validation_error(
-                            cql,
-                            params.as_ref(),
-                            rows_min,
-                            rows_max,
-                            rows_cnt,
-                            custom_err_msg.unwrap_or("").to_string(),
-                        );
-                        if self.validation_strategy == ValidationStrategy::Retry {
+                    }
+                    let rows =
+                        Value::vec(std::mem::take(&mut rune_rows).into_inner()).map_err(|_| {
+                            CassError(CassErrorKind::Error(
+                                "Failed to create shared result vector".to_string(),
+                            ))
+                        })?;
+                    let result = if process_and_return_data {
+                        rows.clone()
+                    } else {
+                        Value::vec(Default::default())?
+                    };
+                    let current_error = match (count_error, custom_validation) {
+                        (Some(error), _) => error,
+                        (None, None) => return Ok(result),
+                        (None, Some(callback)) => match call_validation_callback(
+                            self.validation_callback.as_deref(),
+                            callback,
+                            rows,
+                        ) {
+                            Ok(true) => return Ok(result),
+                            Ok(false) => CassError(CassErrorKind::CustomError(format!(
+                                "Validation function '{callback}' rejected the result of query \"{cql}\""
+                            ))),
+                            Err(e) => {
+                                let err = CassError(CassErrorKind::Error(e));
+                                self.stats.try_lock().unwrap().record_error(err.category());
+                                return Err(err);
+                            }
+                        },
+                    };
+                    self.stats.try_lock().unwrap().record_validation_failure();
+                    match validation_strategy {
+                        ValidationStrategy::Retry => {
                             handle_retry_error(self, current_attempt_num, current_error).await;
                             current_attempt_num += 1;
                             rows_num = 0; // we retry all pages, so reset cnt
                             last_rows_result = None;
                             continue; // try again the same query
-                        } else if self.validation_strategy == ValidationStrategy::FailFast {
+                        }
+                        ValidationStrategy::FailFast | ValidationStrategy::Custom { .. } => {
+                            self.stats
+                                .try_lock()
+                                .unwrap()
+                                .record_error(current_error.category());
                             return Err(current_error); // stop stress execution
-                        } else if self.validation_strategy == ValidationStrategy::Ignore {
+                        }
+                        ValidationStrategy::Ignore => {
                             handle_retry_error(self, current_attempt_num, current_error).await;
-                            return Ok(empty_rune_vec); // handle/print error and go on.
-                        } else {
-                            // should never reach this code branch
-                            return Err(CassError(CassErrorKind::Error(format!(
-                                "Unexpected value for the validation strategy param: {:?}",
-                                self.validation_strategy,
-                            ))));
+                            return Ok(result); // handle/print error and go on.
                         }
                     }
                 }
@@ -523,7 +981,7 @@ impl Context {
                 }
             }
         }
-        Err(CassError::query_retries_exceeded(self.retry_number))
+        Err(CassError::query_retries_exceeded(self.retry_number).with_query(cql))
     }
 
     pub async fn batch_prepared(
@@ -542,19 +1000,22 @@ impl Context {
         }
         let mut batch: Batch = Batch::new(BatchType::Logged);
         let mut batch_values: Vec<RuneQueryParams<'_>> = Vec::with_capacity(keys_len);
+        let mut batch_statements = Vec::with_capacity(keys_len);
         for (i, key) in keys.into_iter().enumerate() {
             let statement = self
                 .statements
-                .try_lock()
+                .lock()
                 .unwrap()
                 .get(key)
                 .cloned()
                 .ok_or_else(|| {
                     CassError(CassErrorKind::PreparedStatementNotFound(key.to_string()))
                 })?;
+            batch_statements.push(statement.get_statement().to_string());
             batch.append_statement((*statement).clone());
             batch_values.push(RuneQueryParams::new(params.get(i)));
         }
+        let batch_cql = batch_query_text(&batch_statements);
         match &self.session {
             Some(session) => {
                 let mut current_attempt_num = 0;
@@ -564,23 +1025,25 @@ impl Context {
                     let duration = Instant::now() - start_time;
                     match rs {
                         Ok(_) => {
-                            self.stats
-                                .try_lock()
-                                .unwrap()
-                                .complete_request(duration, batch_values.len() as u64);
+                            self.stats.try_lock().unwrap().complete_request(
+                                "batch",
+                                duration,
+                                batch_values.len() as u64,
+                            );
                             return Ok(());
                         }
                         Err(e) => {
                             let current_error = CassError(CassErrorKind::Error(format!(
                                 "batch execution failed: {e}"
-                            )));
+                            )))
+                            .with_query(&batch_cql);
                             handle_retry_error(self, current_attempt_num, current_error).await;
                             current_attempt_num += 1;
                             continue;
                         }
                     }
                 }
-                Err(CassError::query_retries_exceeded(self.retry_number))
+                Err(CassError::query_retries_exceeded(self.retry_number).with_query(&batch_cql))
             }
             None => Err(CassError(CassErrorKind::Error(
                 "'session' is not defined".to_string(),
@@ -590,13 +1053,13 @@ impl Context {
 
     pub fn set_report_field(&self, key: &str, value: &str) {
         self.report_metadata
-            .try_lock()
+            .lock()
             .unwrap()
             .insert(key.to_string(), value.to_string());
     }
 
     pub fn report_metadata_snapshot(&self) -> HashMap<String, String> {
-        self.report_metadata.try_lock().unwrap().clone()
+        self.report_metadata.lock().unwrap().clone()
     }
 
     pub fn record_metric(&self, name: &str, value: f64) {
@@ -605,26 +1068,422 @@ impl Context {
 
     pub fn declare_metric(&self, name: &str, orientation: i8) {
         self.metric_orientations
-            .try_lock()
+            .lock()
             .unwrap()
             .insert(name.to_string(), orientation);
     }
 
     pub fn metric_orientations_snapshot(&self) -> HashMap<String, i8> {
-        self.metric_orientations.try_lock().unwrap().clone()
+        self.metric_orientations.lock().unwrap().clone()
     }
 
-    /// Returns the current accumulated request stats snapshot and resets the stats.
-    pub fn take_session_stats(&self) -> SessionStats {
+    /// Returns the request stats accumulated since the previous call.
+    /// The stats themselves keep accumulating until reset.
+    pub fn take_session_stats(&self) -> Result<SessionStats, SubtractionError> {
         let mut stats = self.stats.try_lock().unwrap();
-        let result = stats.clone();
-        stats.reset();
-        result
+        stats.take_interval(&mut self.stats_baseline.try_lock().unwrap())
+    }
+
+    /// Returns the request stats accumulated since the previous `take_session_stats`,
+    /// without taking them.
+    pub fn interval_session_stats(&self) -> Result<SessionStats, SubtractionError> {
+        let stats = self.stats.try_lock().unwrap();
+        stats.since(&self.stats_baseline.try_lock().unwrap())
     }
 
     /// Resets query and request counters
     pub fn reset(&self) {
         self.stats.try_lock().unwrap().reset();
         *self.start_time.try_lock().unwrap() = Instant::now();
+        self.paced_op_count.store(0, Ordering::Relaxed);
+    }
+}
+
+/// Formats a column type the way it is written in CQL, e.g. `map<text, frozen<list<int>>>`.
+fn cql_type_name(typ: &ColumnType) -> String {
+    let frozen = |frozen: bool, name: String| {
+        if frozen {
+            format!("frozen<{name}>")
+        } else {
+            name
+        }
+    };
+    match typ {
+        ColumnType::Native(native) => match native {
+            NativeType::Ascii => "ascii",
+            NativeType::Boolean => "boolean",
+            NativeType::Blob => "blob",
+            NativeType::Counter => "counter",
+            NativeType::Date => "date",
+            NativeType::Decimal => "decimal",
+            NativeType::Double => "double",
+            NativeType::Duration => "duration",
+            NativeType::Float => "float",
+            NativeType::Int => "int",
+            NativeType::BigInt => "bigint",
+            NativeType::Text => "text",
+            NativeType::Timestamp => "timestamp",
+            NativeType::Inet => "inet",
+            NativeType::SmallInt => "smallint",
+            NativeType::TinyInt => "tinyint",
+            NativeType::Time => "time",
+            NativeType::Timeuuid => "timeuuid",
+            NativeType::Uuid => "uuid",
+            NativeType::Varint => "varint",
+            other => return format!("{other:?}").to_lowercase(),
+        }
+        .to_string(),
+        ColumnType::Collection { frozen: f, typ } => {
+            let name = match typ {
+                CollectionType::List(t) => format!("list<{}>", cql_type_name(t)),
+                CollectionType::Set(t) => format!("set<{}>", cql_type_name(t)),
+                CollectionType::Map(k, v) => {
+                    format!("map<{}, {}>", cql_type_name(k), cql_type_name(v))
+                }
+                other => format!("{other:?}"),
+            };
+            frozen(*f, name)
+        }
+        ColumnType::Vector { typ, dimensions } => {
+            format!("vector<{}, {dimensions}>", cql_type_name(typ))
+        }
+        ColumnType::UserDefinedType {
+            frozen: f,
+            definition,
+        } => frozen(*f, definition.name.to_string()),
+        ColumnType::Tuple(types) => format!(
+            "tuple<{}>",
+            types
+                .iter()
+                .map(cql_type_name)
+                .collect::<Vec<_>>()
+                .join(", ")
+        ),
+        other => format!("{other:?}"),
+    }
+}
+
+/// Returns the text of a batch for error messages, in the CQL batch syntax.
+fn batch_query_text(statements: &[String]) -> String {
+    format!("BEGIN BATCH {}; APPLY BATCH", statements.join("; "))
+}
+
+/// Extracts the only column of the only row of `rows` and compares it with `expected`.
+fn verify_single_value(key: &str, rows: Value, expected: &Value) -> Result<Value, Box<CassError>> {
+    let error = |msg: String| {
+        Box::new(CassError(CassErrorKind::Error(format!(
+            "execute_prepared_verify: {msg}"
+        ))))
+    };
+    let rows = rows
+        .borrow_ref::<RuneVec>()
+        .map_err(|e| error(e.to_string()))?;
+    let (Some(row), 1) = (rows.first(), rows.len()) else {
+        return Err(error(format!(
+            "prepared statement '{key}' returned {} rows, expected exactly one",
+            rows.len()
+        )));
+    };
+    let row = row
+        .borrow_ref::<Object>()
+        .map_err(|e| error(e.to_string()))?;
+    let mut columns = row.values();
+    let (Some(actual), None) = (columns.next(), columns.next()) else {
+        return Err(error(format!(
+            "prepared statement '{key}' returned {} columns, expected exactly one",
+            row.len()
+        )));
+    };
+    match Value::partial_eq(actual, expected).into_result() {
+        Ok(true) => Ok(actual.clone()),
+        Ok(false) => Err(Box::new(CassError::value_validation_error(
+            key, expected, actual,
+        ))),
+        Err(e) => Err(error(format!(
+            "cannot compare {actual:?} with {expected:?}: {e}"
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(rows: Vec<Vec<(&str, Value)>>) -> Value {
+        let mut vec = RuneVec::new();
+        for row in rows {
+            let mut obj = Object::new();
+            for (name, value) in row {
+                obj.insert(rune::alloc::String::try_from(name).unwrap(), value)
+                    .unwrap();
+            }
+            vec.push(Value::new(obj).unwrap()).unwrap();
+        }
+        Value::vec(vec.into_inner()).unwrap()
+    }
+
+    #[test]
+    fn cql_type_names() {
+        let list = |typ| ColumnType::Collection {
+            frozen: true,
+            typ: CollectionType::List(Box::new(typ)),
+        };
+        for (typ, expected) in [
+            (ColumnType::Native(NativeType::BigInt), "bigint"),
+            (ColumnType::Native(NativeType::Text), "text"),
+            (
+                ColumnType::Collection {
+                    frozen: false,
+                    typ: CollectionType::Map(
+                        Box::new(ColumnType::Native(NativeType::Text)),
+                        Box::new(list(ColumnType::Native(NativeType::Int))),
+                    ),
+                },
+                "map<text, frozen<list<int>>>",
+            ),
+            (
+                ColumnType::Tuple(vec![
+                    ColumnType::Native(NativeType::Uuid),
+                    ColumnType::Native(NativeType::Double),
+                ]),
+                "tuple<uuid, double>",
+            ),
+            (
+                ColumnType::Vector {
+                    typ: Box::new(ColumnType::Native(NativeType::Float)),
+                    dimensions: 3,
+                },
+                "vector<float, 3>",
+            ),
+        ] {
+            assert_eq!(cql_type_name(&typ), expected);
+        }
+    }
+
+    #[test]
+    fn prepared_variable_types_requires_prepared_statement() {
+        let ctx = Context::new(
+            None,
+            100,
+            "dc1".to_string(),
+            "rack1".to_string(),
+            0,
+            RetryInterval::new("1,2").unwrap(),
+            ValidationStrategy::Ignore,
+        );
+        let err = ctx.prepared_variable_types("missing").unwrap_err();
+        assert!(matches!(err.0, CassErrorKind::PreparedStatementNotFound(_)));
+    }
+
+    #[test]
+    fn connection_count_is_zero_without_session() {
+        let ctx = Context::new(
+            None,
+            100,
+            "dc1".to_string(),
+            "rack1".to_string(),
+            0,
+            RetryInterval::new("1,2").unwrap(),
+            ValidationStrategy::Ignore,
+        );
+        assert_eq!(ctx.connection_count(), 0);
+    }
+
+    #[test]
+    fn batch_errors_show_statements() {
+        let cql = batch_query_text(&[
+            "INSERT INTO t (pk) VALUES (?)".to_string(),
+            "DELETE FROM u WHERE pk = ?".to_string(),
+        ]);
+        let err = CassError::query_retries_exceeded(1).with_query(&cql);
+        assert_eq!(
+            err.to_string(),
+            "Error executing 'BEGIN BATCH INSERT INTO t (pk) VALUES (?); \
+             DELETE FROM u WHERE pk = ?; APPLY BATCH': \
+             QueryRetriesExceeded: Max retry attempts (1) reached"
+        );
+    }
+
+    #[test]
+    fn custom_validation_function_of_workload_decides_on_rows() {
+        let path = concat!(
+            env!("CARGO_MANIFEST_DIR"),
+            "/workloads/integration_tests/custom_validation.rn"
+        );
+        let program = crate::exec::workload::Program::new(
+            rune::Source::from_path(path).unwrap(),
+            Default::default(),
+        )
+        .unwrap();
+        let callback = program.sync_function("check_rows").unwrap();
+        let row = |pk: i64, val: &str| {
+            vec![
+                ("pk", Value::from(pk)),
+                ("val", rune::to_value(val.to_string()).unwrap()),
+            ]
+        };
+
+        let accepted =
+            call_validation_callback(Some(&callback), "check_rows", rows(vec![row(7, "value_7")]));
+        assert_eq!(accepted, Ok(true));
+        let rejected =
+            call_validation_callback(Some(&callback), "check_rows", rows(vec![row(7, "value_8")]));
+        assert_eq!(rejected, Ok(false));
+        let rejected = call_validation_callback(Some(&callback), "check_rows", rows(vec![]));
+        assert_eq!(rejected, Ok(false));
+    }
+
+    #[test]
+    fn verify_single_value_accepts_matching_value() {
+        let actual = verify_single_value(
+            "get",
+            rows(vec![vec![("v", Value::from(42))]]),
+            &Value::from(42),
+        )
+        .unwrap();
+        assert_eq!(rune::from_value::<i64>(actual).unwrap(), 42);
+    }
+
+    #[test]
+    fn verify_single_value_rejects_mismatch() {
+        let err = verify_single_value(
+            "get",
+            rows(vec![vec![("v", Value::from(41))]]),
+            &Value::from(42),
+        )
+        .unwrap_err();
+        assert!(matches!(err.0, CassErrorKind::ValueValidationError(..)));
+        assert!(err.to_string().contains("'get'"));
+    }
+
+    #[test]
+    fn verify_single_value_requires_single_row_and_column() {
+        let expected = Value::from(1);
+        assert!(verify_single_value("get", rows(vec![]), &expected).is_err());
+        assert!(verify_single_value(
+            "get",
+            rows(vec![
+                vec![("v", Value::from(1))],
+                vec![("v", Value::from(1))]
+            ]),
+            &expected
+        )
+        .is_err());
+        let err = verify_single_value(
+            "get",
+            rows(vec![vec![("a", Value::from(1)), ("b", Value::from(1))]]),
+            &expected,
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("2 columns"));
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 8)]
+    async fn concurrent_clones_have_independent_stats() {
+        let ctx = Arc::new(Context::new(
+            None,
+            100,
+            "dc1".to_string(),
+            "rack1".to_string(),
+            0,
+            RetryInterval::new("1,2").unwrap(),
+            ValidationStrategy::Ignore,
+        ));
+        let tasks: Vec<_> = (0..100)
+            .map(|i| {
+                let ctx = Arc::clone(&ctx);
+                tokio::spawn(async move {
+                    let clone = Context::clone(&ctx).unwrap();
+                    clone.set_report_field("clone", &i.to_string());
+                    let mut stats = clone.stats.try_lock().unwrap();
+                    stats.start_request();
+                    stats.complete_request("execute", Duration::from_millis(i), 0);
+                    drop(stats);
+                    clone
+                })
+            })
+            .collect();
+
+        let mut clones = Vec::new();
+        for task in tasks {
+            clones.push(task.await.unwrap());
+        }
+        for (i, clone) in clones.iter().enumerate() {
+            assert_eq!(clone.stats.try_lock().unwrap().req_count, 1);
+            assert_eq!(clone.report_metadata_snapshot()["clone"], i.to_string());
+            assert!(!Arc::ptr_eq(&clone.stats, &ctx.stats));
+        }
+        assert_eq!(ctx.stats.try_lock().unwrap().req_count, 0);
+        assert!(ctx.report_metadata_snapshot().is_empty());
+    }
+
+    #[test]
+    fn select_is_rewritten_to_count_query() {
+        assert_eq!(
+            to_count_query("SELECT pk, ck FROM ks.tbl WHERE pk = 1").unwrap(),
+            "SELECT count(*) FROM ks.tbl WHERE pk = 1"
+        );
+        assert_eq!(
+            to_count_query("select *\n  from ks.tbl").unwrap(),
+            "SELECT count(*) FROM ks.tbl"
+        );
+        assert_eq!(
+            to_count_query("SELECT COUNT(*) FROM ks.tbl").unwrap(),
+            "SELECT COUNT(*) FROM ks.tbl"
+        );
+        assert!(matches!(
+            to_count_query("INSERT INTO ks.tbl (pk) VALUES (1)")
+                .unwrap_err()
+                .0,
+            CassErrorKind::QueryResponseValidationNotApplicableError(_)
+        ));
+    }
+
+    #[test]
+    fn count_changing_clauses_are_rejected() {
+        for (cql, clause) in [
+            ("SELECT DISTINCT pk FROM ks.tbl", "DISTINCT"),
+            ("SELECT pk FROM ks.tbl WHERE pk = 1 LIMIT 10", "LIMIT"),
+            (
+                "select ck from ks.tbl per partition limit 2",
+                "per partition limit",
+            ),
+            (
+                "SELECT pk, ck FROM ks.tbl WHERE pk = 1 GROUP BY ck",
+                "GROUP BY",
+            ),
+        ] {
+            let err = to_count_query(cql).unwrap_err();
+            assert!(matches!(err.0, CassErrorKind::Error(_)), "{cql}");
+            assert!(err.to_string().contains(&format!("'{clause}'")), "{err}");
+        }
+        assert_eq!(
+            to_count_query("SELECT limit_col FROM ks.tbl").unwrap(),
+            "SELECT count(*) FROM ks.tbl"
+        );
+    }
+
+    #[test]
+    fn extra_bind_value_is_added_to_params() {
+        let ttl = Value::from(60);
+        let vec = rune::to_value(vec![1i64, 2]).unwrap();
+        let extended: Vec<i64> =
+            rune::from_value(with_extra_bind_value(&vec, TTL_BIND_MARKER, ttl.clone()).unwrap())
+                .unwrap();
+        assert_eq!(extended, vec![1, 2, 60]);
+
+        let mut obj = Object::new();
+        obj.insert(rune::alloc::String::try_from("pk").unwrap(), Value::from(1))
+            .unwrap();
+        let extended =
+            with_extra_bind_value(&Value::new(obj).unwrap(), TTL_BIND_MARKER, ttl.clone()).unwrap();
+        let extended = extended.borrow_ref::<Object>().unwrap();
+        assert_eq!(extended.len(), 2);
+        assert_eq!(
+            extended.get(TTL_BIND_MARKER).unwrap().as_signed().unwrap(),
+            60
+        );
+
+        assert!(with_extra_bind_value(&Value::from(1), TTL_BIND_MARKER, ttl).is_err());
     }
 }
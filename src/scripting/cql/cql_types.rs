@@ -1,36 +1,327 @@
+use super::cass_error::{CassError, CassErrorKind};
+use chrono::{NaiveDate, Utc};
+use rune::runtime::VmResult;
 use rune::Any;
+use scylla::value::{CqlDecimal, CqlDuration, CqlVarint};
+use std::net::IpAddr;
 
+/// Value that can be bound to a `tinyint` column.
 #[derive(Clone, Debug, Any)]
 pub struct Int8(pub i8);
 
+/// Value that can be bound to a `smallint` column.
 #[derive(Clone, Debug, Any)]
 pub struct Int16(pub i16);
 
+/// Value that can be bound to an `int` column.
 #[derive(Clone, Debug, Any)]
 pub struct Int32(pub i32);
 
+/// Value that can be bound to a `float` column.
 #[derive(Clone, Debug, Any)]
 pub struct Float32(pub f32);
 
+/// Value of the CQL `duration` type.
+/// Months, days and nanoseconds are stored separately, because they don't have fixed ratios.
+#[derive(Clone, Debug, PartialEq, Any)]
+pub struct Duration {
+    #[rune(get)]
+    pub months: i32,
+    #[rune(get)]
+    pub days: i32,
+    #[rune(get)]
+    pub nanoseconds: i64,
+}
+
+impl Duration {
+    /// Validates the components like the database does: months and days must fit in 32 bits,
+    /// and all components must have the same sign.
+    pub fn new(months: i64, days: i64, nanoseconds: i64) -> Result<Duration, String> {
+        let months =
+            i32::try_from(months).map_err(|_| format!("duration months {months} out of range"))?;
+        let days = i32::try_from(days).map_err(|_| format!("duration days {days} out of range"))?;
+        let non_negative = months >= 0 && days >= 0 && nanoseconds >= 0;
+        let non_positive = months <= 0 && days <= 0 && nanoseconds <= 0;
+        if !non_negative && !non_positive {
+            return Err(format!(
+                "duration components must have the same sign, got \
+                 months={months}, days={days}, nanoseconds={nanoseconds}"
+            ));
+        }
+        Ok(Duration {
+            months,
+            days,
+            nanoseconds,
+        })
+    }
+}
+
+impl From<&Duration> for CqlDuration {
+    fn from(d: &Duration) -> Self {
+        CqlDuration {
+            months: d.months,
+            days: d.days,
+            nanoseconds: d.nanoseconds,
+        }
+    }
+}
+
+/// Value of the CQL `varint` type, an integer of arbitrary precision.
+#[derive(Clone, Debug, Any)]
+pub struct Varint(pub CqlVarint);
+
+/// Value of the CQL `decimal` type, `unscaled * 10^(-scale)` with an arbitrary precision
+/// unscaled integer.
+#[derive(Clone, Debug, Any)]
+pub struct Decimal(pub CqlDecimal);
+
+/// Parses an integer written in decimal, e.g. `"-123456789012345678901234567890"`,
+/// into the minimal big-endian two's complement representation used by `varint`.
+pub fn parse_varint_bytes(s: &str) -> Result<Vec<u8>, String> {
+    let (negative, digits) = match s.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, s.strip_prefix('+').unwrap_or(s)),
+    };
+    if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(format!("'{s}' is not an integer"));
+    }
+    // Magnitude as little-endian base-256 digits
+    let mut bytes: Vec<u8> = Vec::new();
+    for digit in digits.bytes() {
+        let mut carry = (digit - b'0') as u32;
+        for byte in bytes.iter_mut() {
+            let x = *byte as u32 * 10 + carry;
+            *byte = x as u8;
+            carry = x >> 8;
+        }
+        if carry > 0 {
+            bytes.push(carry as u8);
+        }
+    }
+    // Room for the sign bit
+    bytes.push(0);
+    if negative {
+        let mut carry = 1;
+        for byte in bytes.iter_mut() {
+            let x = (!*byte) as u16 + carry;
+            *byte = x as u8;
+            carry = x >> 8;
+        }
+    }
+    bytes.reverse();
+    // Leading bytes which only repeat the sign bit are redundant
+    let redundant =
+        |b: &[u8]| b.len() > 1 && ((b[0] == 0x00 && b[1] < 0x80) || (b[0] == 0xff && b[1] >= 0x80));
+    let mut start = 0;
+    while redundant(&bytes[start..]) {
+        start += 1;
+    }
+    Ok(bytes.split_off(start))
+}
+
+/// Parses a decimal number, e.g. `"-1234.5678"` or `"1.5e-3"`, without losing precision.
+/// Returns the unscaled value bytes as in `parse_varint_bytes`, and the scale.
+pub fn parse_decimal(s: &str) -> Result<(Vec<u8>, i32), String> {
+    let invalid = || format!("'{s}' is not a decimal number");
+    let (number, exponent) = match s.find(['e', 'E']) {
+        Some(pos) => (
+            &s[..pos],
+            s[pos + 1..].parse::<i64>().map_err(|_| invalid())?,
+        ),
+        None => (s, 0),
+    };
+    let (int_part, frac_part) = number.split_once('.').unwrap_or((number, ""));
+    let unsigned_int_part = int_part.trim_start_matches(['-', '+']);
+    if unsigned_int_part.len() + 1 < int_part.len()
+        || (unsigned_int_part.is_empty() && frac_part.is_empty())
+        || !frac_part.bytes().all(|b| b.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    let unscaled = parse_varint_bytes(&format!("{int_part}{frac_part}")).map_err(|_| invalid())?;
+    let scale = (frac_part.len() as i64)
+        .checked_sub(exponent)
+        .and_then(|scale| i32::try_from(scale).ok())
+        .ok_or_else(|| format!("scale of '{s}' is out of range"))?;
+    Ok((unscaled, scale))
+}
+
+/// Creates a value that can be bound to a `varint` column from its decimal representation.
+#[rune::function]
+#[allow(clippy::result_large_err)]
+pub fn varint(value: &str) -> Result<Varint, CassError> {
+    parse_varint_bytes(value)
+        .map(|bytes| Varint(CqlVarint::from_signed_bytes_be(bytes)))
+        .map_err(|e| {
+            CassError(CassErrorKind::QueryParamConversion(
+                value.to_string(),
+                "NativeType::Varint".to_string(),
+                Some(e),
+            ))
+        })
+}
+
+/// Creates a value that can be bound to a `decimal` column, equal to `unscaled * 10^(-scale)`.
+/// The unscaled value is given as a string to keep its full precision.
+#[rune::function]
+#[allow(clippy::result_large_err)]
+pub fn decimal(unscaled: &str, scale: i64) -> Result<Decimal, CassError> {
+    let conversion_error = |e: String| {
+        CassError(CassErrorKind::QueryParamConversion(
+            format!("{unscaled}e{}", -scale),
+            "NativeType::Decimal".to_string(),
+            Some(e),
+        ))
+    };
+    let bytes = parse_varint_bytes(unscaled).map_err(conversion_error)?;
+    let scale = i32::try_from(scale)
+        .map_err(|_| conversion_error(format!("scale {scale} is out of range")))?;
+    Ok(Decimal(CqlDecimal::from_signed_be_bytes_and_exponent(
+        bytes, scale,
+    )))
+}
+
+/// Value of the CQL `inet` type, an IPv4 or IPv6 address.
+#[derive(Clone, Debug, Any)]
+pub struct Inet(pub IpAddr);
+
+/// Creates a value that can be bound to an `inet` column from an address like `"10.0.0.1"` or `"::1"`.
+#[rune::function]
+#[allow(clippy::result_large_err)]
+pub fn inet(address: &str) -> Result<Inet, CassError> {
+    address
+        .parse()
+        .map(Inet)
+        .map_err(|e: std::net::AddrParseError| {
+            CassError(CassErrorKind::QueryParamConversion(
+                address.to_string(),
+                "NativeType::Inet".to_string(),
+                Some(e.to_string()),
+            ))
+        })
+}
+
+/// Value of the CQL `date` type, a calendar day without time.
+#[derive(Clone, Debug, Any)]
+pub struct Date(pub NaiveDate);
+
+impl Date {
+    /// Returns `None` if the date doesn't exist, e.g. `2023-02-29`.
+    pub fn from_ymd(year: i64, month: i64, day: i64) -> Option<Date> {
+        let year = i32::try_from(year).ok()?;
+        let month = u32::try_from(month).ok()?;
+        let day = u32::try_from(day).ok()?;
+        NaiveDate::from_ymd_opt(year, month, day).map(Date)
+    }
+}
+
+/// Creates a value that can be bound to a `date` column, e.g. `date(2024, 2, 29)`.
+#[rune::function]
+#[allow(clippy::result_large_err)]
+pub fn date(year: i64, month: i64, day: i64) -> Result<Date, CassError> {
+    Date::from_ymd(year, month, day).ok_or_else(|| {
+        CassError(CassErrorKind::QueryParamConversion(
+            format!("{year}-{month}-{day}"),
+            "NativeType::Date".to_string(),
+            Some("Invalid date".to_string()),
+        ))
+    })
+}
+
+/// Returns the current date in UTC as a value that can be bound to a `date` column.
+#[rune::function]
+pub fn today() -> Date {
+    Date(Utc::now().date_naive())
+}
+
+/// Creates a value that can be bound to a `duration` column.
+#[rune::function]
+pub fn duration(months: i64, days: i64, nanoseconds: i64) -> VmResult<Duration> {
+    match Duration::new(months, days, nanoseconds) {
+        Ok(d) => VmResult::Ok(d),
+        Err(e) => VmResult::panic(e),
+    }
+}
+
 pub mod i64 {
     use super::{Float32, Int16, Int32, Int8};
+    use rune::runtime::VmResult;
+
+    /// Converts a Rune integer to a narrower integer type.
+    /// Returns `None` if the value is out of range.
+    pub(super) fn checked_inner<T: TryFrom<i64>>(value: i64) -> Option<T> {
+        value.try_into().ok()
+    }
+
+    /// Converts a Rune integer to a narrower integer type,
+    /// replacing out of range values with `min` or `max`.
+    pub(super) fn saturating_inner<T: TryFrom<i64>>(value: i64, min: T, max: T) -> T {
+        value
+            .try_into()
+            .unwrap_or(if value < 0 { min } else { max })
+    }
 
-    /// Converts a Rune integer to i8 (Cassandra tinyint)
+    /// Converts a Rune integer to i8 (Cassandra tinyint).
+    /// Returns `None` if the value is out of range; it never wraps.
+    /// Note that `None` is bound as NULL.
     #[rune::function(instance)]
     pub fn to_i8(value: i64) -> Option<Int8> {
-        Some(Int8(value.try_into().ok()?))
+        checked_inner(value).map(Int8)
     }
 
-    /// Converts a Rune integer to i16 (Cassandra smallint)
+    /// Converts a Rune integer to i16 (Cassandra smallint).
+    /// Returns `None` if the value is out of range; it never wraps.
+    /// Note that `None` is bound as NULL.
     #[rune::function(instance)]
     pub fn to_i16(value: i64) -> Option<Int16> {
-        Some(Int16(value.try_into().ok()?))
+        checked_inner(value).map(Int16)
     }
 
-    /// Converts a Rune integer to i32 (Cassandra int)
+    /// Converts a Rune integer to i32 (Cassandra int).
+    /// Returns `None` if the value is out of range; it never wraps.
+    /// Note that `None` is bound as NULL.
     #[rune::function(instance)]
     pub fn to_i32(value: i64) -> Option<Int32> {
-        Some(Int32(value.try_into().ok()?))
+        checked_inner(value).map(Int32)
+    }
+
+    /// Same as `to_i8`, named explicitly after its overflow handling.
+    #[rune::function(instance)]
+    pub fn to_i8_checked(value: i64) -> Option<Int8> {
+        checked_inner(value).map(Int8)
+    }
+
+    /// Same as `to_i16`, named explicitly after its overflow handling.
+    #[rune::function(instance)]
+    pub fn to_i16_checked(value: i64) -> Option<Int16> {
+        checked_inner(value).map(Int16)
+    }
+
+    /// Same as `to_i32`, named explicitly after its overflow handling.
+    #[rune::function(instance)]
+    pub fn to_i32_checked(value: i64) -> Option<Int32> {
+        checked_inner(value).map(Int32)
+    }
+
+    /// Converts a Rune integer to i8 (Cassandra tinyint),
+    /// replacing out of range values with the nearest bound.
+    #[rune::function(instance)]
+    pub fn to_i8_saturating(value: i64) -> Int8 {
+        Int8(saturating_inner(value, i8::MIN, i8::MAX))
+    }
+
+    /// Converts a Rune integer to i16 (Cassandra smallint),
+    /// replacing out of range values with the nearest bound.
+    #[rune::function(instance)]
+    pub fn to_i16_saturating(value: i64) -> Int16 {
+        Int16(saturating_inner(value, i16::MIN, i16::MAX))
+    }
+
+    /// Converts a Rune integer to i32 (Cassandra int),
+    /// replacing out of range values with the nearest bound.
+    #[rune::function(instance)]
+    pub fn to_i32_saturating(value: i64) -> Int32 {
+        Int32(saturating_inner(value, i32::MIN, i32::MAX))
     }
 
     /// Converts a Rune integer to f32 (Cassandra float)
@@ -50,21 +341,44 @@ pub mod i64 {
     pub fn clamp(value: i64, min: i64, max: i64) -> i64 {
         value.clamp(min, max)
     }
+
+    pub(super) fn clamp_to_range_inner(value: i64, min: i64, max: i64) -> VmResult<i64> {
+        if min > max {
+            return VmResult::panic(format!(
+                "clamp_to_range: min ({min}) must not be greater than max ({max})"
+            ));
+        }
+        VmResult::Ok(value.clamp(min, max))
+    }
+
+    /// Restricts a value to the range `min..=max`. Both bounds are inclusive,
+    /// so `min` and `max` themselves are returned unchanged.
+    /// Raises an error if `min` is greater than `max`.
+    #[rune::function(instance)]
+    pub fn clamp_to_range(value: i64, min: i64, max: i64) -> VmResult<i64> {
+        clamp_to_range_inner(value, min, max)
+    }
 }
 
 pub mod f64 {
     use super::{Float32, Int16, Int32, Int8};
 
+    /// Converts a Rune float to i8 (Cassandra tinyint), rounding towards zero.
+    /// Out of range values saturate to the nearest bound and NaN becomes 0.
     #[rune::function(instance)]
     pub fn to_i8(value: f64) -> Int8 {
         Int8(value as i8)
     }
 
+    /// Converts a Rune float to i16 (Cassandra smallint), rounding towards zero.
+    /// Out of range values saturate to the nearest bound and NaN becomes 0.
     #[rune::function(instance)]
     pub fn to_i16(value: f64) -> Int16 {
         Int16(value as i16)
     }
 
+    /// Converts a Rune float to i32 (Cassandra int), rounding towards zero.
+    /// Out of range values saturate to the nearest bound and NaN becomes 0.
     #[rune::function(instance)]
     pub fn to_i32(value: f64) -> Int32 {
         Int32(value as i32)
@@ -80,9 +394,70 @@ pub mod f64 {
         value.to_string()
     }
 
+    /// Formats a float with a fixed number of decimal places, never using scientific notation.
+    /// The last digit is rounded, e.g. `0.00012.to_fixed(4)` gives `"0.0001"`.
+    #[rune::function(instance)]
+    pub fn to_fixed(value: f64, decimals: usize) -> String {
+        format!("{value:.decimals$}")
+    }
+
     /// Restricts a value to a certain interval unless it is NaN.
     #[rune::function(instance)]
     pub fn clamp(value: f64, min: f64, max: f64) -> f64 {
         value.clamp(min, max)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::i64::{checked_inner, clamp_to_range_inner, saturating_inner};
+
+    #[test]
+    fn clamp_to_range_is_inclusive() {
+        let clamp = |value, min, max| clamp_to_range_inner(value, min, max).into_result();
+        assert_eq!(clamp(10, 10, 20).unwrap(), 10);
+        assert_eq!(clamp(20, 10, 20).unwrap(), 20);
+        assert_eq!(clamp(9, 10, 20).unwrap(), 10);
+        assert_eq!(clamp(21, 10, 20).unwrap(), 20);
+        assert_eq!(clamp(15, 10, 20).unwrap(), 15);
+        assert_eq!(clamp(i64::MIN, 5, 5).unwrap(), 5);
+        assert!(clamp(15, 20, 10).is_err());
+    }
+
+    #[test]
+    fn checked_conversion_rejects_out_of_range_values() {
+        assert_eq!(checked_inner::<i8>(i8::MIN.into()), Some(i8::MIN));
+        assert_eq!(checked_inner::<i8>(i8::MAX.into()), Some(i8::MAX));
+        assert_eq!(checked_inner::<i8>(i64::from(i8::MIN) - 1), None);
+        assert_eq!(checked_inner::<i8>(i64::from(i8::MAX) + 1), None);
+        assert_eq!(checked_inner::<i16>(i16::MIN.into()), Some(i16::MIN));
+        assert_eq!(checked_inner::<i16>(i16::MAX.into()), Some(i16::MAX));
+        assert_eq!(checked_inner::<i16>(i64::from(i16::MIN) - 1), None);
+        assert_eq!(checked_inner::<i16>(i64::from(i16::MAX) + 1), None);
+        assert_eq!(checked_inner::<i32>(i32::MIN.into()), Some(i32::MIN));
+        assert_eq!(checked_inner::<i32>(i32::MAX.into()), Some(i32::MAX));
+        assert_eq!(checked_inner::<i32>(i64::from(i32::MIN) - 1), None);
+        assert_eq!(checked_inner::<i32>(i64::from(i32::MAX) + 1), None);
+    }
+
+    #[test]
+    fn saturating_conversion_clamps_out_of_range_values() {
+        let to_i8 = |value| saturating_inner(value, i8::MIN, i8::MAX);
+        assert_eq!(to_i8(i8::MIN.into()), i8::MIN);
+        assert_eq!(to_i8(i8::MAX.into()), i8::MAX);
+        assert_eq!(to_i8(i64::from(i8::MIN) - 1), i8::MIN);
+        assert_eq!(to_i8(i64::from(i8::MAX) + 1), i8::MAX);
+        let to_i16 = |value| saturating_inner(value, i16::MIN, i16::MAX);
+        assert_eq!(to_i16(i16::MIN.into()), i16::MIN);
+        assert_eq!(to_i16(i16::MAX.into()), i16::MAX);
+        assert_eq!(to_i16(i64::from(i16::MIN) - 1), i16::MIN);
+        assert_eq!(to_i16(i64::from(i16::MAX) + 1), i16::MAX);
+        let to_i32 = |value| saturating_inner(value, i32::MIN, i32::MAX);
+        assert_eq!(to_i32(i32::MIN.into()), i32::MIN);
+        assert_eq!(to_i32(i32::MAX.into()), i32::MAX);
+        assert_eq!(to_i32(i64::from(i32::MIN) - 1), i32::MIN);
+        assert_eq!(to_i32(i64::from(i32::MAX) + 1), i32::MAX);
+        assert_eq!(to_i32(i64::MIN), i32::MIN);
+        assert_eq!(to_i32(i64::MAX), i32::MAX);
+    }
+}
@@ -1,9 +1,12 @@
+use crate::config::ValidationStrategy;
 use crate::scripting::functions_common::extract_validation_args;
+use crate::scripting::rune_uuid::Uuid;
 
 use super::cass_error::{CassError, CassErrorKind};
 use super::context::Context;
-use rune::runtime::Ref;
-use rune::Value;
+use crate::scripting::cluster_info::ClusterInfo;
+use rune::runtime::{Object, Ref, VmError, VmResult};
+use rune::{vm_try, Value};
 use std::ops::Deref;
 
 #[rune::function(instance)]
@@ -36,11 +39,31 @@ pub async fn execute_with_validation(
     .await
 }
 
+#[rune::function(instance)]
+pub async fn execute_count_validation(
+    ctx: Ref<Context>,
+    cql: Ref<str>,
+    expected_min: u64,
+    expected_max: u64,
+) -> Result<Value, CassError> {
+    ctx.execute_count_validation(cql.deref(), expected_min, expected_max)
+        .await
+}
+
 #[rune::function(instance)]
 pub async fn execute_with_result(ctx: Ref<Context>, cql: Ref<str>) -> Result<Value, CassError> {
     ctx.execute_with_result(cql.deref()).await
 }
 
+#[rune::function(instance)]
+pub async fn execute_unprepared(
+    ctx: Ref<Context>,
+    cql: Ref<str>,
+    params: Value,
+) -> Result<Value, CassError> {
+    ctx.execute_unprepared(cql.deref(), params).await
+}
+
 #[rune::function(instance)]
 pub async fn execute_prepared(
     ctx: Ref<Context>,
@@ -73,6 +96,50 @@ pub async fn execute_prepared_with_validation(
     .await
 }
 
+/// Parses the validation strategy given to a single `execute_prepared_with_validation_strategy`
+/// call. Besides the `--validation-strategy` names, `"fail"` and `"warn"` are accepted
+/// as shorthands for `fail-fast` and `ignore`, which reports the error and goes on.
+/// Custom strategies are rejected, because their function is resolved once per run.
+fn parse_call_validation_strategy(strategy: &str) -> Result<ValidationStrategy, String> {
+    match strategy {
+        "fail" => Ok(ValidationStrategy::FailFast),
+        "warn" => Ok(ValidationStrategy::Ignore),
+        _ => match strategy.parse()? {
+            ValidationStrategy::Custom { .. } => Err(format!(
+                "Validation strategy '{strategy}' can only be set with --validation-strategy"
+            )),
+            parsed => Ok(parsed),
+        },
+    }
+}
+
+#[rune::function(instance)]
+pub async fn execute_prepared_with_validation_strategy(
+    ctx: Ref<Context>,
+    key: Ref<str>,
+    params: Value,
+    validation_args: Vec<Value>,
+    strategy: Ref<str>,
+) -> Result<Value, CassError> {
+    let (args, strategy) = extract_validation_args(validation_args)
+        .and_then(|args| Ok((args, parse_call_validation_strategy(&strategy)?)))
+        .map_err(|e| {
+            CassError(CassErrorKind::Error(format!(
+                "execute_prepared_with_validation_strategy: {e}"
+            )))
+        })?;
+
+    ctx.execute_prepared_with_validation_strategy(
+        &key,
+        params,
+        args.expected_min,
+        args.expected_max,
+        &args.custom_err_msg,
+        &strategy,
+    )
+    .await
+}
+
 #[rune::function(instance)]
 pub async fn execute_prepared_with_result(
     ctx: Ref<Context>,
@@ -82,6 +149,83 @@ pub async fn execute_prepared_with_result(
     ctx.execute_prepared_with_result(&key, params).await
 }
 
+#[rune::function(instance)]
+pub async fn execute_prepared_verify(
+    ctx: Ref<Context>,
+    key: Ref<str>,
+    params: Value,
+    expected: Value,
+) -> Result<Value, CassError> {
+    ctx.execute_prepared_verify(&key, params, expected).await
+}
+
+#[rune::function(instance)]
+pub async fn execute_prepared_with_ttl(
+    ctx: Ref<Context>,
+    key: Ref<str>,
+    params: Value,
+    ttl_secs: i32,
+) -> Result<Value, CassError> {
+    ctx.execute_prepared_with_ttl(&key, params, ttl_secs).await
+}
+
+#[rune::function(instance)]
+pub async fn execute_prepared_with_timestamp(
+    ctx: Ref<Context>,
+    key: Ref<str>,
+    params: Value,
+    timestamp_micros: i64,
+) -> Result<Value, CassError> {
+    ctx.execute_prepared_with_timestamp(&key, params, timestamp_micros)
+        .await
+}
+
+fn compute_token_inner(ctx: &Context, key: &str, params: Value) -> VmResult<i64> {
+    let token = vm_try!(ctx
+        .compute_token(key, params)
+        .map_err(|e| VmError::panic(e.to_string())));
+    VmResult::Ok(token)
+}
+
+#[rune::function(instance)]
+pub fn compute_token(ctx: Ref<Context>, key: Ref<str>, params: Value) -> VmResult<i64> {
+    compute_token_inner(&ctx, &key, params)
+}
+
+fn prepared_info_object(variable_types: Vec<String>) -> Result<Object, Box<CassError>> {
+    let mut obj = Object::new();
+    obj.insert(
+        rune::alloc::String::try_from("variable_count")?,
+        Value::from(variable_types.len() as i64),
+    )?;
+    obj.insert(
+        rune::alloc::String::try_from("variable_types")?,
+        rune::to_value(variable_types).map_err(|e| {
+            Box::new(CassError(CassErrorKind::Error(format!(
+                "Failed to convert variable types: {e}"
+            ))))
+        })?,
+    )?;
+    Ok(obj)
+}
+
+/// Returns the number and the CQL types of the bind variables of a prepared statement.
+#[rune::function(instance)]
+pub async fn prepared_info(ctx: Ref<Context>, key: Ref<str>) -> Result<Value, CassError> {
+    let obj =
+        prepared_info_object(ctx.prepared_variable_types(&key).map_err(|e| *e)?).map_err(|e| *e)?;
+    Ok(Value::new(obj)?)
+}
+
+#[rune::function(instance)]
+pub async fn execute_prepared_traced(
+    ctx: Ref<Context>,
+    key: Ref<str>,
+    params: Value,
+) -> Result<(Value, Uuid), CassError> {
+    ctx.execute_prepared_traced(&key, params).await
+}
+
 #[rune::function(instance)]
 pub async fn batch_prepared(
     ctx: Ref<Context>,
@@ -92,7 +236,138 @@ pub async fn batch_prepared(
         .await
 }
 
+/// Converts cluster metadata to a Rune object with `name`, `db_version`, `dc` and `rack` fields.
+/// The `dc` and `rack` fields hold the datacenter and rack preferred by the driver.
+/// Unknown cluster name and version are reported as empty strings.
+fn cluster_info_object(
+    info: Option<&ClusterInfo>,
+    dc: &str,
+    rack: &str,
+) -> Result<Object, Box<CassError>> {
+    let (name, db_version) = info
+        .map(|info| (info.name.as_str(), info.db_version.as_str()))
+        .unwrap_or_default();
+    let mut obj = Object::new();
+    for (key, value) in [
+        ("name", name),
+        ("db_version", db_version),
+        ("dc", dc),
+        ("rack", rack),
+    ] {
+        obj.insert(
+            rune::alloc::String::try_from(key)?,
+            rune::to_value(rune::alloc::String::try_from(value)?).map_err(|e| {
+                Box::new(CassError(CassErrorKind::Error(format!(
+                    "Failed to convert cluster info field '{key}': {e}"
+                ))))
+            })?,
+        )?;
+    }
+    Ok(obj)
+}
+
+#[rune::function(instance)]
+pub async fn get_cluster_info(ctx: Ref<Context>) -> Result<Value, CassError> {
+    let info = ctx.cluster_info().await?;
+    let obj = cluster_info_object(
+        info.as_ref(),
+        &ctx.preferred_datacenter,
+        &ctx.preferred_rack,
+    )?;
+    Ok(Value::new(obj)?)
+}
+
+#[rune::function(instance)]
+pub fn connection_count(ctx: &Context) -> u64 {
+    ctx.connection_count()
+}
+
 #[rune::function(instance)]
 pub async fn get_datacenters(ctx: Ref<Context>) -> Result<Vec<String>, CassError> {
     ctx.get_datacenters().await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RetryInterval;
+
+    fn field(obj: &Object, key: &str) -> String {
+        obj.get(key)
+            .unwrap()
+            .borrow_ref::<rune::alloc::String>()
+            .unwrap()
+            .to_string()
+    }
+
+    #[test]
+    fn cluster_info_object_maps_fields() {
+        let info = ClusterInfo {
+            name: "cluster".to_string(),
+            db_version: "ScyllaDB 6.0".to_string(),
+            ..Default::default()
+        };
+        let obj = cluster_info_object(Some(&info), "dc1", "rack1").unwrap();
+        assert_eq!(obj.len(), 4);
+        assert_eq!(field(&obj, "name"), "cluster");
+        assert_eq!(field(&obj, "db_version"), "ScyllaDB 6.0");
+        assert_eq!(field(&obj, "dc"), "dc1");
+        assert_eq!(field(&obj, "rack"), "rack1");
+    }
+
+    #[test]
+    fn prepared_info_object_lists_variable_types() {
+        let obj = prepared_info_object(vec!["bigint".to_string(), "text".to_string()]).unwrap();
+        assert_eq!(
+            obj.get("variable_count")
+                .unwrap()
+                .as_integer::<i64>()
+                .unwrap(),
+            2
+        );
+        let types: Vec<String> =
+            rune::from_value(obj.get("variable_types").unwrap().clone()).unwrap();
+        assert_eq!(types, ["bigint", "text"]);
+    }
+
+    #[test]
+    fn compute_token_requires_prepared_statement() {
+        let ctx = Context::new(
+            None,
+            100,
+            "dc1".to_string(),
+            "rack1".to_string(),
+            0,
+            RetryInterval::new("1,2").unwrap(),
+            ValidationStrategy::Ignore,
+        );
+        let params = rune::to_value(vec![1_i64]).unwrap();
+        let err = compute_token_inner(&ctx, "missing", params)
+            .into_result()
+            .unwrap_err();
+        assert!(err.to_string().contains("missing"), "{err}");
+    }
+
+    #[test]
+    fn cluster_info_object_handles_unknown_cluster() {
+        let obj = cluster_info_object(None, "dc1", "").unwrap();
+        assert_eq!(field(&obj, "name"), "");
+        assert_eq!(field(&obj, "db_version"), "");
+        assert_eq!(field(&obj, "dc"), "dc1");
+    }
+
+    #[test]
+    fn call_validation_strategy_is_parsed() {
+        for (name, expected) in [
+            ("fail", ValidationStrategy::FailFast),
+            ("fail-fast", ValidationStrategy::FailFast),
+            ("warn", ValidationStrategy::Ignore),
+            ("ignore", ValidationStrategy::Ignore),
+            ("retry", ValidationStrategy::Retry),
+        ] {
+            assert_eq!(parse_call_validation_strategy(name).unwrap(), expected);
+        }
+        assert!(parse_call_validation_strategy("custom:check").is_err());
+        assert!(parse_call_validation_strategy("lenient").is_err());
+    }
+}
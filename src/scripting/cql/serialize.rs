@@ -1,6 +1,10 @@
 //! Functions for binding rune values to CQL parameters
 
 use super::cass_error::{CassError, CassErrorKind};
+use super::cql_types::{
+    parse_decimal, parse_varint_bytes, Date, Decimal, Duration, Float32, Inet, Int16, Int32, Int8,
+    Varint,
+};
 use crate::scripting::rune_uuid::Uuid;
 use chrono::{NaiveDate, NaiveTime};
 use once_cell::sync::Lazy;
@@ -354,23 +358,16 @@ fn to_scylla_value(v: &Value, typ: &ColumnType) -> Result<Option<CqlValue>, Box<
                     nanoseconds,
                 })))
             }
-            ColumnType::Native(NativeType::Varint) => {
-                if !s.as_str().chars().all(|c| c.is_ascii_digit()) {
-                    return Err(Box::new(CassError(CassErrorKind::QueryParamConversion(
-                        format!("{v:?}"),
-                        "NativeType::Varint".to_string(),
-                        Some("Input contains non-digit characters".to_string()),
-                    ))));
-                }
-                let byte_vector: Vec<u8> = s
-                    .as_str()
-                    .chars()
-                    .map(|c| c.to_digit(10).expect("Invalid digit") as u8)
-                    .collect();
-                Ok(Some(CqlValue::Varint(
-                    scylla::value::CqlVarint::from_signed_bytes_be(byte_vector),
-                )))
-            }
+            ColumnType::Native(NativeType::Varint) => match parse_varint_bytes(s.as_str()) {
+                Ok(bytes) => Ok(Some(CqlValue::Varint(CqlVarint::from_signed_bytes_be(
+                    bytes,
+                )))),
+                Err(e) => Err(Box::new(CassError(CassErrorKind::QueryParamConversion(
+                    format!("{v:?}"),
+                    "NativeType::Varint".to_string(),
+                    Some(e),
+                )))),
+            },
             ColumnType::Native(NativeType::Timeuuid) => match CqlTimeuuid::from_str(s.as_str()) {
                 Ok(timeuuid) => Ok(Some(CqlValue::Timeuuid(timeuuid))),
                 Err(e) => Err(Box::new(CassError(CassErrorKind::QueryParamConversion(
@@ -390,15 +387,16 @@ fn to_scylla_value(v: &Value, typ: &ColumnType) -> Result<Option<CqlValue>, Box<
                     Some(format!("{e}")),
                 )))),
             },
-            ColumnType::Native(NativeType::Decimal) => {
-                let decimal = rust_decimal::Decimal::from_str_exact(s.as_str()).unwrap();
-                Ok(Some(CqlValue::Decimal(
-                    scylla::value::CqlDecimal::from_signed_be_bytes_and_exponent(
-                        decimal.mantissa().to_be_bytes().to_vec(),
-                        decimal.scale().try_into().unwrap(),
-                    ),
-                )))
-            }
+            ColumnType::Native(NativeType::Decimal) => match parse_decimal(s.as_str()) {
+                Ok((unscaled, scale)) => Ok(Some(CqlValue::Decimal(
+                    scylla::value::CqlDecimal::from_signed_be_bytes_and_exponent(unscaled, scale),
+                ))),
+                Err(e) => Err(Box::new(CassError(CassErrorKind::QueryParamConversion(
+                    format!("{v:?}"),
+                    "NativeType::Decimal".to_string(),
+                    Some(e),
+                )))),
+            },
             _ => type_mismatch(v, typ),
         };
     }
@@ -419,13 +417,7 @@ fn to_scylla_value(v: &Value, typ: &ColumnType) -> Result<Option<CqlValue>, Box<
                     vec.iter().map(|v| v.as_unsigned().unwrap() as u8).collect();
                 Ok(Some(CqlValue::Blob(byte_vec)))
             }
-            ColumnType::Tuple(tuple) => {
-                let mut elements = Vec::with_capacity(vec.len());
-                for (i, current_element) in vec.iter().enumerate() {
-                    elements.push(to_scylla_value(current_element, &tuple[i])?);
-                }
-                Ok(Some(CqlValue::Tuple(elements)))
-            }
+            ColumnType::Tuple(types) => to_scylla_tuple(v, vec.iter(), types, typ),
             ColumnType::Vector { typ: elem_typ, .. } => {
                 let elements = vec
                     .iter()
@@ -518,13 +510,7 @@ fn to_scylla_value(v: &Value, typ: &ColumnType) -> Result<Option<CqlValue>, Box<
     // OwnedTuple
     if let Ok(tuple) = v.borrow_ref::<OwnedTuple>() {
         return match typ {
-            ColumnType::Tuple(types) => {
-                let mut elements = Vec::with_capacity(tuple.len());
-                for (i, current_element) in tuple.iter().enumerate() {
-                    elements.push(to_scylla_value(current_element, &types[i])?);
-                }
-                Ok(Some(CqlValue::Tuple(elements)))
-            }
+            ColumnType::Tuple(types) => to_scylla_tuple(v, tuple.iter(), types, typ),
             _ => type_mismatch(v, typ),
         };
     }
@@ -545,6 +531,15 @@ fn to_scylla_value(v: &Value, typ: &ColumnType) -> Result<Option<CqlValue>, Box<
                 }
                 Ok(Some(CqlValue::Map(map_vec)))
             }
+            ColumnType::Native(NativeType::Duration) => duration_from_object(&obj)
+                .map(|d| Some(CqlValue::Duration((&d).into())))
+                .map_err(|e| {
+                    Box::new(CassError(CassErrorKind::QueryParamConversion(
+                        format!("{v:?}"),
+                        "NativeType::Duration".to_string(),
+                        Some(e),
+                    )))
+                }),
             ColumnType::UserDefinedType { definition, .. } => {
                 let field_types: Vec<(String, ColumnType)> = definition
                     .field_types
@@ -582,10 +577,85 @@ fn to_scylla_value(v: &Value, typ: &ColumnType) -> Result<Option<CqlValue>, Box<
         };
     }
 
+    // Duration (custom Rune Any type)
+    if let Ok(d) = v.borrow_ref::<Duration>() {
+        return match typ {
+            ColumnType::Native(NativeType::Duration) => Ok(Some(CqlValue::Duration((&*d).into()))),
+            _ => type_mismatch(v, typ),
+        };
+    }
+
+    // Results of the `to_i8`, `to_i16`, `to_i32` and `to_f32` conversions (custom Rune Any types)
+    if let Ok(i) = v.borrow_ref::<Int8>() {
+        return match typ {
+            ColumnType::Native(NativeType::TinyInt) => Ok(Some(CqlValue::TinyInt(i.0))),
+            _ => type_mismatch(v, typ),
+        };
+    }
+    if let Ok(i) = v.borrow_ref::<Int16>() {
+        return match typ {
+            ColumnType::Native(NativeType::SmallInt) => Ok(Some(CqlValue::SmallInt(i.0))),
+            _ => type_mismatch(v, typ),
+        };
+    }
+    if let Ok(i) = v.borrow_ref::<Int32>() {
+        return match typ {
+            ColumnType::Native(NativeType::Int) => Ok(Some(CqlValue::Int(i.0))),
+            _ => type_mismatch(v, typ),
+        };
+    }
+    if let Ok(f) = v.borrow_ref::<Float32>() {
+        return match typ {
+            ColumnType::Native(NativeType::Float) => Ok(Some(CqlValue::Float(f.0))),
+            _ => type_mismatch(v, typ),
+        };
+    }
+
+    // Date (custom Rune Any type)
+    if let Ok(date) = v.borrow_ref::<Date>() {
+        return match typ {
+            ColumnType::Native(NativeType::Date) => Ok(Some(CqlValue::Date(CqlDate::from(date.0)))),
+            _ => type_mismatch(v, typ),
+        };
+    }
+
+    // Inet (custom Rune Any type)
+    if let Ok(inet) = v.borrow_ref::<Inet>() {
+        return match typ {
+            ColumnType::Native(NativeType::Inet) => Ok(Some(CqlValue::Inet(inet.0))),
+            _ => type_mismatch(v, typ),
+        };
+    }
+
+    // Varint and Decimal (custom Rune Any types)
+    if let Ok(varint) = v.borrow_ref::<Varint>() {
+        return match typ {
+            ColumnType::Native(NativeType::Varint) => Ok(Some(CqlValue::Varint(varint.0.clone()))),
+            ColumnType::Native(NativeType::Decimal) => Ok(Some(CqlValue::Decimal(
+                scylla::value::CqlDecimal::from_signed_be_bytes_and_exponent(
+                    varint.0.as_signed_bytes_be_slice().to_vec(),
+                    0,
+                ),
+            ))),
+            _ => type_mismatch(v, typ),
+        };
+    }
+    if let Ok(decimal) = v.borrow_ref::<Decimal>() {
+        return match typ {
+            ColumnType::Native(NativeType::Decimal) => {
+                Ok(Some(CqlValue::Decimal(decimal.0.clone())))
+            }
+            _ => type_mismatch(v, typ),
+        };
+    }
+
     // UUID (custom Rune Any type)
     if let Ok(uuid) = v.borrow_ref::<Uuid>() {
         return match typ {
             ColumnType::Native(NativeType::Uuid) => Ok(Some(CqlValue::Uuid(uuid.0))),
+            ColumnType::Native(NativeType::Timeuuid) => {
+                Ok(Some(CqlValue::Timeuuid(CqlTimeuuid::from(uuid.0))))
+            }
             _ => type_mismatch(v, typ),
         };
     }
@@ -593,6 +663,46 @@ fn to_scylla_value(v: &Value, typ: &ColumnType) -> Result<Option<CqlValue>, Box<
     type_mismatch(v, typ)
 }
 
+/// Reads a duration from an object shaped like durations read from the database,
+/// i.e. with `months`, `days` and `nanoseconds` fields. Missing fields are zero.
+fn duration_from_object(obj: &Object) -> Result<Duration, String> {
+    let component = |name: &str| {
+        obj.get(name)
+            .map_or(Ok(0), |c| c.as_integer::<i64>())
+            .map_err(|e| format!("invalid duration {name}: {e}"))
+    };
+    Duration::new(
+        component("months")?,
+        component("days")?,
+        component("nanoseconds")?,
+    )
+}
+
+/// Converts the elements of a rune vector or tuple into a CQL tuple of the given element types.
+fn to_scylla_tuple<'a>(
+    v: &Value,
+    elements: impl ExactSizeIterator<Item = &'a Value>,
+    types: &[ColumnType],
+    typ: &ColumnType,
+) -> Result<Option<CqlValue>, Box<CassError>> {
+    if elements.len() != types.len() {
+        return Err(Box::new(CassError(CassErrorKind::QueryParamConversion(
+            format!("{v:?}"),
+            format!("{typ:?}"),
+            Some(format!(
+                "expected {} tuple elements, got {}",
+                types.len(),
+                elements.len()
+            )),
+        ))));
+    }
+    let elements = elements
+        .zip(types)
+        .map(|(element, typ)| to_scylla_value(element, typ))
+        .try_collect()?;
+    Ok(Some(CqlValue::Tuple(elements)))
+}
+
 fn type_mismatch(v: &Value, typ: &ColumnType) -> Result<Option<CqlValue>, Box<CassError>> {
     Err(Box::new(CassError(CassErrorKind::QueryParamConversion(
         format!("{v:?}"),
@@ -636,6 +746,7 @@ mod tests {
     use rstest::rstest;
     use rune::alloc::String as RuneString;
     use rune::runtime::{Object, Vec as RuneVec};
+    use scylla::cluster::metadata::UserDefinedType;
     use scylla::frame::response::result::TableSpec;
     use scylla::serialize::row::RowSerializationContext;
     use scylla::serialize::writers::RowWriter;
@@ -1034,6 +1145,88 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_to_scylla_value_tuple_from_vec() {
+        let val = rune_vec(vec![rune_int(1), rune_string("hello")]);
+        let typ = ColumnType::Tuple(vec![
+            ColumnType::Native(NativeType::BigInt),
+            ColumnType::Native(NativeType::Text),
+        ]);
+        let result = to_scylla_value(&val, &typ).unwrap().unwrap();
+        assert_eq!(
+            result,
+            CqlValue::Tuple(vec![
+                Some(CqlValue::BigInt(1)),
+                Some(CqlValue::Text("hello".to_string())),
+            ])
+        );
+    }
+
+    #[rstest]
+    #[case::vec_too_long(rune_vec(vec![rune_int(1), rune_int(2), rune_int(3)]))]
+    #[case::vec_too_short(rune_vec(vec![rune_int(1)]))]
+    #[case::tuple_too_long(rune_tuple(vec![rune_int(1), rune_int(2), rune_int(3)]))]
+    #[case::tuple_too_short(rune_tuple(vec![rune_int(1)]))]
+    fn test_to_scylla_value_tuple_wrong_length_errors(#[case] val: Value) {
+        let typ = ColumnType::Tuple(vec![
+            ColumnType::Native(NativeType::Int),
+            ColumnType::Native(NativeType::Int),
+        ]);
+        let err = to_scylla_value(&val, &typ).unwrap_err();
+        assert!(err.to_string().contains("expected 2 tuple elements"));
+    }
+
+    #[test]
+    fn test_to_scylla_value_udt_from_object() {
+        let typ = ColumnType::UserDefinedType {
+            frozen: true,
+            definition: std::sync::Arc::new(UserDefinedType {
+                name: "address".into(),
+                keyspace: "ks".into(),
+                field_types: vec![
+                    ("street".into(), ColumnType::Native(NativeType::Text)),
+                    ("zip".into(), ColumnType::Native(NativeType::Int)),
+                    (
+                        "tags".into(),
+                        ColumnType::Collection {
+                            frozen: false,
+                            typ: CollectionType::List(Box::new(ColumnType::Native(
+                                NativeType::Text,
+                            ))),
+                        },
+                    ),
+                ],
+            }),
+        };
+        let val = rune_object(vec![
+            ("zip", rune_int(12345)),
+            ("street", rune_string("Main St")),
+            ("tags", rune_vec(vec![rune_string("home")])),
+        ]);
+        let result = to_scylla_value(&val, &typ).unwrap().unwrap();
+        assert_eq!(
+            result,
+            CqlValue::UserDefinedType {
+                name: "address".to_string(),
+                keyspace: "ks".to_string(),
+                fields: vec![
+                    (
+                        "street".to_string(),
+                        Some(CqlValue::Text("Main St".to_string()))
+                    ),
+                    ("zip".to_string(), Some(CqlValue::Int(12345))),
+                    (
+                        "tags".to_string(),
+                        Some(CqlValue::List(vec![CqlValue::Text("home".to_string())]))
+                    ),
+                ],
+            }
+        );
+
+        let wrong_field_type = rune_object(vec![("zip", rune_string("12345"))]);
+        assert!(to_scylla_value(&wrong_field_type, &typ).is_err());
+    }
+
     #[test]
     fn test_to_scylla_value_vector() {
         let val = rune_vec(vec![rune_float(1.0), rune_float(2.0), rune_float(3.0)]);
@@ -1256,6 +1449,297 @@ mod tests {
         assert_eq!(actual.unwrap().unwrap().to_string(), expected);
     }
 
+    fn varint_to_i128(bytes: &[u8]) -> i128 {
+        let fill = if bytes[0] >= 0x80 { 0xff } else { 0x00 };
+        let mut padded = [fill; 16];
+        padded[16 - bytes.len()..].copy_from_slice(bytes);
+        i128::from_be_bytes(padded)
+    }
+
+    #[rstest]
+    #[case("0", &[0x00])]
+    #[case("-0", &[0x00])]
+    #[case("123", &[0x7b])]
+    #[case("+127", &[0x7f])]
+    #[case("128", &[0x00, 0x80])]
+    #[case("-1", &[0xff])]
+    #[case("-128", &[0x80])]
+    #[case("-129", &[0xff, 0x7f])]
+    #[case("18446744073709551616", &[0x01, 0, 0, 0, 0, 0, 0, 0, 0])]
+    #[case("-18446744073709551616", &[0xff, 0, 0, 0, 0, 0, 0, 0, 0])]
+    fn test_parse_varint_bytes(#[case] input: &str, #[case] expected: &[u8]) {
+        assert_eq!(parse_varint_bytes(input).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parse_varint_bytes_matches_integers() {
+        for i in [
+            i64::MIN as i128,
+            i64::MAX as i128,
+            i128::MIN,
+            i128::MAX,
+            -255,
+            255,
+            -256,
+            32768,
+            -32769,
+        ] {
+            let bytes = parse_varint_bytes(&i.to_string()).unwrap();
+            assert_eq!(varint_to_i128(&bytes), i, "{i}");
+        }
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case("-")]
+    #[case("12a")]
+    #[case("1.5")]
+    #[case("--1")]
+    #[case(" 1")]
+    fn test_parse_varint_bytes_invalid(#[case] input: &str) {
+        assert!(parse_varint_bytes(input).is_err());
+    }
+
+    #[rstest]
+    #[case("1234.5678", 12345678, 4)]
+    #[case("-0.01", -1, 2)]
+    #[case("42", 42, 0)]
+    #[case(".5", 5, 1)]
+    #[case("5.", 5, 0)]
+    #[case("1.5e-3", 15, 4)]
+    #[case("-15E2", -15, -2)]
+    #[case("1e-2147483647", 1, 2147483647)]
+    #[case("1e2147483648", 1, -2147483648)]
+    fn test_parse_decimal(#[case] input: &str, #[case] unscaled: i128, #[case] scale: i32) {
+        let (bytes, actual_scale) = parse_decimal(input).unwrap();
+        assert_eq!((varint_to_i128(&bytes), actual_scale), (unscaled, scale));
+    }
+
+    #[rstest]
+    #[case("")]
+    #[case(".")]
+    #[case("1.2.3")]
+    #[case("1e")]
+    #[case("-+1")]
+    #[case("1.-2")]
+    #[case("abc")]
+    #[case("1e-2147483649")]
+    #[case("1e2147483649")]
+    #[case("1.5e-9223372036854775808")]
+    #[case("1e9223372036854775807")]
+    fn test_parse_decimal_invalid(#[case] input: &str) {
+        assert!(parse_decimal(input).is_err());
+        assert!(to_scylla_value(
+            &rune_string(input),
+            &ColumnType::Native(NativeType::Decimal)
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_to_scylla_value_big_numbers_from_string() {
+        let digits = "123456789012345678901234567890123456789";
+        let varint = to_scylla_value(
+            &rune_string(digits),
+            &ColumnType::Native(NativeType::Varint),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            varint,
+            CqlValue::Varint(CqlVarint::from_signed_bytes_be(
+                parse_varint_bytes(digits).unwrap()
+            ))
+        );
+        let decimal = to_scylla_value(
+            &rune_string("-12345678901234567890.123456789"),
+            &ColumnType::Native(NativeType::Decimal),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            decimal,
+            CqlValue::Decimal(
+                scylla::value::CqlDecimal::from_signed_be_bytes_and_exponent(
+                    parse_varint_bytes("-12345678901234567890123456789").unwrap(),
+                    9
+                )
+            )
+        );
+    }
+
+    #[test]
+    fn test_to_scylla_value_big_number_types() {
+        let bytes = parse_varint_bytes("-98765432109876543210").unwrap();
+        let varint = Value::new(Varint(CqlVarint::from_signed_bytes_be(bytes.clone()))).unwrap();
+        assert_eq!(
+            to_scylla_value(&varint, &ColumnType::Native(NativeType::Varint))
+                .unwrap()
+                .unwrap(),
+            CqlValue::Varint(CqlVarint::from_signed_bytes_be(bytes.clone()))
+        );
+        let decimal = Value::new(Decimal(
+            scylla::value::CqlDecimal::from_signed_be_bytes_and_exponent(bytes.clone(), 5),
+        ))
+        .unwrap();
+        assert_eq!(
+            to_scylla_value(&decimal, &ColumnType::Native(NativeType::Decimal))
+                .unwrap()
+                .unwrap(),
+            CqlValue::Decimal(
+                scylla::value::CqlDecimal::from_signed_be_bytes_and_exponent(bytes, 5)
+            )
+        );
+        assert!(to_scylla_value(&decimal, &ColumnType::Native(NativeType::Varint)).is_err());
+    }
+
+    #[test]
+    fn test_to_scylla_value_timeuuid_from_uuid() {
+        let id = uuid::Uuid::parse_str("58e0a7d7-eebc-11d8-9669-0800200c9a66").unwrap();
+        let v = Value::new(Uuid(id)).unwrap();
+        assert_eq!(
+            to_scylla_value(&v, &ColumnType::Native(NativeType::Timeuuid))
+                .unwrap()
+                .unwrap(),
+            CqlValue::Timeuuid(CqlTimeuuid::from(id))
+        );
+    }
+
+    #[test]
+    fn test_to_scylla_value_converted_numbers() {
+        let cases = [
+            (
+                Value::new(Int8(-8)).unwrap(),
+                NativeType::TinyInt,
+                CqlValue::TinyInt(-8),
+            ),
+            (
+                Value::new(Int16(i16::MAX)).unwrap(),
+                NativeType::SmallInt,
+                CqlValue::SmallInt(i16::MAX),
+            ),
+            (
+                Value::new(Int32(i32::MIN)).unwrap(),
+                NativeType::Int,
+                CqlValue::Int(i32::MIN),
+            ),
+            (
+                Value::new(Float32(1.5)).unwrap(),
+                NativeType::Float,
+                CqlValue::Float(1.5),
+            ),
+        ];
+        for (value, typ, expected) in cases {
+            let typ = ColumnType::Native(typ);
+            assert_eq!(to_scylla_value(&value, &typ).unwrap().unwrap(), expected);
+            assert!(to_scylla_value(&value, &ColumnType::Native(NativeType::BigInt)).is_err());
+        }
+    }
+
+    #[test]
+    fn test_to_scylla_value_date() {
+        let typ = ColumnType::Native(NativeType::Date);
+        let date = Value::new(Date::from_ymd(2024, 2, 29).unwrap()).unwrap();
+        let bound = to_scylla_value(&date, &typ).unwrap().unwrap();
+        // Dates are stored as days since the Unix epoch shifted by 2^31
+        assert_eq!(bound, CqlValue::Date(CqlDate((1u32 << 31) + 19782)));
+        assert_eq!(
+            to_scylla_value(&rune_string("2024-02-29"), &typ)
+                .unwrap()
+                .unwrap(),
+            bound
+        );
+        assert!(to_scylla_value(&date, &ColumnType::Native(NativeType::Timestamp)).is_err());
+
+        assert!(Date::from_ymd(2023, 2, 29).is_none());
+        assert!(Date::from_ymd(2024, 13, 1).is_none());
+        assert!(Date::from_ymd(2024, -1, 1).is_none());
+    }
+
+    #[test]
+    fn test_to_scylla_value_inet() {
+        let typ = ColumnType::Native(NativeType::Inet);
+        for address in ["192.168.0.1", "::1", "2001:db8::8a2e:370:7334"] {
+            let expected = CqlValue::Inet(address.parse().unwrap());
+            let inet = Value::new(Inet(address.parse().unwrap())).unwrap();
+            assert_eq!(to_scylla_value(&inet, &typ).unwrap().unwrap(), expected);
+            assert_eq!(
+                to_scylla_value(&rune_string(address), &typ)
+                    .unwrap()
+                    .unwrap(),
+                expected
+            );
+        }
+        let inet = Value::new(Inet("10.0.0.1".parse().unwrap())).unwrap();
+        assert!(to_scylla_value(&inet, &ColumnType::Native(NativeType::Text)).is_err());
+        assert!(to_scylla_value(&rune_string("300.0.0.1"), &typ).is_err());
+    }
+
+    #[test]
+    fn test_duration_round_trip() {
+        use crate::scripting::cql::deserialize::RuneValue;
+        use scylla::deserialize::value::DeserializeValue;
+        use scylla::deserialize::FrameSlice;
+        use scylla::serialize::writers::CellWriter;
+
+        let typ = ColumnType::Native(NativeType::Duration);
+        let duration = Value::new(Duration::new(14, 3, 5_000_000_123).unwrap()).unwrap();
+        let bound = to_scylla_value(&duration, &typ).unwrap().unwrap();
+        assert_eq!(bound.to_string(), "14mo3d5000000123ns");
+
+        // Encode as the database would return it and decode like `execute_with_result` does
+        let mut buf = Vec::new();
+        bound.serialize(&typ, CellWriter::new(&mut buf)).unwrap();
+        let bytes = bytes::Bytes::copy_from_slice(&buf[4..]);
+        let read = RuneValue::deserialize(&typ, Some(FrameSlice::new(&bytes)))
+            .unwrap()
+            .0;
+        {
+            let obj = read.borrow_ref::<Object>().unwrap();
+            assert_eq!(obj.get("months").unwrap().as_signed().unwrap(), 14);
+            assert_eq!(obj.get("days").unwrap().as_signed().unwrap(), 3);
+            assert_eq!(
+                obj.get("nanoseconds").unwrap().as_signed().unwrap(),
+                5_000_000_123
+            );
+        }
+        // The decoded object can be bound again
+        assert_eq!(to_scylla_value(&read, &typ).unwrap().unwrap(), bound);
+    }
+
+    #[rstest]
+    #[case::mixed_signs(1, -1, 0)]
+    #[case::months_out_of_range(i32::MAX as i64 + 1, 0, 0)]
+    #[case::days_out_of_range(0, i32::MIN as i64 - 1, 0)]
+    fn test_duration_invalid(#[case] months: i64, #[case] days: i64, #[case] nanoseconds: i64) {
+        assert!(Duration::new(months, days, nanoseconds).is_err());
+        let obj = rune_object(vec![
+            ("months", Value::from(months)),
+            ("days", Value::from(days)),
+            ("nanoseconds", Value::from(nanoseconds)),
+        ]);
+        assert!(to_scylla_value(&obj, &ColumnType::Native(NativeType::Duration)).is_err());
+    }
+
+    #[test]
+    fn test_duration_negative() {
+        let d = Duration::new(-1, -2, -3).unwrap();
+        let bound = to_scylla_value(
+            &Value::new(d).unwrap(),
+            &ColumnType::Native(NativeType::Duration),
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(
+            bound,
+            CqlValue::Duration(CqlDuration {
+                months: -1,
+                days: -2,
+                nanoseconds: -3
+            })
+        );
+    }
+
     #[rstest]
     #[case("")]
     #[case(" ")]
@@ -1,22 +1,57 @@
+use crate::config::RetryInterval;
 use crate::scripting::context::Context;
 use crate::scripting::db_error::{DbError, DbErrorKind};
 use crate::scripting::rune_uuid::Uuid;
 use crate::scripting::Resources;
+use base64::{engine::general_purpose as base64_engine, Engine as _};
 use chrono::Utc;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use hmac::{Hmac, KeyInit, Mac};
 use metrohash::MetroHash64;
 use rand::distr::Distribution;
 use rand::rngs::SmallRng;
+use rand::seq::SliceRandom;
 use rand::{Rng, SeedableRng};
-use rand_distr::{Normal, Uniform};
+use rand_distr::{Beta, LogNormal, Normal, Poisson, Uniform};
+use rayon::prelude::*;
+use rune::alloc::clone::TryClone;
 use rune::macros::{quote, MacroContext, TokenStream};
 use rune::parse::Parser;
-use rune::runtime::{Function, Ref, VmError, VmResult};
-use rune::{ast, vm_try, Value};
-use std::collections::HashMap;
+use rune::runtime::{Function, Object, Ref, SyncFunction, VmError, VmResult};
+use rune::{ast, vm_try, Any, ToValue, Value};
+use sha2::{Digest, Sha256, Sha512};
+use std::collections::{HashMap, HashSet};
 use std::fs::File;
 use std::hash::{Hash, Hasher};
 use std::io;
-use std::io::{BufRead, BufReader, ErrorKind, Read};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Write};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+
+/// String key-value store shared by all clones of a context, which lets workers exchange data.
+#[derive(Clone, Default)]
+pub struct SharedState(Arc<RwLock<HashMap<String, String>>>);
+
+impl SharedState {
+    pub fn read(&self, key: &str) -> Option<String> {
+        self.0
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(key)
+            .cloned()
+    }
+
+    pub fn write(&self, key: &str, value: &str) {
+        self.0
+            .write()
+            .unwrap_or_else(|e| e.into_inner())
+            .insert(key.to_string(), value.to_string());
+    }
+}
 
 /// Returns the literal value stored in the `params` map under the key given as the first
 /// macro arg, and if not found, returns the expression from the second arg.
@@ -98,6 +133,21 @@ pub fn extract_validation_args(validation_args: Vec<Value>) -> Result<Validation
     }
 }
 
+/// Calls the workload function configured by the `custom:<function>` validation strategy
+/// with the rows returned by a query and returns its verdict.
+/// Fails if the function has not been resolved, fails itself or does not return a boolean.
+pub fn call_validation_callback(
+    callback: Option<&SyncFunction>,
+    name: &str,
+    rows: Value,
+) -> Result<bool, String> {
+    let callback = callback.ok_or_else(|| format!("Validation function '{name}' is not set"))?;
+    callback
+        .call::<bool>((rows,))
+        .into_result()
+        .map_err(|e| format!("Validation function '{name}' failed: {e}"))
+}
+
 /// Creates a new UUID for current iteration
 #[rune::function]
 pub fn uuid(i: i64) -> Uuid {
@@ -132,11 +182,71 @@ fn hash2_inner(a: i64, b: i64) -> i64 {
     (hash.finish() & 0x7FFFFFFFFFFFFFFF) as i64
 }
 
+fn hash_range_between_inner(i: i64, min: i64, max: i64) -> VmResult<i64> {
+    if min >= max {
+        return VmResult::panic(format!(
+            "hash_range: range [{min}, {max}) must not be empty"
+        ));
+    }
+    let span = max as i128 - min as i128;
+    VmResult::Ok((min as i128 + hash_inner(i) as i128 % span) as i64)
+}
+
 /// Computes a hash of an integer value `i`.
-/// Returns a value in range `0..max`.
+/// Returns a value in range `0..max`; `max` must be positive.
+#[rune::function]
+pub fn hash_range(i: i64, max: i64) -> VmResult<i64> {
+    hash_range_between_inner(i, 0, max)
+}
+
+/// Computes a hash of an integer value `i`.
+/// Returns a value in range `min..max`, which may include negative values.
+#[rune::function]
+pub fn hash_range_between(i: i64, min: i64, max: i64) -> VmResult<i64> {
+    hash_range_between_inner(i, min, max)
+}
+
+fn hash_bool_inner(i: i64, true_probability: f64) -> VmResult<bool> {
+    if !(0.0..=1.0).contains(&true_probability) {
+        return VmResult::panic(format!(
+            "hash_bool: probability must be in range 0.0..=1.0, got {true_probability}"
+        ));
+    }
+    let threshold = (true_probability * i64::MAX as f64) as i64;
+    VmResult::Ok(hash_inner(i) < threshold)
+}
+
+/// Deterministically generates a boolean value based on the hash of `i`.
+/// Returns `true` with the given probability in range `0.0..=1.0`.
 #[rune::function]
-pub fn hash_range(i: i64, max: i64) -> i64 {
-    hash_inner(i) % max
+pub fn hash_bool(i: i64, true_probability: f64) -> VmResult<bool> {
+    hash_bool_inner(i, true_probability)
+}
+
+/// Jump consistent hash (Lamping & Veach, 2014).
+/// When the number of buckets grows from N to N+1, only about 1/(N+1) of the keys move.
+fn hash_consistent_inner(i: i64, num_vnodes: i64) -> VmResult<i64> {
+    if num_vnodes <= 0 {
+        return VmResult::panic(format!(
+            "hash_consistent: number of vnodes must be positive, got {num_vnodes}"
+        ));
+    }
+    let mut key = i as u64;
+    let mut bucket: i64 = -1;
+    let mut next: i64 = 0;
+    while next < num_vnodes {
+        bucket = next;
+        key = key.wrapping_mul(2862933555777941757).wrapping_add(1);
+        next = ((bucket + 1) as f64 * ((1u64 << 31) as f64 / ((key >> 33) + 1) as f64)) as i64;
+    }
+    VmResult::Ok(bucket)
+}
+
+/// Maps `i` to a bucket in range `0..num_vnodes` with consistent hashing,
+/// so that changing the number of buckets remaps only a small fraction of values.
+#[rune::function]
+pub fn hash_consistent(i: i64, num_vnodes: i64) -> VmResult<i64> {
+    hash_consistent_inner(i, num_vnodes)
 }
 
 /// Generates a 64-bits floating point value with normal distribution
@@ -165,6 +275,88 @@ pub fn uniform(i: i64, min: f64, max: f64) -> VmResult<f64> {
     VmResult::Ok(distribution.sample(&mut rng))
 }
 
+fn uniform_int_inner(i: i64, min: i64, max: i64) -> VmResult<i64> {
+    let mut rng = SmallRng::seed_from_u64(i as u64);
+    let distribution = vm_try!(Uniform::new(min, max).map_err(|e| VmError::panic(format!("{e}"))));
+    VmResult::Ok(distribution.sample(&mut rng))
+}
+
+/// Generates an integer uniformly distributed in range `[min, max)`.
+#[rune::function]
+pub fn uniform_int(i: i64, min: i64, max: i64) -> VmResult<i64> {
+    uniform_int_inner(i, min, max)
+}
+
+fn poisson_inner(i: i64, lambda: f64) -> VmResult<i64> {
+    if !(lambda > 0.0 && lambda.is_finite()) {
+        return VmResult::panic(format!(
+            "poisson: lambda must be a positive number, got {lambda}"
+        ));
+    }
+    let mut rng = SmallRng::seed_from_u64(i as u64);
+    let distribution = vm_try!(Poisson::new(lambda).map_err(|e| VmError::panic(format!("{e}"))));
+    VmResult::Ok(distribution.sample(&mut rng) as i64)
+}
+
+/// Generates a non-negative integer with Poisson distribution with mean `lambda`.
+#[rune::function]
+pub fn poisson(i: i64, lambda: f64) -> VmResult<i64> {
+    poisson_inner(i, lambda)
+}
+
+fn lognormal_inner(i: i64, mu: f64, sigma: f64) -> VmResult<f64> {
+    if !(sigma > 0.0 && sigma.is_finite()) {
+        return VmResult::panic(format!(
+            "lognormal: sigma must be a positive number, got {sigma}"
+        ));
+    }
+    let mut rng = SmallRng::seed_from_u64(i as u64);
+    let distribution =
+        vm_try!(LogNormal::new(mu, sigma).map_err(|e| VmError::panic(format!("lognormal: {e}"))));
+    VmResult::Ok(distribution.sample(&mut rng))
+}
+
+/// Generates a positive floating point number whose logarithm is normally distributed
+/// with mean `mu` and standard deviation `sigma`, e.g. for object sizes.
+#[rune::function]
+pub fn lognormal(i: i64, mu: f64, sigma: f64) -> VmResult<f64> {
+    lognormal_inner(i, mu, sigma)
+}
+
+fn beta_inner(i: i64, alpha: f64, beta_param: f64) -> VmResult<f64> {
+    let mut rng = SmallRng::seed_from_u64(i as u64);
+    let distribution =
+        vm_try!(Beta::new(alpha, beta_param).map_err(|e| VmError::panic(format!("beta: {e}"))));
+    VmResult::Ok(distribution.sample(&mut rng))
+}
+
+/// Generates a floating point number in range `[0.0, 1.0]` from a beta distribution
+/// with shape parameters `alpha` and `beta_param`, e.g. for ratios or completion percentages.
+#[rune::function]
+pub fn beta(i: i64, alpha: f64, beta_param: f64) -> VmResult<f64> {
+    beta_inner(i, alpha, beta_param)
+}
+
+fn geometric_inner(i: i64, p: f64) -> VmResult<i64> {
+    if !(p > 0.0 && p <= 1.0) {
+        return VmResult::panic(format!(
+            "geometric: probability must be in range (0.0, 1.0], got {p}"
+        ));
+    }
+    let mut rng = SmallRng::seed_from_u64(i as u64);
+    // 1 - random() is in (0, 1], so its logarithm is finite
+    let u = 1.0 - rng.random::<f64>();
+    VmResult::Ok((u.ln() / (1.0 - p).ln()).floor() as i64)
+}
+
+/// Generates the number of failed Bernoulli trials with success probability `p`
+/// before the first success, e.g. the number of retries of an operation.
+/// The mean of the distribution is `(1 - p) / p`.
+#[rune::function]
+pub fn geometric(i: i64, p: f64) -> VmResult<i64> {
+    geometric_inner(i, p)
+}
+
 /// Generates random blob of data of given length.
 /// Parameter `seed` is used to seed the RNG.
 #[rune::function]
@@ -173,6 +365,185 @@ pub fn blob(seed: i64, len: usize) -> Vec<u8> {
     (0..len).map(|_| rng.random::<u8>()).collect()
 }
 
+fn random_inet_inner(seed: i64, v6: bool) -> String {
+    let mut rng = SmallRng::seed_from_u64(seed as u64);
+    let address = if v6 {
+        IpAddr::V6(Ipv6Addr::from(rng.random::<u128>()))
+    } else {
+        IpAddr::V4(Ipv4Addr::from(rng.random::<u32>()))
+    };
+    address.to_string()
+}
+
+/// Generates a random IPv4 address, or an IPv6 address if `v6` is true.
+/// Parameter `seed` is used to seed the RNG.
+#[rune::function]
+pub fn random_inet(seed: i64, v6: bool) -> String {
+    random_inet_inner(seed, v6)
+}
+
+/// Converts a vector of integers in range `0..=255` into bytes.
+fn values_to_bytes(fn_name: &str, data: &[Value]) -> VmResult<Vec<u8>> {
+    let mut bytes = Vec::with_capacity(data.len());
+    for v in data {
+        match v
+            .as_integer::<i64>()
+            .ok()
+            .and_then(|b| u8::try_from(b).ok())
+        {
+            Some(b) => bytes.push(b),
+            None => return VmResult::panic(format!("{fn_name}: {v:?} is not a byte value")),
+        }
+    }
+    VmResult::Ok(bytes)
+}
+
+fn compress_blob_inner(data: &[Value]) -> VmResult<Vec<u8>> {
+    let bytes = vm_try!(values_to_bytes("compress_blob", data));
+    VmResult::Ok(lz4_flex::compress_prepend_size(&bytes))
+}
+
+fn decompress_blob_inner(data: &[Value]) -> VmResult<Vec<u8>> {
+    let bytes = vm_try!(values_to_bytes("decompress_blob", data));
+    match lz4_flex::decompress_size_prepended(&bytes) {
+        Ok(decompressed) => VmResult::Ok(decompressed),
+        Err(e) => VmResult::panic(format!("decompress_blob: invalid LZ4 data: {e}")),
+    }
+}
+
+/// Compresses a blob given as a vector of bytes with LZ4.
+/// The uncompressed size is stored in front of the compressed data.
+#[rune::function]
+pub fn compress_blob(data: &[Value]) -> VmResult<Vec<u8>> {
+    compress_blob_inner(data)
+}
+
+/// Decompresses a blob compressed with `compress_blob`.
+#[rune::function]
+pub fn decompress_blob(data: &[Value]) -> VmResult<Vec<u8>> {
+    decompress_blob_inner(data)
+}
+
+fn gzip_compress_inner(data: &[Value]) -> VmResult<Vec<u8>> {
+    let bytes = vm_try!(values_to_bytes("gzip_compress", data));
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+    match encoder.write_all(&bytes).and_then(|_| encoder.finish()) {
+        Ok(compressed) => VmResult::Ok(compressed),
+        Err(e) => VmResult::panic(format!("gzip_compress: {e}")),
+    }
+}
+
+fn gzip_decompress_inner(data: &[Value]) -> VmResult<Vec<u8>> {
+    let bytes = vm_try!(values_to_bytes("gzip_decompress", data));
+    let mut decompressed = Vec::new();
+    match GzDecoder::new(bytes.as_slice()).read_to_end(&mut decompressed) {
+        Ok(_) => VmResult::Ok(decompressed),
+        Err(e) => VmResult::panic(format!("gzip_decompress: invalid gzip data: {e}")),
+    }
+}
+
+/// Compresses a blob given as a vector of bytes with gzip.
+#[rune::function]
+pub fn gzip_compress(data: &[Value]) -> VmResult<Vec<u8>> {
+    gzip_compress_inner(data)
+}
+
+/// Decompresses a blob compressed with gzip.
+#[rune::function]
+pub fn gzip_decompress(data: &[Value]) -> VmResult<Vec<u8>> {
+    gzip_decompress_inner(data)
+}
+
+fn base64_encode_inner(data: &[Value]) -> VmResult<String> {
+    let bytes = vm_try!(values_to_bytes("base64_encode", data));
+    VmResult::Ok(base64_engine::STANDARD.encode(bytes))
+}
+
+fn base64_decode_inner(s: &str) -> VmResult<Vec<Value>> {
+    match base64_engine::STANDARD.decode(s) {
+        Ok(bytes) => VmResult::Ok(bytes.into_iter().map(|b| Value::from(b as i64)).collect()),
+        Err(e) => VmResult::panic(format!("base64_decode: invalid base64 string: {e}")),
+    }
+}
+
+/// Encodes a vector of bytes as a base64 string with padding.
+#[rune::function]
+pub fn base64_encode(data: &[Value]) -> VmResult<String> {
+    base64_encode_inner(data)
+}
+
+/// Decodes a base64 string with padding into a vector of bytes.
+#[rune::function]
+pub fn base64_decode(s: &str) -> VmResult<Vec<Value>> {
+    base64_decode_inner(s)
+}
+
+fn hex_encode_inner(data: &[Value]) -> VmResult<String> {
+    let bytes = vm_try!(values_to_bytes("hex_encode", data));
+    VmResult::Ok(hex::encode(bytes))
+}
+
+fn hex_decode_inner(s: &str) -> VmResult<Vec<Value>> {
+    match hex::decode(s) {
+        Ok(bytes) => VmResult::Ok(bytes.into_iter().map(|b| Value::from(b as i64)).collect()),
+        Err(e) => VmResult::panic(format!("hex_decode: invalid hex string '{s}': {e}")),
+    }
+}
+
+/// Encodes a vector of bytes as a lowercase hex string.
+#[rune::function]
+pub fn hex_encode(data: &[Value]) -> VmResult<String> {
+    hex_encode_inner(data)
+}
+
+/// Decodes a hex string, in lower or upper case, into a vector of bytes.
+#[rune::function]
+pub fn hex_decode(s: &str) -> VmResult<Vec<Value>> {
+    hex_decode_inner(s)
+}
+
+fn sha256_hash_inner(data: &[Value]) -> VmResult<Vec<u8>> {
+    let bytes = vm_try!(values_to_bytes("sha256_hash", data));
+    VmResult::Ok(Sha256::digest(bytes).to_vec())
+}
+
+fn sha512_hash_inner(data: &[Value]) -> VmResult<Vec<u8>> {
+    let bytes = vm_try!(values_to_bytes("sha512_hash", data));
+    VmResult::Ok(Sha512::digest(bytes).to_vec())
+}
+
+fn hmac_sha256_inner(key: &[Value], data: &[Value]) -> VmResult<Vec<u8>> {
+    if key.is_empty() {
+        return VmResult::panic("hmac_sha256: key must not be empty");
+    }
+    let key = vm_try!(values_to_bytes("hmac_sha256", key));
+    let data = vm_try!(values_to_bytes("hmac_sha256", data));
+    let mut mac = match Hmac::<Sha256>::new_from_slice(&key) {
+        Ok(mac) => mac,
+        Err(e) => return VmResult::panic(format!("hmac_sha256: invalid key: {e}")),
+    };
+    mac.update(&data);
+    VmResult::Ok(mac.finalize().into_bytes().to_vec())
+}
+
+/// Computes the 32-byte HMAC-SHA256 of a vector of bytes with the given non-empty key.
+#[rune::function]
+pub fn hmac_sha256(key: &[Value], data: &[Value]) -> VmResult<Vec<u8>> {
+    hmac_sha256_inner(key, data)
+}
+
+/// Computes the 32-byte SHA-256 digest of a vector of bytes.
+#[rune::function]
+pub fn sha256_hash(data: &[Value]) -> VmResult<Vec<u8>> {
+    sha256_hash_inner(data)
+}
+
+/// Computes the 64-byte SHA-512 digest of a vector of bytes.
+#[rune::function]
+pub fn sha512_hash(data: &[Value]) -> VmResult<Vec<u8>> {
+    sha512_hash_inner(data)
+}
+
 /// Generates random string of given length.
 /// Parameter `seed` is used to seed
 /// the RNG.
@@ -191,42 +562,412 @@ pub fn text(seed: i64, len: usize) -> String {
         .collect()
 }
 
+fn vector_inner(len: usize, generator: Function) -> VmResult<Vec<Value>> {
+    let mut result = Vec::with_capacity(len);
+    for i in 0..len {
+        match generator.call((i,)) {
+            VmResult::Ok(value) => result.push(value),
+            VmResult::Err(e) => {
+                return VmResult::panic(format!("vector: generator failed at index {i}: {e}"))
+            }
+        }
+    }
+    VmResult::Ok(result)
+}
+
+/// Generates a vector of `len` elements by calling `generator` with each element index.
+/// The generator is called `len` times, so the cost is O(len).
+/// Stops at the first failing call and reports its index.
 #[rune::function]
 pub fn vector(len: usize, generator: Function) -> VmResult<Vec<Value>> {
-    let mut result = Vec::with_capacity(len);
+    vector_inner(len, generator)
+}
+
+fn map_gen_inner(
+    len: usize,
+    key_generator: Function,
+    value_generator: Function,
+) -> VmResult<Object> {
+    let mut result = vm_try!(Object::with_capacity(len));
     for i in 0..len {
-        let value = vm_try!(generator.call((i,)));
-        result.push(value);
+        let key: String = match key_generator.call((i,)) {
+            VmResult::Ok(key) => key,
+            VmResult::Err(e) => {
+                return VmResult::panic(format!("map_gen: key generator failed at index {i}: {e}"))
+            }
+        };
+        let value: Value = match value_generator.call((i,)) {
+            VmResult::Ok(value) => value,
+            VmResult::Err(e) => {
+                return VmResult::panic(format!(
+                    "map_gen: value generator failed at index {i}: {e}"
+                ))
+            }
+        };
+        vm_try!(result.insert(vm_try!(rune::alloc::String::try_from(key)), value));
     }
     VmResult::Ok(result)
 }
 
+/// Generates an object (usable as a CQL map) by calling `key_generator` and `value_generator`
+/// with each index in `0..len`. Keys must be strings.
+/// If the key generator returns the same key more than once, the last value wins,
+/// so the result may have fewer than `len` entries.
+#[rune::function]
+pub fn map_gen(len: usize, key_generator: Function, value_generator: Function) -> VmResult<Object> {
+    map_gen_inner(len, key_generator, value_generator)
+}
+
+fn set_gen_inner(seed: i64, len: usize, min: i64, max: i64) -> VmResult<Vec<i64>> {
+    let range = max as i128 - min as i128;
+    if range < len as i128 {
+        return VmResult::panic(format!(
+            "set_gen: cannot generate {len} distinct values in range [{min}, {max})"
+        ));
+    }
+    let range = range as u64;
+    let mut rng = SmallRng::seed_from_u64(seed as u64);
+    // Floyd's algorithm: selects `len` distinct offsets using exactly `len` random draws.
+    let mut offsets = HashSet::with_capacity(len);
+    for j in (range - len as u64)..range {
+        let t = rng.random_range(0..=j);
+        if !offsets.insert(t) {
+            offsets.insert(j);
+        }
+    }
+    let mut result: Vec<i64> = offsets
+        .into_iter()
+        .map(|offset| (min as i128 + offset as i128) as i64)
+        .collect();
+    result.sort_unstable();
+    VmResult::Ok(result)
+}
+
+/// Generates a sorted vector of `len` distinct integers from range `[min, max)`.
+/// Parameter `seed` is used to seed the RNG.
+#[rune::function]
+pub fn set_gen(seed: i64, len: usize, min: i64, max: i64) -> VmResult<Vec<i64>> {
+    set_gen_inner(seed, len, min, max)
+}
+
 /// Generates 'now' timestamp
 #[rune::function]
 pub fn now_timestamp() -> i64 {
     Utc::now().timestamp()
 }
 
+/// Measures the time of a part of an iteration, e.g. a sequence of requests.
+#[derive(Clone, Debug, Any)]
+pub struct StopWatch {
+    start: Instant,
+}
+
+impl StopWatch {
+    fn elapsed_ms(&self) -> f64 {
+        self.start.elapsed().as_secs_f64() * 1000.0
+    }
+}
+
+/// Returns a new stopwatch measuring time from now.
+#[rune::function]
+pub fn start_stopwatch() -> StopWatch {
+    StopWatch {
+        start: Instant::now(),
+    }
+}
+
+/// Returns the number of milliseconds since the stopwatch was started or last reset.
+#[rune::function]
+pub fn stopwatch_elapsed_ms(sw: &StopWatch) -> f64 {
+    sw.elapsed_ms()
+}
+
+/// Restarts the measurement of the stopwatch from now.
+#[rune::function]
+pub fn stopwatch_reset(sw: &mut StopWatch) {
+    sw.start = Instant::now();
+}
+
+async fn sleep_until_timestamp_inner(unix_ms: i64) {
+    let delay = unix_ms.saturating_sub(Utc::now().timestamp_millis()).max(0) as u64;
+    tokio::time::sleep_until(tokio::time::Instant::now() + Duration::from_millis(delay)).await;
+}
+
+/// Sleeps until the given wall-clock time in milliseconds since the Unix epoch,
+/// e.g. to start several latte processes at a pre-agreed time.
+/// Returns immediately if the time has already passed.
+#[rune::function]
+pub async fn sleep_until_timestamp(unix_ms: i64) -> VmResult<()> {
+    sleep_until_timestamp_inner(unix_ms).await;
+    VmResult::Ok(())
+}
+
 /// Selects one item from the collection based on the hash of the given value.
 #[rune::function]
 pub fn hash_select(i: i64, collection: &[Value]) -> Value {
     collection[(hash_inner(i) % collection.len() as i64) as usize].clone()
 }
 
-/// Joins all strings in vector with given separator
+fn sample_without_replacement_inner(
+    i: i64,
+    k: usize,
+    collection: &[Value],
+) -> VmResult<Vec<Value>> {
+    if k > collection.len() {
+        return VmResult::panic(format!(
+            "sample_without_replacement: cannot select {k} items from a collection of {}",
+            collection.len()
+        ));
+    }
+    let mut rng = SmallRng::seed_from_u64(i as u64);
+    // Partial Fisher-Yates shuffle of the indexes: only the first `k` positions are drawn.
+    let mut indexes: Vec<usize> = (0..collection.len()).collect();
+    for j in 0..k {
+        let t = rng.random_range(j..indexes.len());
+        indexes.swap(j, t);
+    }
+    VmResult::Ok(
+        indexes[..k]
+            .iter()
+            .map(|&idx| collection[idx].clone())
+            .collect(),
+    )
+}
+
+/// Selects `k` items at distinct positions of the collection, in an order determined by the seed `i`.
+#[rune::function]
+pub fn sample_without_replacement(i: i64, k: usize, collection: &[Value]) -> VmResult<Vec<Value>> {
+    sample_without_replacement_inner(i, k, collection)
+}
+
+fn hash_category_inner(i: i64, labels: &[Value], weights: &[Value]) -> VmResult<Value> {
+    if labels.len() != weights.len() {
+        return VmResult::panic(format!(
+            "hash_category: number of labels ({}) and weights ({}) must be equal",
+            labels.len(),
+            weights.len()
+        ));
+    }
+    let mut numeric_weights = Vec::with_capacity(weights.len());
+    for w in weights {
+        let w = match (w.as_float(), w.as_signed()) {
+            (Ok(f), _) => f,
+            (_, Ok(i)) => i as f64,
+            _ => return VmResult::panic(format!("hash_category: weight {w:?} is not a number")),
+        };
+        if !w.is_finite() || w < 0.0 {
+            return VmResult::panic(format!(
+                "hash_category: weights must be non-negative finite numbers, got {w}"
+            ));
+        }
+        numeric_weights.push(w);
+    }
+    let total: f64 = numeric_weights.iter().sum();
+    if total <= 0.0 {
+        return VmResult::panic("hash_category: total weight must be greater than 0");
+    }
+    let target = hash_inner(i) as f64 / i64::MAX as f64 * total;
+    let mut cumulative = 0.0;
+    let mut selected = 0;
+    for (idx, w) in numeric_weights.into_iter().enumerate() {
+        if w > 0.0 {
+            // Falls back to the last label with a positive weight on floating point rounding
+            selected = idx;
+            cumulative += w;
+            if target < cumulative {
+                break;
+            }
+        }
+    }
+    VmResult::Ok(labels[selected].clone())
+}
+
+/// Deterministically selects one of the labels based on the hash of `i`.
+/// Each label is selected with probability proportional to its weight.
+#[rune::function]
+pub fn hash_category(i: i64, labels: &[Value], weights: &[Value]) -> VmResult<Value> {
+    hash_category_inner(i, labels, weights)
+}
+
+/// Joins all strings in vector with given separator
+#[rune::function]
+pub fn join(collection: &[Value], separator: &str) -> VmResult<String> {
+    let mut result = String::new();
+    let mut first = true;
+    for v in collection {
+        let v = vm_try!(v.clone().into_string());
+        if !first {
+            result.push_str(separator);
+        }
+        result.push_str(v.as_str());
+        first = false;
+    }
+    VmResult::Ok(result)
+}
+
+fn zip_inner(a: Vec<Value>, b: Vec<Value>) -> VmResult<Vec<Value>> {
+    if a.len() != b.len() {
+        return VmResult::panic(format!(
+            "zip: vectors must have the same length, got {} and {}",
+            a.len(),
+            b.len()
+        ));
+    }
+    let mut result = Vec::with_capacity(a.len());
+    for (x, y) in a.into_iter().zip(b) {
+        result.push(vm_try!(vec![x, y].to_value()));
+    }
+    VmResult::Ok(result)
+}
+
+/// Pairs up elements of two vectors of equal length into a vector of `[a, b]` pairs.
+#[rune::function]
+pub fn zip(a: Vec<Value>, b: Vec<Value>) -> VmResult<Vec<Value>> {
+    zip_inner(a, b)
+}
+
+fn enumerate_inner(a: Vec<Value>) -> VmResult<Vec<Value>> {
+    let mut result = Vec::with_capacity(a.len());
+    for (i, v) in a.into_iter().enumerate() {
+        result.push(vm_try!(vec![Value::from(i as i64), v].to_value()));
+    }
+    VmResult::Ok(result)
+}
+
+/// Returns a vector of `[index, value]` pairs for all elements of the input vector.
+#[rune::function]
+pub fn enumerate(a: Vec<Value>) -> VmResult<Vec<Value>> {
+    enumerate_inner(a)
+}
+
+fn chunk_inner(arr: Vec<Value>, size: usize) -> VmResult<Vec<Value>> {
+    if size == 0 {
+        return VmResult::panic("chunk: size must be greater than 0");
+    }
+    let mut result = Vec::with_capacity(arr.len().div_ceil(size));
+    for chunk in arr.chunks(size) {
+        result.push(vm_try!(chunk.to_vec().to_value()));
+    }
+    VmResult::Ok(result)
+}
+
+/// Splits a vector into consecutive sub-vectors of at most `size` elements.
+#[rune::function]
+pub fn chunk(arr: Vec<Value>, size: usize) -> VmResult<Vec<Value>> {
+    chunk_inner(arr, size)
+}
+
+fn shuffle_inner(i: i64, mut arr: Vec<Value>) -> Vec<Value> {
+    let mut rng = SmallRng::seed_from_u64(i as u64);
+    arr.shuffle(&mut rng);
+    arr
+}
+
+/// Returns a copy of the vector with the elements in a random order determined by the seed `i`.
+#[rune::function]
+pub fn shuffle(i: i64, arr: Vec<Value>) -> Vec<Value> {
+    shuffle_inner(i, arr)
+}
+
+fn flatten_inner(arr: Vec<Value>) -> VmResult<Vec<Value>> {
+    let mut result = Vec::with_capacity(arr.len());
+    for v in arr {
+        if let Ok(nested) = v.borrow_ref::<rune::runtime::Vec>() {
+            result.extend(nested.iter().cloned());
+            continue;
+        }
+        result.push(v);
+    }
+    VmResult::Ok(result)
+}
+
+/// Flattens nested vectors by one level. Elements that are not vectors are kept as they are.
+#[rune::function]
+pub fn flatten(arr: Vec<Value>) -> VmResult<Vec<Value>> {
+    flatten_inner(arr)
+}
+
+fn map_keys_inner(obj: Object, f: Function) -> VmResult<Object> {
+    let mut result = vm_try!(Object::with_capacity(obj.len()));
+    for (k, v) in obj {
+        let key: String = vm_try!(f.call((k.as_str(),)));
+        vm_try!(result.insert(vm_try!(rune::alloc::String::try_from(key)), v));
+    }
+    VmResult::Ok(result)
+}
+
+/// Returns a new object with every key replaced by the result of calling `f` on it.
+/// Values are kept unchanged. If `f` maps two keys to the same key, only one entry is kept.
+#[rune::function]
+pub fn map_keys(obj: Object, f: Function) -> VmResult<Object> {
+    map_keys_inner(obj, f)
+}
+
+fn map_values_inner(obj: Object, f: Function) -> VmResult<Value> {
+    let mut result = vm_try!(Object::with_capacity(obj.len()));
+    for (k, v) in obj {
+        let value: Value = vm_try!(f.call((v,)));
+        vm_try!(result.insert(k, value));
+    }
+    VmResult::Ok(vm_try!(result.to_value()))
+}
+
+/// Returns a new object with every value replaced by the result of calling `f` on it.
+#[rune::function]
+pub fn map_values(obj: Object, f: Function) -> VmResult<Value> {
+    map_values_inner(obj, f)
+}
+
+fn merge_objects_inner(base: Object, override_: Object) -> VmResult<Object> {
+    let mut result = base;
+    for (k, v) in override_ {
+        let merged = match (result.get(k.as_str()), v.borrow_ref::<Object>()) {
+            (Some(base_value), Ok(override_obj)) => match base_value.borrow_ref::<Object>() {
+                Ok(base_obj) => {
+                    let base_obj = vm_try!(base_obj.try_clone());
+                    let override_obj = vm_try!(override_obj.try_clone());
+                    Some(vm_try!(vm_try!(merge_objects_inner(
+                        base_obj,
+                        override_obj
+                    ))
+                    .to_value()))
+                }
+                Err(_) => None,
+            },
+            _ => None,
+        };
+        vm_try!(result.insert(k, merged.unwrap_or(v)));
+    }
+    VmResult::Ok(result)
+}
+
+/// Deep-merges two objects. Nested objects present in both arguments are merged recursively,
+/// any other value from `override_` replaces the value in `base`.
 #[rune::function]
-pub fn join(collection: &[Value], separator: &str) -> VmResult<String> {
-    let mut result = String::new();
-    let mut first = true;
-    for v in collection {
-        let v = vm_try!(v.clone().into_string());
-        if !first {
-            result.push_str(separator);
+pub fn merge_objects(base: Object, override_: Object) -> VmResult<Object> {
+    merge_objects_inner(base, override_)
+}
+
+fn object_get_path_inner(obj: Value, path: &str) -> VmResult<Value> {
+    let mut current = obj;
+    for segment in path.split('.') {
+        let next = match current.borrow_ref::<Object>() {
+            Ok(obj) => obj.get(segment).cloned(),
+            Err(_) => None,
+        };
+        match next {
+            Some(next) => current = next,
+            None => return VmResult::Ok(vm_try!(None::<Value>.to_value())),
         }
-        result.push_str(v.as_str());
-        first = false;
     }
-    VmResult::Ok(result)
+    VmResult::Ok(current)
+}
+
+/// Returns the value found in nested objects under the dot-separated `path`, e.g. `"a.b.c"`.
+/// Returns `None` if any segment of the path is missing.
+#[rune::function]
+pub fn object_get_path(obj: Value, path: &str) -> VmResult<Value> {
+    object_get_path_inner(obj, path)
 }
 
 /// Checks whether input value is of None type or not
@@ -243,6 +984,23 @@ pub fn is_none(input: Value) -> bool {
     false
 }
 
+fn parse_retry_interval_inner(s: &str) -> Result<Vec<u64>, String> {
+    let interval = RetryInterval::new(s)?;
+    Ok(vec![
+        interval.min.as_millis() as u64,
+        interval.max.as_millis() as u64,
+    ])
+}
+
+/// Parses a retry interval in the `--retry-interval` format, e.g. `"500ms"` or `"200ms,5s"`,
+/// and returns its `[min, max]` bounds in milliseconds.
+/// Returns `DbError` so scripts can propagate invalid input with `?`.
+#[rune::function]
+#[allow(clippy::result_large_err)]
+pub fn parse_retry_interval(s: &str) -> Result<Vec<u64>, DbError> {
+    parse_retry_interval_inner(s).map_err(|e| DbError::new(DbErrorKind::Error(e)))
+}
+
 /// Reads a file into a string.
 #[rune::function]
 pub fn read_to_string(filename: &str) -> io::Result<String> {
@@ -266,6 +1024,67 @@ pub fn read_lines(filename: &str) -> io::Result<Vec<String>> {
     Ok(result)
 }
 
+fn read_lines_parallel_inner(filename: &str, num_threads: usize) -> io::Result<Vec<String>> {
+    if num_threads == 0 {
+        return Err(io::Error::new(
+            ErrorKind::InvalidInput,
+            "read_lines_parallel: number of threads must be positive",
+        ));
+    }
+    let file = File::open(filename)
+        .map_err(|e| io::Error::new(e.kind(), format!("Failed to open file {filename}: {e}")))?;
+    if file.metadata()?.len() == 0 {
+        return Ok(Vec::new());
+    }
+    // SAFETY: the file is only read, and fixture files are not expected to change while loading.
+    let mmap = unsafe { memmap2::Mmap::map(&file)? };
+    let data = &mmap[..];
+
+    // Split the data into chunks ending right after a newline, so no line spans two chunks
+    let mut bounds = vec![0];
+    for t in 1..num_threads {
+        let start = (data.len() * t / num_threads).max(*bounds.last().unwrap());
+        let end = data[start..]
+            .iter()
+            .position(|&b| b == b'\n')
+            .map_or(data.len(), |pos| start + pos + 1);
+        if end < data.len() && end > *bounds.last().unwrap() {
+            bounds.push(end);
+        }
+    }
+    bounds.push(data.len());
+
+    let pool = rayon::ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .build()
+        .map_err(io::Error::other)?;
+    let chunks: Vec<Vec<String>> = pool.install(|| {
+        bounds
+            .par_windows(2)
+            .map(|w| {
+                let chunk = &data[w[0]..w[1]];
+                let chunk = chunk.strip_suffix(b"\n").unwrap_or(chunk);
+                chunk
+                    .split(|&b| b == b'\n')
+                    .map(|line| {
+                        let line = line.strip_suffix(b"\r").unwrap_or(line);
+                        String::from_utf8(line.to_vec())
+                            .map_err(|e| io::Error::new(ErrorKind::InvalidData, e))
+                    })
+                    .collect::<io::Result<Vec<String>>>()
+            })
+            .collect::<io::Result<_>>()
+    })?;
+    Ok(chunks.into_iter().flatten().collect())
+}
+
+/// Reads a file into a vector of lines, splitting the work between `num_threads` threads.
+/// Returns the same lines as `read_lines`, but is faster for large files.
+#[rune::function]
+pub fn read_lines_parallel(filename: &str, num_threads: usize) -> io::Result<Vec<String>> {
+    read_lines_parallel_inner(filename, num_threads)
+}
+
 /// Reads a file into a vector of words.
 #[rune::function]
 pub fn read_words(filename: &str) -> io::Result<Vec<String>> {
@@ -294,6 +1113,70 @@ fn read_resource_to_string_inner(path: &str) -> io::Result<String> {
     Ok(contents.to_string())
 }
 
+fn file_exists_inner(filename: &str) -> bool {
+    std::path::Path::new(filename).exists()
+}
+
+/// Checks whether a file or directory exists at the given path.
+#[rune::function]
+pub fn file_exists(filename: &str) -> bool {
+    file_exists_inner(filename)
+}
+
+fn file_size_inner(filename: &str) -> io::Result<u64> {
+    std::fs::metadata(filename).map(|m| m.len()).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("Failed to read metadata of file {filename}: {e}"),
+        )
+    })
+}
+
+/// Returns the size of a file in bytes.
+#[rune::function]
+pub fn file_size(filename: &str) -> io::Result<u64> {
+    file_size_inner(filename)
+}
+
+/// Serializes file writes of all workers, so their contents never interleave.
+static FILE_WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+fn write_to_file_inner(filename: &str, content: &str) -> io::Result<()> {
+    let _guard = FILE_WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    // Write to a sibling file first and rename it, so readers never see a partially written file.
+    let tmp_filename = format!("{filename}.{}.tmp", std::process::id());
+    std::fs::write(&tmp_filename, content)
+        .and_then(|_| std::fs::rename(&tmp_filename, filename))
+        .map_err(|e| io::Error::new(e.kind(), format!("Failed to write file {filename}: {e}")))
+}
+
+fn append_to_file_inner(filename: &str, content: &str) -> io::Result<()> {
+    let _guard = FILE_WRITE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    std::fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(filename)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("Failed to append to file {filename}: {e}"),
+            )
+        })
+}
+
+/// Replaces the contents of a file with the given string, creating the file if needed.
+#[rune::function]
+pub fn write_to_file(filename: &str, content: &str) -> io::Result<()> {
+    write_to_file_inner(filename, content)
+}
+
+/// Appends the given string to a file, creating the file if needed.
+#[rune::function]
+pub fn append_to_file(filename: &str, content: &str) -> io::Result<()> {
+    append_to_file_inner(filename, content)
+}
+
 #[rune::function]
 pub fn read_resource_to_string(path: &str) -> io::Result<String> {
     read_resource_to_string_inner(path)
@@ -326,6 +1209,67 @@ pub fn elapsed_secs(ctx: &Context) -> f64 {
     ctx.start_time.try_lock().unwrap().elapsed().as_secs_f64()
 }
 
+/// Discards the request stats collected so far by this worker and starts a new
+/// measurement window, e.g. at the end of a warmup phase.
+/// The samples taken before the current one are dropped from the final stats.
+#[rune::function(instance)]
+pub fn begin_measurement(ctx: &Context) {
+    ctx.stats.try_lock().unwrap().begin_measurement();
+}
+
+/// Ends the measurement window: requests of this worker completed afterwards are not
+/// included in the stats, until `begin_measurement` is called again.
+/// The samples taken after the current one are dropped from the final stats.
+#[rune::function(instance)]
+pub fn end_measurement(ctx: &Context) {
+    ctx.stats.try_lock().unwrap().end_measurement();
+}
+
+/// Discards the request stats collected so far by this worker.
+/// The samples taken before the current one are dropped from the final stats.
+#[rune::function(instance)]
+pub fn discard_stats(ctx: &Context) {
+    ctx.stats.try_lock().unwrap().discard();
+}
+
+/// Returns the value stored under the key by `shared_write` in any worker, or `None`.
+#[rune::function(instance)]
+pub fn shared_read(ctx: &Context, key: &str) -> Option<String> {
+    ctx.shared_state.read(key)
+}
+
+/// Stores a value visible to all workers, replacing the previous value under the key.
+#[rune::function(instance)]
+pub fn shared_write(ctx: &Context, key: &str, value: &str) {
+    ctx.shared_state.write(key, value);
+}
+
+/// Returns how long to wait before issuing the operation with the given 0-based index,
+/// so that operations are issued at `ops_per_sec` counting from the start.
+fn pacing_delay(elapsed: Duration, op_index: u64, ops_per_sec: f64) -> Duration {
+    Duration::from_secs_f64(op_index as f64 / ops_per_sec).saturating_sub(elapsed)
+}
+
+/// Sleeps just enough to issue the operations of this context at the target rate.
+/// Each call schedules one operation at its due time computed from the start time,
+/// so delays in previous operations are caught up rather than accumulated.
+/// The rate applies to every worker context separately.
+#[rune::function(instance)]
+pub async fn rate_limit(ctx: Ref<Context>, target_ops_per_sec: f64) -> VmResult<()> {
+    if !(target_ops_per_sec > 0.0 && target_ops_per_sec.is_finite()) {
+        return VmResult::panic(format!(
+            "rate_limit: target rate must be a positive number, got {target_ops_per_sec}"
+        ));
+    }
+    let op_index = ctx.paced_op_count.fetch_add(1, Ordering::Relaxed);
+    let elapsed = ctx.start_time.try_lock().unwrap().elapsed();
+    let delay = pacing_delay(elapsed, op_index, target_ops_per_sec);
+    if !delay.is_zero() {
+        tokio::time::sleep(delay).await;
+    }
+    VmResult::Ok(())
+}
+
 /// Rejects calls that write run-level report state from a worker-cloned
 /// context: workers operate on per-thread copies that are never merged back,
 /// so such writes would be silently lost.
@@ -428,6 +1372,29 @@ mod test {
         assert!(worker.shallow_clone().is_worker_clone);
     }
 
+    #[test]
+    fn shared_state_visible_to_all_workers() {
+        let original = test_context();
+        original.shared_state.write("phase", "setup");
+
+        let worker1 = original.clone().unwrap();
+        let worker2 = original.clone().unwrap();
+        assert_eq!(
+            worker2.shared_state.read("phase"),
+            Some("setup".to_string())
+        );
+
+        worker1.shared_state.write("phase", "run");
+        worker1.shared_state.write("leader", "1");
+        assert_eq!(worker2.shared_state.read("phase"), Some("run".to_string()));
+        assert_eq!(
+            worker2.shallow_clone().shared_state.read("leader"),
+            Some("1".to_string())
+        );
+        assert_eq!(original.shared_state.read("leader"), Some("1".to_string()));
+        assert_eq!(worker2.shared_state.read("missing"), None);
+    }
+
     #[test]
     fn report_state_writes_rejected_in_worker_clone() {
         let original = test_context();
@@ -447,4 +1414,776 @@ mod test {
         let worker = original.clone().unwrap();
         assert!(reject_in_setup(&worker, "record_metric").is_ok());
     }
+
+    fn to_i64_nested(values: Vec<Value>) -> Vec<Vec<i64>> {
+        values
+            .iter()
+            .map(|v| {
+                v.borrow_ref::<rune::runtime::Vec>()
+                    .unwrap()
+                    .iter()
+                    .map(|x| x.as_signed().unwrap())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn zip_pairs_elements() {
+        let a = vec![Value::from(1i64), Value::from(2i64)];
+        let b = vec![Value::from(3i64), Value::from(4i64)];
+        let result = zip_inner(a, b).into_result().unwrap();
+        assert_eq!(to_i64_nested(result), vec![vec![1, 3], vec![2, 4]]);
+    }
+
+    #[test]
+    fn zip_rejects_length_mismatch() {
+        let a = vec![Value::from(1i64), Value::from(2i64)];
+        let b = vec![Value::from(3i64)];
+        assert!(zip_inner(a, b).into_result().is_err());
+    }
+
+    #[test]
+    fn enumerate_prepends_indexes() {
+        let a = vec![Value::from(10i64), Value::from(20i64)];
+        let result = enumerate_inner(a).into_result().unwrap();
+        assert_eq!(to_i64_nested(result), vec![vec![0, 10], vec![1, 20]]);
+    }
+
+    #[test]
+    fn hash_bool_follows_probability() {
+        let n = 10000;
+        let count = |p: f64| {
+            (0..n)
+                .filter(|&i| hash_bool_inner(i, p).into_result().unwrap())
+                .count()
+        };
+        assert_eq!(count(0.0), 0);
+        assert_eq!(count(1.0), n as usize);
+        let true_count = count(0.3);
+        assert!((2800..3200).contains(&true_count), "got {true_count}");
+    }
+
+    #[test]
+    fn hash_bool_is_deterministic() {
+        for i in 0..100 {
+            assert_eq!(
+                hash_bool_inner(i, 0.5).into_result().unwrap(),
+                hash_bool_inner(i, 0.5).into_result().unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn hash_bool_rejects_invalid_probability() {
+        assert!(hash_bool_inner(1, -0.1).into_result().is_err());
+        assert!(hash_bool_inner(1, 1.1).into_result().is_err());
+        assert!(hash_bool_inner(1, f64::NAN).into_result().is_err());
+    }
+
+    #[test]
+    fn hash_range_between_stays_in_range() {
+        for i in 0..1000 {
+            assert_eq!(
+                hash_range_between_inner(i, 0, 100).into_result().unwrap(),
+                hash_inner(i) % 100
+            );
+            let v = hash_range_between_inner(i, -50, 50).into_result().unwrap();
+            assert!((-50..50).contains(&v));
+            let v = hash_range_between_inner(i, i64::MIN, i64::MAX)
+                .into_result()
+                .unwrap();
+            assert!(v < i64::MAX);
+        }
+        assert!((0..100).any(|i| hash_range_between_inner(i, -50, 50).into_result().unwrap() < 0));
+        assert!(hash_range_between_inner(1, 0, 0).into_result().is_err());
+        assert!(hash_range_between_inner(1, 0, -5).into_result().is_err());
+        assert!(hash_range_between_inner(1, 5, 4).into_result().is_err());
+    }
+
+    #[test]
+    fn hash_consistent_remaps_few_values() {
+        let buckets = |n| {
+            (0..10000)
+                .map(|i| hash_consistent_inner(i, n).into_result().unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert!(buckets(1).iter().all(|&b| b == 0));
+        let ten = buckets(10);
+        assert!(ten.iter().all(|b| (0..10).contains(b)));
+        let eleven = buckets(11);
+        let moved = ten.iter().zip(&eleven).filter(|(a, b)| a != b).count();
+        // About 1/11 of the values should move, all of them to the new bucket
+        assert!((700..1100).contains(&moved), "moved = {moved}");
+        assert!(ten.iter().zip(&eleven).all(|(&a, &b)| a == b || b == 10));
+        assert!(hash_consistent_inner(1, 0).into_result().is_err());
+    }
+
+    #[test]
+    fn hash_category_follows_weights() {
+        let labels = i64_values(&[1, 2, 3]);
+        let weights = vec![Value::from(0.7), Value::from(0i64), Value::from(3i64)];
+        let mut counts = [0; 3];
+        for i in 0..10000 {
+            let label = hash_category_inner(i, &labels, &weights)
+                .into_result()
+                .unwrap();
+            counts[label.as_signed().unwrap() as usize - 1] += 1;
+        }
+        assert_eq!(counts[1], 0);
+        assert!((1700..2100).contains(&counts[0]), "got {counts:?}");
+        assert_eq!(counts[0] + counts[2], 10000);
+    }
+
+    #[test]
+    fn hash_category_rejects_invalid_weights() {
+        let labels = i64_values(&[1, 2]);
+        assert!(hash_category_inner(1, &labels, &i64_values(&[1]))
+            .into_result()
+            .is_err());
+        assert!(hash_category_inner(1, &labels, &i64_values(&[0, 0]))
+            .into_result()
+            .is_err());
+        assert!(hash_category_inner(1, &labels, &i64_values(&[1, -1]))
+            .into_result()
+            .is_err());
+    }
+
+    fn to_i64_vec(values: &[Value]) -> Vec<i64> {
+        values.iter().map(|v| v.as_signed().unwrap()).collect()
+    }
+
+    fn i64_values(values: &[i64]) -> Vec<Value> {
+        values.iter().map(|&i| Value::from(i)).collect()
+    }
+
+    #[test]
+    fn sample_without_replacement_selects_distinct_items() {
+        let input: Vec<i64> = (0..50).collect();
+        let sample = |seed, k| -> Vec<i64> {
+            sample_without_replacement_inner(seed, k, &i64_values(&input))
+                .into_result()
+                .unwrap()
+                .iter()
+                .map(|v| v.as_integer::<i64>().unwrap())
+                .collect()
+        };
+        for seed in 0..100 {
+            for k in [0, 1, 10, 50] {
+                let selected = sample(seed, k);
+                assert_eq!(selected.len(), k);
+                assert_eq!(selected.iter().collect::<HashSet<_>>().len(), k);
+                assert!(selected.iter().all(|v| input.contains(v)));
+                assert_eq!(selected, sample(seed, k));
+            }
+        }
+        assert_ne!(sample(1, 10), sample(2, 10));
+        assert!(sample_without_replacement_inner(1, 51, &i64_values(&input))
+            .into_result()
+            .is_err());
+    }
+
+    /// Returns a path in the temp directory unique to the test and the test process,
+    /// so concurrent test runs don't overwrite each other's files.
+    fn temp_file_path(test_name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("latte_{test_name}_{}.txt", std::process::id()))
+    }
+
+    #[test]
+    fn read_lines_parallel_returns_same_lines_as_read_lines() {
+        let path = temp_file_path("read_lines_parallel_test");
+        let filename = path.to_str().unwrap();
+        let read_lines = |filename: &str| -> Vec<String> {
+            BufReader::new(File::open(filename).unwrap())
+                .lines()
+                .map(|l| l.unwrap())
+                .collect()
+        };
+        let long_lines: String = (0..1000).map(|i| format!("line {i}\n")).collect();
+        for contents in [
+            "",
+            "\n",
+            "single",
+            "a\nb\n",
+            "a\r\n\n\nb\r\nlast without newline",
+            "zażółć\ngęślą\njaźń\n",
+            long_lines.as_str(),
+        ] {
+            std::fs::write(&path, contents).unwrap();
+            for num_threads in [1, 2, 3, 8, 100] {
+                assert_eq!(
+                    read_lines_parallel_inner(filename, num_threads).unwrap(),
+                    read_lines(filename),
+                    "contents: {contents:?}, threads: {num_threads}"
+                );
+            }
+        }
+        assert!(read_lines_parallel_inner(filename, 0).is_err());
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(read_lines_parallel_inner(filename, 2).is_err());
+    }
+
+    #[test]
+    fn file_exists_and_file_size_report_file_metadata() {
+        let path = temp_file_path("file_size_test");
+        let filename = path.to_str().unwrap();
+        std::fs::write(&path, "0123456789").unwrap();
+
+        assert!(file_exists_inner(filename));
+        assert_eq!(file_size_inner(filename).unwrap(), 10);
+        std::fs::write(&path, "").unwrap();
+        assert_eq!(file_size_inner(filename).unwrap(), 0);
+
+        std::fs::remove_file(&path).unwrap();
+        assert!(!file_exists_inner(filename));
+        assert_eq!(
+            file_size_inner(filename).unwrap_err().kind(),
+            ErrorKind::NotFound
+        );
+    }
+
+    #[test]
+    fn write_to_file_truncates_and_append_to_file_accumulates() {
+        let path = temp_file_path("write_to_file_test");
+        let filename = path.to_str().unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        append_to_file_inner(filename, "a\n").unwrap();
+        append_to_file_inner(filename, "b\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "a\nb\n");
+        write_to_file_inner(filename, "c\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "c\n");
+        append_to_file_inner(filename, "d\n").unwrap();
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "c\nd\n");
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn append_to_file_does_not_interleave_concurrent_writes() {
+        let path = temp_file_path("append_to_file_test");
+        let filename = path.to_str().unwrap().to_string();
+        let _ = std::fs::remove_file(&path);
+
+        let line = "x".repeat(1000) + "\n";
+        let threads: Vec<_> = (0..8)
+            .map(|_| {
+                let (filename, line) = (filename.clone(), line.clone());
+                std::thread::spawn(move || {
+                    for _ in 0..50 {
+                        append_to_file_inner(&filename, &line).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for t in threads {
+            t.join().unwrap();
+        }
+        let contents = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(contents.lines().count(), 400);
+        assert!(contents.lines().all(|l| l.len() == 1000));
+
+        std::fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn random_inet_generates_deterministic_addresses() {
+        for seed in 0..100 {
+            let v4 = random_inet_inner(seed, false);
+            assert!(v4.parse::<Ipv4Addr>().is_ok(), "{v4}");
+            assert_eq!(v4, random_inet_inner(seed, false));
+            let v6 = random_inet_inner(seed, true);
+            assert!(v6.parse::<Ipv6Addr>().is_ok(), "{v6}");
+            assert_eq!(v6, random_inet_inner(seed, true));
+        }
+        assert_ne!(random_inet_inner(1, false), random_inet_inner(2, false));
+    }
+
+    #[test]
+    fn base64_encodes_and_decodes_bytes() {
+        let bytes: Vec<Value> = b"latte\x00\xff"
+            .iter()
+            .map(|&b| Value::from(b as i64))
+            .collect();
+        let encoded = base64_encode_inner(&bytes).into_result().unwrap();
+        assert_eq!(encoded, "bGF0dGUA/w==");
+        let decoded: Vec<i64> = base64_decode_inner(&encoded)
+            .into_result()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_integer::<i64>().unwrap())
+            .collect();
+        assert_eq!(decoded, [108, 97, 116, 116, 101, 0, 255]);
+
+        assert_eq!(base64_encode_inner(&[]).into_result().unwrap(), "");
+        assert!(base64_decode_inner("not base64!").into_result().is_err());
+        assert!(base64_encode_inner(&[Value::from(256)])
+            .into_result()
+            .is_err());
+    }
+
+    #[test]
+    fn hex_encodes_and_decodes_bytes() {
+        let as_i64 = |values: Vec<Value>| -> Vec<i64> {
+            values
+                .iter()
+                .map(|v| v.as_integer::<i64>().unwrap())
+                .collect()
+        };
+        let bytes: Vec<Value> = [0x00, 0x1f, 0xab, 0xff]
+            .iter()
+            .map(|&b| Value::from(b as i64))
+            .collect();
+        assert_eq!(hex_encode_inner(&bytes).into_result().unwrap(), "001fabff");
+        assert_eq!(
+            as_i64(hex_decode_inner("001fabff").into_result().unwrap()),
+            [0x00, 0x1f, 0xab, 0xff]
+        );
+        assert_eq!(
+            as_i64(hex_decode_inner("DEADbeef").into_result().unwrap()),
+            [0xde, 0xad, 0xbe, 0xef]
+        );
+        assert!(hex_decode_inner("").into_result().unwrap().is_empty());
+        assert!(hex_decode_inner("abc").into_result().is_err());
+        assert!(hex_decode_inner("zz").into_result().is_err());
+        assert!(hex_encode_inner(&[Value::from(-1)]).into_result().is_err());
+    }
+
+    #[test]
+    fn sha_hashes_match_known_digests() {
+        let abc: Vec<Value> = b"abc".iter().map(|&b| Value::from(b as i64)).collect();
+        let sha256 = |data: &[Value]| hex::encode(sha256_hash_inner(data).into_result().unwrap());
+        let sha512 = |data: &[Value]| hex::encode(sha512_hash_inner(data).into_result().unwrap());
+        assert_eq!(
+            sha256(&[]),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(
+            sha256(&abc),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(
+            sha512(&[]),
+            "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce\
+             47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"
+        );
+        assert_eq!(
+            sha512(&abc),
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a\
+             2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+        );
+        assert_eq!(sha256_hash_inner(&abc).into_result().unwrap().len(), 32);
+        assert_eq!(sha512_hash_inner(&abc).into_result().unwrap().len(), 64);
+        assert!(sha256_hash_inner(&[Value::from(256)])
+            .into_result()
+            .is_err());
+    }
+
+    #[test]
+    fn hmac_sha256_matches_rfc_4231_vectors() {
+        let to_values =
+            |bytes: &[u8]| -> Vec<Value> { bytes.iter().map(|&b| Value::from(b as i64)).collect() };
+        let hmac = |key: &[u8], data: &[u8]| {
+            hex::encode(
+                hmac_sha256_inner(&to_values(key), &to_values(data))
+                    .into_result()
+                    .unwrap(),
+            )
+        };
+        // Test case 1
+        assert_eq!(
+            hmac(&[0x0b; 20], b"Hi There"),
+            "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7"
+        );
+        // Test case 2
+        assert_eq!(
+            hmac(b"Jefe", b"what do ya want for nothing?"),
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843"
+        );
+        // Test case 6, key longer than the block size
+        assert_eq!(
+            hmac(
+                &[0xaa; 131],
+                b"Test Using Larger Than Block-Size Key - Hash Key First"
+            ),
+            "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54"
+        );
+        assert!(hmac_sha256_inner(&[], &to_values(b"data"))
+            .into_result()
+            .is_err());
+    }
+
+    #[test]
+    fn gzip_round_trips() {
+        let to_values =
+            |bytes: &[u8]| -> Vec<Value> { bytes.iter().map(|&b| Value::from(b as i64)).collect() };
+        let mut rng = SmallRng::seed_from_u64(42);
+        let blob: Vec<u8> = (0..10_000)
+            .map(|i| if i % 100 < 50 { rng.random() } else { b'x' })
+            .collect();
+        let compressed = gzip_compress_inner(&to_values(&blob))
+            .into_result()
+            .unwrap();
+        assert!(compressed.len() < blob.len());
+        let decompressed = gzip_decompress_inner(&to_values(&compressed))
+            .into_result()
+            .unwrap();
+        assert_eq!(decompressed, blob);
+
+        assert_eq!(
+            gzip_decompress_inner(&to_values(&gzip_compress_inner(&[]).into_result().unwrap()))
+                .into_result()
+                .unwrap(),
+            Vec::<u8>::new()
+        );
+        let garbage: Vec<u8> = (0..100).map(|_| rng.random()).collect();
+        assert!(gzip_decompress_inner(&to_values(&garbage))
+            .into_result()
+            .is_err());
+        assert!(gzip_compress_inner(&[Value::from(256)])
+            .into_result()
+            .is_err());
+    }
+
+    #[test]
+    fn compress_blob_round_trips() {
+        let to_values =
+            |bytes: &[u8]| -> Vec<Value> { bytes.iter().map(|&b| Value::from(b as i64)).collect() };
+        let mut rng = SmallRng::seed_from_u64(42);
+        // Repeated fragments make the blob compressible, like most real data
+        let blob: Vec<u8> = (0..10_000)
+            .map(|i| if i % 100 < 50 { rng.random() } else { b'x' })
+            .collect();
+        let compressed = compress_blob_inner(&to_values(&blob))
+            .into_result()
+            .unwrap();
+        assert!(compressed.len() < blob.len());
+        let decompressed = decompress_blob_inner(&to_values(&compressed))
+            .into_result()
+            .unwrap();
+        assert_eq!(decompressed, blob);
+
+        let twice = compress_blob_inner(&to_values(&compressed))
+            .into_result()
+            .unwrap();
+        let once = decompress_blob_inner(&to_values(&twice))
+            .into_result()
+            .unwrap();
+        assert_eq!(once, compressed);
+
+        assert_eq!(
+            decompress_blob_inner(&to_values(&compress_blob_inner(&[]).into_result().unwrap()))
+                .into_result()
+                .unwrap(),
+            Vec::<u8>::new()
+        );
+        assert!(decompress_blob_inner(&to_values(&[1, 2, 3]))
+            .into_result()
+            .is_err());
+        assert!(compress_blob_inner(&[Value::from(256)])
+            .into_result()
+            .is_err());
+        assert!(compress_blob_inner(&[Value::from(-1)])
+            .into_result()
+            .is_err());
+    }
+
+    #[test]
+    fn shuffle_permutes_deterministically() {
+        let as_i64 = |values: Vec<Value>| -> Vec<i64> {
+            values
+                .iter()
+                .map(|v| v.as_integer::<i64>().unwrap())
+                .collect()
+        };
+        let input: Vec<i64> = (0..20).collect();
+        let mut changed = 0;
+        for seed in 0..100 {
+            let shuffled = as_i64(shuffle_inner(seed, i64_values(&input)));
+            assert_eq!(shuffled, as_i64(shuffle_inner(seed, i64_values(&input))));
+            let mut sorted = shuffled.clone();
+            sorted.sort();
+            assert_eq!(sorted, input);
+            changed += (shuffled != input) as usize;
+        }
+        assert!(changed > 95, "changed = {changed}");
+        assert_eq!(as_i64(shuffle_inner(1, i64_values(&[7]))), vec![7]);
+    }
+
+    #[test]
+    fn chunk_splits_into_groups() {
+        let result = chunk_inner(i64_values(&[1, 2, 3, 4, 5]), 2)
+            .into_result()
+            .unwrap();
+        assert_eq!(to_i64_nested(result), vec![vec![1, 2], vec![3, 4], vec![5]]);
+    }
+
+    #[test]
+    fn chunk_rejects_zero_size() {
+        assert!(chunk_inner(i64_values(&[1, 2]), 0).into_result().is_err());
+    }
+
+    #[test]
+    fn flatten_removes_one_level_of_nesting() {
+        let nested = vec![
+            i64_values(&[1, 2]).to_value().unwrap(),
+            i64_values(&[3]).to_value().unwrap(),
+        ];
+        let result = flatten_inner(nested).into_result().unwrap();
+        assert_eq!(to_i64_vec(&result), vec![1, 2, 3]);
+    }
+
+    fn test_object(entries: &[(&str, i64)]) -> Object {
+        let mut obj = Object::new();
+        for (k, v) in entries {
+            obj.insert(rune::alloc::String::try_from(*k).unwrap(), Value::from(*v))
+                .unwrap();
+        }
+        obj
+    }
+
+    #[test]
+    fn merge_objects_merges_nested_objects() {
+        let mut base = test_object(&[("b", 3)]);
+        let base_a = test_object(&[("x", 1), ("y", 2)]);
+        base.insert("a".try_into().unwrap(), base_a.to_value().unwrap())
+            .unwrap();
+        let mut override_ = test_object(&[("c", 4)]);
+        let override_a = test_object(&[("y", 99)]);
+        override_
+            .insert("a".try_into().unwrap(), override_a.to_value().unwrap())
+            .unwrap();
+
+        let result = merge_objects_inner(base, override_).into_result().unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result.get("b").unwrap().as_signed().unwrap(), 3);
+        assert_eq!(result.get("c").unwrap().as_signed().unwrap(), 4);
+        let a = result.get("a").unwrap().borrow_ref::<Object>().unwrap();
+        assert_eq!(a.len(), 2);
+        assert_eq!(a.get("x").unwrap().as_signed().unwrap(), 1);
+        assert_eq!(a.get("y").unwrap().as_signed().unwrap(), 99);
+    }
+
+    #[test]
+    fn object_get_path_traverses_nested_objects() {
+        let mut b = Object::new();
+        b.insert(
+            "b".try_into().unwrap(),
+            test_object(&[("c", 42)]).to_value().unwrap(),
+        )
+        .unwrap();
+        let mut a = Object::new();
+        a.insert("a".try_into().unwrap(), b.to_value().unwrap())
+            .unwrap();
+        let obj = a.to_value().unwrap();
+
+        let found = object_get_path_inner(obj.clone(), "a.b.c")
+            .into_result()
+            .unwrap();
+        assert_eq!(found.as_signed().unwrap(), 42);
+        let missing = object_get_path_inner(obj, "a.x.c").into_result().unwrap();
+        assert!(missing.borrow_ref::<Option<Value>>().unwrap().is_none());
+    }
+
+    #[test]
+    fn uniform_int_stays_in_range() {
+        for i in 0..1000 {
+            let v = uniform_int_inner(i, -5, 5).into_result().unwrap();
+            assert!((-5..5).contains(&v));
+        }
+        assert_eq!(
+            uniform_int_inner(42, 0, 1000).into_result().unwrap(),
+            uniform_int_inner(42, 0, 1000).into_result().unwrap()
+        );
+        assert!(uniform_int_inner(0, 5, 5).into_result().is_err());
+    }
+
+    #[test]
+    fn lognormal_mean_is_close_to_expected() {
+        let n = 100000;
+        let samples: Vec<f64> = (0..n)
+            .map(|i| lognormal_inner(i, 1.0, 0.5).into_result().unwrap())
+            .collect();
+        assert!(samples.iter().all(|&x| x > 0.0));
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        let expected = (1.0f64 + 0.5 * 0.5 / 2.0).exp();
+        assert!((mean - expected).abs() / expected < 0.02, "mean = {mean}");
+        assert!(lognormal_inner(0, 1.0, 0.0).into_result().is_err());
+        assert!(lognormal_inner(0, 1.0, -1.0).into_result().is_err());
+    }
+
+    #[test]
+    fn beta_with_unit_parameters_is_uniform() {
+        let n = 10000;
+        let samples: Vec<f64> = (0..n)
+            .map(|i| beta_inner(i, 1.0, 1.0).into_result().unwrap())
+            .collect();
+        assert!(samples.iter().all(|x| (0.0..=1.0).contains(x)));
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        let below_quarter = samples.iter().filter(|&&x| x < 0.25).count() as f64 / n as f64;
+        assert!((mean - 0.5).abs() < 0.02, "mean = {mean}");
+        assert!(
+            (below_quarter - 0.25).abs() < 0.02,
+            "below_quarter = {below_quarter}"
+        );
+        assert!((0..1000)
+            .all(|i| (0.0..=1.0).contains(&beta_inner(i, 0.5, 5.0).into_result().unwrap())));
+        assert!(beta_inner(0, 0.0, 1.0).into_result().is_err());
+        assert!(beta_inner(0, 1.0, -1.0).into_result().is_err());
+    }
+
+    #[test]
+    fn geometric_mean_is_close_to_expected() {
+        let n = 10000;
+        let sum: i64 = (0..n)
+            .map(|i| geometric_inner(i, 0.25).into_result().unwrap())
+            .sum();
+        let mean = sum as f64 / n as f64;
+        // Number of trials until the first success has mean 1/p; failures are one fewer
+        assert!((mean + 1.0 - 4.0).abs() < 0.15, "mean = {mean}");
+        assert!((0..100).all(|i| geometric_inner(i, 1.0).into_result().unwrap() == 0));
+        assert!(geometric_inner(0, 0.0).into_result().is_err());
+        assert!(geometric_inner(0, 1.1).into_result().is_err());
+        assert!(geometric_inner(0, f64::NAN).into_result().is_err());
+    }
+
+    #[test]
+    fn poisson_mean_and_variance_are_close_to_lambda() {
+        let n = 10000;
+        let samples: Vec<f64> = (0..n)
+            .map(|i| poisson_inner(i, 4.0).into_result().unwrap() as f64)
+            .collect();
+        let mean = samples.iter().sum::<f64>() / n as f64;
+        let variance = samples.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+        assert!((mean - 4.0).abs() < 0.1, "mean = {mean}");
+        assert!((variance - 4.0).abs() < 0.3, "variance = {variance}");
+        assert!(poisson_inner(0, 0.0).into_result().is_err());
+        assert!(poisson_inner(0, -1.0).into_result().is_err());
+    }
+
+    #[test]
+    fn vector_reports_failing_index() {
+        let generator = Function::new(|i: usize| -> VmResult<usize> {
+            if i == 3 {
+                VmResult::panic("boom")
+            } else {
+                VmResult::Ok(i)
+            }
+        });
+        let err = vector_inner(5, generator).into_result().unwrap_err();
+        assert!(err.to_string().contains("index 3"), "{err}");
+    }
+
+    #[test]
+    fn map_gen_builds_object_with_last_write_wins() {
+        let keys = Function::new(|i: usize| format!("k{}", i % 3));
+        let values = Function::new(|i: usize| i as i64);
+        let result = map_gen_inner(5, keys, values).into_result().unwrap();
+        assert_eq!(result.len(), 3);
+        assert_eq!(result.get("k0").unwrap().as_signed().unwrap(), 3);
+        assert_eq!(result.get("k1").unwrap().as_signed().unwrap(), 4);
+        assert_eq!(result.get("k2").unwrap().as_signed().unwrap(), 2);
+    }
+
+    #[test]
+    fn set_gen_returns_sorted_distinct_values() {
+        for seed in 0..100 {
+            let result = set_gen_inner(seed, 10, -5, 10).into_result().unwrap();
+            assert_eq!(result.len(), 10);
+            assert!(result.windows(2).all(|w| w[0] < w[1]));
+            assert!(result.iter().all(|v| (-5..10).contains(v)));
+        }
+        let full = set_gen_inner(1, 4, 0, 4).into_result().unwrap();
+        assert_eq!(full, vec![0, 1, 2, 3]);
+        assert_eq!(
+            set_gen_inner(7, 5, i64::MIN, i64::MAX)
+                .into_result()
+                .unwrap(),
+            set_gen_inner(7, 5, i64::MIN, i64::MAX)
+                .into_result()
+                .unwrap()
+        );
+        assert!(set_gen_inner(1, 5, 0, 4).into_result().is_err());
+    }
+
+    #[test]
+    fn stopwatch_measures_elapsed_time() {
+        let mut sw = StopWatch {
+            start: Instant::now(),
+        };
+        std::thread::sleep(Duration::from_millis(10));
+        assert!(sw.elapsed_ms() >= 10.0);
+        sw.start = Instant::now();
+        assert!(sw.elapsed_ms() < 10.0);
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn sleep_until_timestamp_waits_only_for_future_timestamps() {
+        let start = tokio::time::Instant::now();
+        sleep_until_timestamp_inner(Utc::now().timestamp_millis() - 1000).await;
+        assert_eq!(start.elapsed(), Duration::ZERO);
+        sleep_until_timestamp_inner(Utc::now().timestamp_millis() + 5000).await;
+        assert!(start.elapsed() >= Duration::from_millis(4900));
+    }
+
+    #[test]
+    fn pacing_delay_self_corrects_for_drift() {
+        let ms = Duration::from_millis;
+        // 100 ops/s: the op with index 5 is due 50ms after the start
+        assert_eq!(pacing_delay(ms(20), 5, 100.0), ms(30));
+        // running late: no delay, so the rate catches up
+        assert_eq!(pacing_delay(ms(80), 5, 100.0), Duration::ZERO);
+        assert_eq!(pacing_delay(ms(0), 0, 100.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn parse_retry_interval_returns_millis() {
+        assert_eq!(parse_retry_interval_inner("2s").unwrap(), vec![2000, 2000]);
+        assert_eq!(
+            parse_retry_interval_inner("200ms,5s").unwrap(),
+            vec![200, 5000]
+        );
+        assert!(parse_retry_interval_inner("").is_err());
+        assert!(parse_retry_interval_inner("1s,x").is_err());
+    }
+
+    #[test]
+    fn validation_callback_verdict_is_returned() {
+        let rows = i64_values(&[1, 2, 3]).to_value().unwrap();
+        let non_empty = Function::new(|rows: Vec<Value>| !rows.is_empty())
+            .into_sync()
+            .unwrap();
+        assert_eq!(
+            call_validation_callback(Some(&non_empty), "non_empty", rows.clone()),
+            Ok(true)
+        );
+        let always_fails = Function::new(|_: Value| false).into_sync().unwrap();
+        assert_eq!(
+            call_validation_callback(Some(&always_fails), "always_fails", rows.clone()),
+            Ok(false)
+        );
+        assert!(call_validation_callback(None, "missing", rows).is_err());
+    }
+
+    #[test]
+    fn map_keys_transforms_keys() {
+        let obj = test_object(&[("a", 1), ("b", 2)]);
+        let upper = Function::new(|k: String| k.to_uppercase());
+        let result = map_keys_inner(obj, upper).into_result().unwrap();
+        assert_eq!(result.len(), 2);
+        assert!(result.keys().all(|k| k.chars().all(|c| c.is_uppercase())));
+        assert_eq!(result.get("A").unwrap().as_signed().unwrap(), 1);
+        assert_eq!(result.get("B").unwrap().as_signed().unwrap(), 2);
+    }
+
+    #[test]
+    fn map_values_transforms_values() {
+        let obj = test_object(&[("a", 1), ("b", 2)]);
+        let double = Function::new(|v: i64| v * 2);
+        let result = map_values_inner(obj, double).into_result().unwrap();
+        let result = result.borrow_ref::<Object>().unwrap();
+        assert_eq!(result.get("a").unwrap().as_signed().unwrap(), 2);
+        assert_eq!(result.get("b").unwrap().as_signed().unwrap(), 4);
+    }
 }
@@ -0,0 +1,103 @@
+use rune::runtime::Mut;
+use rune::Any;
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader, ErrorKind, Seek, SeekFrom};
+
+/// A file opened for random access to its lines.
+/// On open, the file is streamed once to build a table of line start offsets, so any line
+/// can later be read with a single seek, without loading the file contents into memory.
+/// The offset table takes O(number_of_lines) memory (8 bytes per line).
+#[derive(Any, Debug)]
+pub struct IndexedFile {
+    reader: BufReader<File>,
+    offsets: Vec<u64>,
+}
+
+impl IndexedFile {
+    pub fn open(path: &str) -> io::Result<Self> {
+        let file = File::open(path)
+            .map_err(|e| io::Error::new(e.kind(), format!("Failed to open file {path}: {e}")))?;
+        let mut reader = BufReader::new(file);
+        let mut offsets = Vec::new();
+        let mut offset: u64 = 0;
+        let mut buf = Vec::new();
+        loop {
+            buf.clear();
+            let n = reader.read_until(b'\n', &mut buf)?;
+            if n == 0 {
+                break;
+            }
+            offsets.push(offset);
+            offset += n as u64;
+        }
+        Ok(IndexedFile { reader, offsets })
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.offsets.len()
+    }
+
+    /// Reads the line with the given 0-based number, without the trailing line terminator.
+    pub fn line_at(&mut self, n: usize) -> io::Result<String> {
+        let offset = *self.offsets.get(n).ok_or_else(|| {
+            io::Error::new(
+                ErrorKind::InvalidInput,
+                format!(
+                    "Line number {n} out of range, the file has {} lines",
+                    self.offsets.len()
+                ),
+            )
+        })?;
+        self.reader.seek(SeekFrom::Start(offset))?;
+        let mut line = String::new();
+        self.reader.read_line(&mut line)?;
+        if line.ends_with('\n') {
+            line.pop();
+            if line.ends_with('\r') {
+                line.pop();
+            }
+        }
+        Ok(line)
+    }
+}
+
+#[rune::function(instance)]
+pub fn line_at(mut file: Mut<IndexedFile>, n: usize) -> io::Result<String> {
+    file.line_at(n)
+}
+
+#[rune::function(instance)]
+pub fn line_count(file: &IndexedFile) -> usize {
+    file.line_count()
+}
+
+/// Opens a file for random line access by building an index of line offsets.
+#[rune::function]
+pub fn open_indexed_file(filename: &str) -> io::Result<IndexedFile> {
+    IndexedFile::open(filename)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn line_at_reads_lines_in_any_order() {
+        let path = std::env::temp_dir().join("latte_indexed_file_test.txt");
+        let mut file = File::create(&path).unwrap();
+        write!(file, "first\nsecond\r\n\nlast").unwrap();
+        drop(file);
+
+        let mut indexed = IndexedFile::open(path.to_str().unwrap()).unwrap();
+        assert_eq!(indexed.line_count(), 4);
+        assert_eq!(indexed.line_at(3).unwrap(), "last");
+        assert_eq!(indexed.line_at(0).unwrap(), "first");
+        assert_eq!(indexed.line_at(2).unwrap(), "");
+        assert_eq!(indexed.line_at(1).unwrap(), "second");
+        assert!(indexed.line_at(4).is_err());
+
+        std::fs::remove_file(path).unwrap();
+    }
+}
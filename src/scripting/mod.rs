@@ -4,10 +4,12 @@ use std::collections::HashMap;
 
 pub mod cluster_info;
 mod functions_common;
+mod indexed_file;
 pub mod retry_error;
 mod row_distribution;
 pub mod rune_uuid;
 mod split_lines_iter;
+mod stats;
 
 #[cfg(feature = "alternator")]
 mod alternator;
@@ -54,14 +56,25 @@ fn try_install(
     // NOTE: 1st group of query-oriented functions - without usage of prepared statements
     context_module.function_meta(functions::execute)?;
     context_module.function_meta(functions::execute_with_validation)?;
+    context_module.function_meta(functions::execute_count_validation)?;
     context_module.function_meta(functions::execute_with_result)?;
+    context_module.function_meta(functions::execute_unprepared)?;
     // NOTE: 2nd group of query-oriented functions - with usage of prepared statements
     context_module.function_meta(functions::execute_prepared)?;
     context_module.function_meta(functions::execute_prepared_with_validation)?;
+    context_module.function_meta(functions::execute_prepared_with_validation_strategy)?;
     context_module.function_meta(functions::execute_prepared_with_result)?;
+    context_module.function_meta(functions::execute_prepared_verify)?;
+    context_module.function_meta(functions::execute_prepared_traced)?;
+    context_module.function_meta(functions::execute_prepared_with_ttl)?;
+    context_module.function_meta(functions::execute_prepared_with_timestamp)?;
+    context_module.function_meta(functions::compute_token)?;
+    context_module.function_meta(functions::prepared_info)?;
 
     context_module.function_meta(functions::batch_prepared)?;
     context_module.function_meta(functions::get_datacenters)?;
+    context_module.function_meta(functions::get_cluster_info)?;
+    context_module.function_meta(functions::connection_count)?;
 
     let err_module = init_error_module()?;
     let uuid_module = init_uuid_module()?;
@@ -70,14 +83,39 @@ fn try_install(
     latte_module.function_meta(cql_types::i64::to_i32)?;
     latte_module.function_meta(cql_types::i64::to_i16)?;
     latte_module.function_meta(cql_types::i64::to_i8)?;
+    latte_module.function_meta(cql_types::i64::to_i32_checked)?;
+    latte_module.function_meta(cql_types::i64::to_i16_checked)?;
+    latte_module.function_meta(cql_types::i64::to_i8_checked)?;
+    latte_module.function_meta(cql_types::i64::to_i32_saturating)?;
+    latte_module.function_meta(cql_types::i64::to_i16_saturating)?;
+    latte_module.function_meta(cql_types::i64::to_i8_saturating)?;
     latte_module.function_meta(cql_types::i64::to_f32)?;
     latte_module.function_meta(cql_types::i64::clamp)?;
+    latte_module.function_meta(cql_types::i64::clamp_to_range)?;
 
     latte_module.function_meta(cql_types::f64::to_i8)?;
     latte_module.function_meta(cql_types::f64::to_i16)?;
     latte_module.function_meta(cql_types::f64::to_i32)?;
     latte_module.function_meta(cql_types::f64::to_f32)?;
+    latte_module.function_meta(cql_types::f64::to_fixed)?;
     latte_module.function_meta(cql_types::f64::clamp)?;
+    latte_module.function_meta(cql_types::duration)?;
+    latte_module.function_meta(cql_types::varint)?;
+    latte_module.function_meta(cql_types::decimal)?;
+    latte_module.function_meta(cql_types::inet)?;
+    latte_module.function_meta(cql_types::date)?;
+    latte_module.function_meta(cql_types::today)?;
+
+    let mut cql_types_module = Module::default();
+    cql_types_module.ty::<cql_types::Int8>()?;
+    cql_types_module.ty::<cql_types::Int16>()?;
+    cql_types_module.ty::<cql_types::Int32>()?;
+    cql_types_module.ty::<cql_types::Float32>()?;
+    cql_types_module.ty::<cql_types::Duration>()?;
+    cql_types_module.ty::<cql_types::Varint>()?;
+    cql_types_module.ty::<cql_types::Decimal>()?;
+    cql_types_module.ty::<cql_types::Inet>()?;
+    cql_types_module.ty::<cql_types::Date>()?;
 
     let mut fs_module = init_fs_module()?;
     let iter_module = init_iter_module(&mut fs_module)?;
@@ -86,6 +124,7 @@ fn try_install(
     rune_ctx.install(&err_module)?;
     rune_ctx.install(&uuid_module)?;
     rune_ctx.install(&latte_module)?;
+    rune_ctx.install(&cql_types_module)?;
     rune_ctx.install(&fs_module)?;
     rune_ctx.install(&iter_module)?;
 
@@ -103,12 +142,22 @@ fn try_install(
     context_module.function_meta(functions::delete_table)?;
     context_module.function_meta(functions::put)?;
     context_module.function_meta(functions::get)?;
+    context_module.function_meta(functions::get_consistent)?;
     context_module.function_meta(functions::delete)?;
+    context_module.function_meta(functions::delete_if)?;
+    context_module.function_meta(functions::transact_write)?;
     context_module.function_meta(functions::update)?;
     context_module.function_meta(functions::batch_get_item)?;
     context_module.function_meta(functions::batch_write_item)?;
     context_module.function_meta(functions::query)?;
+    context_module.function_meta(functions::query_range)?;
+    context_module.function_meta(functions::query_all)?;
+    context_module.function_meta(functions::query_projected)?;
     context_module.function_meta(functions::scan)?;
+    context_module.function_meta(functions::scan_segment)?;
+    context_module.function_meta(functions::get_page_size)?;
+    context_module.function_meta(functions::set_page_size)?;
+    context_module.function_meta(functions::switch_endpoint)?;
 
     let err_module = init_error_module()?;
     let uuid_module = init_uuid_module()?;
@@ -136,14 +185,34 @@ fn init_context_module() -> Result<Module, ContextError> {
     context_module.ty::<context::Context>()?;
     context_module.function_meta(functions_common::signal_failure)?;
     context_module.function_meta(functions_common::elapsed_secs)?;
+    context_module.function_meta(functions_common::begin_measurement)?;
+    context_module.function_meta(functions_common::end_measurement)?;
+    context_module.function_meta(functions_common::discard_stats)?;
+    context_module.function_meta(functions_common::shared_read)?;
+    context_module.function_meta(functions_common::shared_write)?;
+    context_module.function_meta(functions_common::rate_limit)?;
     context_module.function_meta(functions_common::set_report_field)?;
     context_module.function_meta(functions_common::record_metric)?;
     context_module.function_meta(functions_common::declare_metric)?;
+    context_module.function_meta(stats::stats)?;
+    context_module.function_meta(stats::validation_failures)?;
+    context_module.function_meta(stats::stats_snapshot)?;
+    context_module.function_meta(stats::stats_by_category)?;
+    context_module.ty::<stats::Stats>()?;
+    context_module.function_meta(stats::error_rate)?;
+    context_module.function_meta(stats::elapsed_throughput)?;
 
     context_module.function_meta(row_distribution::init_partition_row_distribution_preset)?;
+    context_module.function_meta(row_distribution::init_partition_row_distribution_preset_auto)?;
+    context_module.function_meta(row_distribution::init_round_robin_preset)?;
+    context_module.function_meta(row_distribution::delete_partition_row_preset)?;
+    context_module.function_meta(row_distribution::list_partition_row_presets)?;
+    context_module.function_meta(row_distribution::preset_partition_count)?;
     context_module.function_meta(row_distribution::get_partition_idx)?;
     context_module.ty::<row_distribution::Partition>()?;
     context_module.function_meta(row_distribution::get_partition_info)?;
+    context_module.function_meta(row_distribution::partition_rows)?;
+    context_module.function_meta(row_distribution::get_preset_summary)?;
 
     Ok(context_module)
 }
@@ -153,6 +222,13 @@ fn init_error_module() -> Result<Module, ContextError> {
 
     err_module.ty::<db_error::DbError>()?;
     err_module.function_meta(db_error::DbError::string_display)?;
+    err_module.function_meta(retry_error::retry_error)?;
+
+    err_module.ty::<retry_error::RetryError>()?;
+    err_module.function_meta(retry_error::RetryError::string_display)?;
+    err_module.function_meta(retry_error::is_timeout)?;
+    err_module.function_meta(retry_error::is_overloaded)?;
+    err_module.function_meta(retry_error::attempts)?;
 
     Ok(err_module)
 }
@@ -162,6 +238,9 @@ fn init_uuid_module() -> Result<Module, ContextError> {
 
     uuid_module.ty::<rune_uuid::Uuid>()?;
     uuid_module.function_meta(rune_uuid::Uuid::string_display)?;
+    uuid_module.function_meta(rune_uuid::uuid_to_unix_timestamp)?;
+    uuid_module.function_meta(rune_uuid::uuid_min_for_timestamp_ms)?;
+    uuid_module.function_meta(rune_uuid::uuid_max_for_timestamp_ms)?;
 
     Ok(uuid_module)
 }
@@ -173,19 +252,58 @@ fn init_latte_module(params: HashMap<String, String>) -> Result<Module, ContextE
         functions_common::param(ctx, &params, ts)
     })?;
     latte_module.function_meta(functions_common::blob)?;
+    latte_module.function_meta(functions_common::random_inet)?;
+    latte_module.function_meta(functions_common::compress_blob)?;
+    latte_module.function_meta(functions_common::decompress_blob)?;
+    latte_module.function_meta(functions_common::gzip_compress)?;
+    latte_module.function_meta(functions_common::gzip_decompress)?;
+    latte_module.function_meta(functions_common::base64_encode)?;
+    latte_module.function_meta(functions_common::base64_decode)?;
+    latte_module.function_meta(functions_common::hex_encode)?;
+    latte_module.function_meta(functions_common::hex_decode)?;
+    latte_module.function_meta(functions_common::sha256_hash)?;
+    latte_module.function_meta(functions_common::sha512_hash)?;
+    latte_module.function_meta(functions_common::hmac_sha256)?;
     latte_module.function_meta(functions_common::text)?;
     latte_module.function_meta(functions_common::vector)?;
+    latte_module.function_meta(functions_common::map_gen)?;
+    latte_module.function_meta(functions_common::set_gen)?;
     latte_module.function_meta(functions_common::join)?;
+    latte_module.function_meta(functions_common::zip)?;
+    latte_module.function_meta(functions_common::enumerate)?;
+    latte_module.function_meta(functions_common::chunk)?;
+    latte_module.function_meta(functions_common::flatten)?;
+    latte_module.function_meta(functions_common::shuffle)?;
+    latte_module.function_meta(functions_common::map_keys)?;
+    latte_module.function_meta(functions_common::map_values)?;
+    latte_module.function_meta(functions_common::merge_objects)?;
+    latte_module.function_meta(functions_common::object_get_path)?;
     latte_module.function_meta(functions_common::now_timestamp)?;
+    latte_module.function_meta(functions_common::sleep_until_timestamp)?;
+    latte_module.ty::<functions_common::StopWatch>()?;
+    latte_module.function_meta(functions_common::start_stopwatch)?;
+    latte_module.function_meta(functions_common::stopwatch_elapsed_ms)?;
+    latte_module.function_meta(functions_common::stopwatch_reset)?;
     latte_module.function_meta(functions_common::hash)?;
     latte_module.function_meta(functions_common::hash2)?;
     latte_module.function_meta(functions_common::hash_range)?;
+    latte_module.function_meta(functions_common::hash_range_between)?;
     latte_module.function_meta(functions_common::hash_select)?;
+    latte_module.function_meta(functions_common::sample_without_replacement)?;
+    latte_module.function_meta(functions_common::hash_bool)?;
+    latte_module.function_meta(functions_common::hash_category)?;
+    latte_module.function_meta(functions_common::hash_consistent)?;
     latte_module.function_meta(functions_common::uuid)?;
     latte_module.function_meta(functions_common::normal)?;
     latte_module.function_meta(functions_common::normal_f32)?;
     latte_module.function_meta(functions_common::uniform)?;
+    latte_module.function_meta(functions_common::uniform_int)?;
+    latte_module.function_meta(functions_common::poisson)?;
+    latte_module.function_meta(functions_common::geometric)?;
+    latte_module.function_meta(functions_common::beta)?;
+    latte_module.function_meta(functions_common::lognormal)?;
     latte_module.function_meta(functions_common::is_none)?;
+    latte_module.function_meta(functions_common::parse_retry_interval)?;
 
     Ok(latte_module)
 }
@@ -195,11 +313,21 @@ fn init_fs_module() -> Result<Module, ContextError> {
 
     fs_module.function_meta(functions_common::read_to_string)?;
     fs_module.function_meta(functions_common::read_lines)?;
+    fs_module.function_meta(functions_common::read_lines_parallel)?;
     fs_module.function_meta(functions_common::read_words)?;
+    fs_module.function_meta(functions_common::file_exists)?;
+    fs_module.function_meta(functions_common::file_size)?;
+    fs_module.function_meta(functions_common::write_to_file)?;
+    fs_module.function_meta(functions_common::append_to_file)?;
     fs_module.function_meta(functions_common::read_resource_to_string)?;
     fs_module.function_meta(functions_common::read_resource_lines)?;
     fs_module.function_meta(functions_common::read_resource_words)?;
 
+    fs_module.ty::<indexed_file::IndexedFile>()?;
+    fs_module.function_meta(indexed_file::open_indexed_file)?;
+    fs_module.function_meta(indexed_file::line_at)?;
+    fs_module.function_meta(indexed_file::line_count)?;
+
     Ok(fs_module)
 }
 
@@ -1,11 +1,63 @@
 use chrono::Utc;
 use rand::random;
+use rune::alloc::fmt::TryWrite;
+use rune::runtime::VmResult;
+use rune::{vm_write, Any};
 use std::time::Duration;
 use tracing::error;
 
 use super::context::Context;
 use super::db_error::DbError;
 
+/// Describes a failed database operation in terms useful for retry decisions
+/// made by workload scripts.
+#[derive(Any, Clone, Debug)]
+pub struct RetryError {
+    /// Number of retry attempts made before giving up; 0 if the operation was not retried.
+    pub attempts: u64,
+    pub timeout: bool,
+    pub overloaded: bool,
+    pub message: String,
+}
+
+impl RetryError {
+    pub fn new(attempts: u64, timeout: bool, overloaded: bool, message: String) -> RetryError {
+        RetryError {
+            attempts,
+            timeout,
+            overloaded,
+            message,
+        }
+    }
+
+    #[rune::function(protocol = DISPLAY_FMT)]
+    pub fn string_display(&self, f: &mut rune::runtime::Formatter) -> VmResult<()> {
+        let _ = vm_write!(f, "{}", self.message);
+        VmResult::Ok(())
+    }
+}
+
+/// Returns the retry-oriented view of a database error.
+#[rune::function(instance)]
+pub fn retry_error(err: &DbError) -> RetryError {
+    err.to_retry_error()
+}
+
+#[rune::function(instance)]
+pub fn is_timeout(err: &RetryError) -> bool {
+    err.timeout
+}
+
+#[rune::function(instance)]
+pub fn is_overloaded(err: &RetryError) -> bool {
+    err.overloaded
+}
+
+#[rune::function(instance)]
+pub fn attempts(err: &RetryError) -> u64 {
+    err.attempts
+}
+
 pub fn get_exponential_retry_interval(
     min_interval: Duration,
     max_interval: Duration,
@@ -43,6 +95,10 @@ pub async fn handle_retry_error(ctxt: &Context, current_attempt_num: u64, curren
         current_error,
     );
     error!("{}", err_msg);
+    ctxt.stats
+        .try_lock()
+        .unwrap()
+        .record_error(current_error.category());
     if !is_last_attempt {
         ctxt.stats.try_lock().unwrap().store_retry_error(err_msg);
         tokio::time::sleep(current_retry_interval).await;
@@ -50,3 +106,21 @@ pub async fn handle_retry_error(ctxt: &Context, current_attempt_num: u64, curren
         eprintln!("{err_msg}");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retries_exceeded_round_trips_through_rune() {
+        let err = DbError::query_retries_exceeded(7).with_context("insert");
+        let value = rune::to_value(err.to_retry_error()).unwrap();
+        let retry_error: RetryError = rune::from_value(value).unwrap();
+        assert_eq!(retry_error.attempts, 7);
+        assert!(!retry_error.timeout);
+        assert!(!retry_error.overloaded);
+        assert!(retry_error
+            .message
+            .contains("Max retry attempts (7) reached"));
+    }
+}
@@ -1,6 +1,7 @@
-use rune::runtime::Ref;
-use rune::Any;
+use rune::runtime::{Object, Ref, Vec as RuneVec};
+use rune::{Any, Value};
 use std::collections::HashMap;
+use tracing::info;
 
 use super::context::Context;
 use super::db_error::{DbError, DbErrorKind};
@@ -21,11 +22,72 @@ pub struct RowDistribution {
     pub n_rows_for_all_cycles: u64,
 }
 
-#[derive(Clone, Debug, PartialEq)]
+/// Maximum difference between 100 and the sum of the partition group percentages.
+/// Allows rounded splits like "33.3:1,33.3:2,33.3:4".
+const PERCENTAGE_TOLERANCE: f64 = 0.1;
+
+/// Number of slots of the partition lookup cache of a preset.
+const PARTITION_LOOKUP_CACHE_SIZE: usize = 4096;
+
+#[derive(Clone, Debug)]
 pub struct RowDistributionPreset {
     pub total_rows: u64,
     pub partition_groups: Vec<PartitionGroup>,
     pub row_distributions: Vec<(RowDistribution, RowDistribution)>,
+    /// Direct-mapped cache of recent lookups: row idx -> (partition idx, rows in it).
+    /// Allocated on the first lookup.
+    lookup_cache: Vec<Option<(u64, (u64, u64))>>,
+}
+
+/// Presets are equal if they describe the same distribution, whatever lookups they have cached.
+impl PartialEq for RowDistributionPreset {
+    fn eq(&self, other: &Self) -> bool {
+        self.total_rows == other.total_rows
+            && self.partition_groups == other.partition_groups
+            && self.row_distributions == other.row_distributions
+    }
+}
+
+/// Numbers of partitions and rows of a preset
+#[derive(Clone, Debug, PartialEq)]
+pub struct PresetSummary {
+    pub total_partitions: u64,
+    pub total_rows: u64,
+    pub partition_groups: Vec<PartitionGroup>,
+}
+
+impl PresetSummary {
+    fn to_object(&self) -> Result<Object, rune::alloc::Error> {
+        let mut groups = RuneVec::new();
+        for pg in &self.partition_groups {
+            let percent = pg.n_partitions as f64 / self.total_partitions as f64 * 100.0;
+            groups.push(Value::new(new_object([
+                ("partitions", Value::from(pg.n_partitions as i64)),
+                (
+                    "rows_per_partition",
+                    Value::from(pg.n_rows_per_partition as i64),
+                ),
+                ("rows", Value::from(pg.n_rows_per_group as i64)),
+                ("percent", Value::from(percent)),
+            ])?)?)?;
+        }
+        new_object([
+            (
+                "total_partitions",
+                Value::from(self.total_partitions as i64),
+            ),
+            ("total_rows", Value::from(self.total_rows as i64)),
+            ("groups", Value::vec(groups.into_inner())?),
+        ])
+    }
+}
+
+fn new_object<const N: usize>(fields: [(&str, Value); N]) -> Result<Object, rune::alloc::Error> {
+    let mut object = Object::new();
+    for (key, value) in fields {
+        object.insert(rune::alloc::String::try_from(key)?, value)?;
+    }
+    Ok(object)
 }
 
 impl RowDistributionPreset {
@@ -35,6 +97,7 @@ impl RowDistributionPreset {
             total_rows,
             partition_groups,
             row_distributions: vec![],
+            lookup_cache: vec![],
         }
     }
 
@@ -75,38 +138,115 @@ impl RowDistributionPreset {
         }
     }
 
+    /// Checks that the row distributions cover exactly the rows of the partition groups:
+    /// for each group, the cycles must place all the rows of that group on the left side
+    /// and all the rows of the following groups on the right side.
+    #[allow(clippy::result_large_err)]
+    pub fn validate(&self) -> Result<(), DbError> {
+        if self.row_distributions.len() != self.partition_groups.len() {
+            return Err(DbError::new(DbErrorKind::InvalidPartitionRowPreset(
+                format!(
+                    "Row distribution preset has {} row distributions for {} partition groups",
+                    self.row_distributions.len(),
+                    self.partition_groups.len()
+                ),
+            )));
+        }
+        let mut remaining_rows = self.total_rows;
+        for (i, (partition_group, (cycle_type_1, cycle_type_2))) in self
+            .partition_groups
+            .iter()
+            .zip(&self.row_distributions)
+            .enumerate()
+        {
+            let all_rows = cycle_type_1.n_rows_for_all_cycles + cycle_type_2.n_rows_for_all_cycles;
+            let left_rows = cycle_type_1.n_cycles * cycle_type_1.n_rows_for_left
+                + cycle_type_2.n_cycles * cycle_type_2.n_rows_for_left;
+            if all_rows != remaining_rows || left_rows != partition_group.n_rows_per_group {
+                return Err(DbError::new(DbErrorKind::InvalidPartitionRowPreset(
+                    format!(
+                        "Row distribution of partition group {i} covers {all_rows} rows \
+                    ({left_rows} of the group), but expected {remaining_rows} rows \
+                    ({} of the group); partition groups: {:?}",
+                        partition_group.n_rows_per_group, self.partition_groups
+                    ),
+                )));
+            }
+            remaining_rows -= partition_group.n_rows_per_group;
+        }
+        Ok(())
+    }
+
+    /// Returns the total number of partitions of all partition groups.
+    pub fn partition_count(&self) -> u64 {
+        self.partition_groups.iter().map(|pg| pg.n_partitions).sum()
+    }
+
+    pub fn summary(&self) -> PresetSummary {
+        PresetSummary {
+            total_partitions: self.partition_count(),
+            total_rows: self.total_rows,
+            partition_groups: self.partition_groups.clone(),
+        }
+    }
+
+    /// Returns the number of rows in the partition with the given index,
+    /// or `None` if the index is beyond the partitions of the preset.
+    /// Partition indexes are assigned group by group, in the order of the partition groups.
+    pub fn partition_size(&self, partition_idx: u64) -> Option<u64> {
+        let mut partn_offset = 0;
+        for partition_group in &self.partition_groups {
+            if partition_idx < partn_offset + partition_group.n_partitions {
+                return Some(partition_group.n_rows_per_partition);
+            }
+            partn_offset += partition_group.n_partitions;
+        }
+        None
+    }
+
     /// Returns partition index and number of expected rows in it
     /// based on the provided stress iteration index.
-    pub async fn get_partition_info(&self, idx: u64) -> (u64, u64) {
-        self._get_partition_info(
-            idx % self.total_rows,
-            0,
-            self.partition_groups.clone(),
-            self.row_distributions.clone(),
-        )
-        .await
+    /// Results of recent lookups are memoized, so repeated indexes are cheap.
+    #[allow(clippy::result_large_err)]
+    pub fn get_partition_info(&mut self, idx: u64) -> Result<(u64, u64), DbError> {
+        if self.total_rows == 0 {
+            return Err(invalid_preset("the preset has no rows"));
+        }
+        let idx = idx % self.total_rows;
+        let slot = (idx % PARTITION_LOOKUP_CACHE_SIZE as u64) as usize;
+        if let Some(Some((cached_idx, info))) = self.lookup_cache.get(slot) {
+            if *cached_idx == idx {
+                return Ok(*info);
+            }
+        }
+        let info =
+            self._get_partition_info(idx, 0, &self.partition_groups, &self.row_distributions)?;
+        if self.lookup_cache.is_empty() {
+            self.lookup_cache = vec![None; PARTITION_LOOKUP_CACHE_SIZE];
+        }
+        self.lookup_cache[slot] = Some((idx, info));
+        Ok(info)
     }
 
-    async fn _get_partition_info(
+    #[allow(clippy::result_large_err)]
+    fn _get_partition_info(
         &self,
         mut idx: u64,
         mut partn_offset: u64,
-        partition_groups: Vec<PartitionGroup>,
-        row_distributions: Vec<(RowDistribution, RowDistribution)>,
-    ) -> (u64, u64) {
+        partition_groups: &[PartitionGroup],
+        row_distributions: &[(RowDistribution, RowDistribution)],
+    ) -> Result<(u64, u64), DbError> {
         if partition_groups.is_empty() {
-            panic!("No partition groups found, cannot proceed");
+            return Err(invalid_preset("no partition groups found"));
         }
-        if row_distributions.is_empty() {
-            panic!("No row_distributions found, cannot proceed");
+        if row_distributions.len() < partition_groups.len() {
+            return Err(invalid_preset("row distributions were not generated"));
         }
-        for (loop_i, current_partn) in partition_groups.into_iter().enumerate() {
+        for (current_partn, (cycle_type_1, cycle_type_2)) in
+            partition_groups.iter().zip(row_distributions)
+        {
             let current_partn_count = current_partn.n_partitions;
 
-            let current_row_distribution = row_distributions[loop_i].clone();
-            let cycle_type_1 = current_row_distribution.0;
-            let cycle_type_2 = current_row_distribution.1;
-
             let cycle_type_1_size = cycle_type_1.n_rows_for_left_and_right;
             let done_cycle_type_1_num: u64;
             let done_cycle_type_1_rows: u64;
@@ -127,7 +267,7 @@ impl RowDistributionPreset {
                         + (idx - done_cycle_type_1_rows
                             + done_cycle_type_1_num * cycle_type_1.n_rows_for_left)
                             % current_partn_count;
-                    return (ret, current_partn.n_rows_per_partition);
+                    return Ok((ret, current_partn.n_rows_per_partition));
                 }
             } else {
                 done_cycle_type_1_num = cycle_type_1.n_cycles;
@@ -151,7 +291,7 @@ impl RowDistributionPreset {
                             - done_cycle_type_2_rows
                             + done_cycle_type_2_num * cycle_type_2.n_rows_for_left)
                             % current_partn_count;
-                    return (ret, current_partn.n_rows_per_partition);
+                    return Ok((ret, current_partn.n_rows_per_partition));
                 }
             }
             idx = idx
@@ -159,14 +299,19 @@ impl RowDistributionPreset {
                 - done_cycle_type_2_num * cycle_type_2.n_rows_for_left;
             partn_offset += current_partn_count;
         }
-        panic!(
-            "Failed to match idx and partition idx! \
+        Err(invalid_preset(format!(
+            "failed to match idx and partition idx! \
             Most probably row distribution values were incorrectly calculated \
-            according to the partition groups data."
-        );
+            according to the partition groups data: {:?}",
+            self.partition_groups
+        )))
     }
 }
 
+fn invalid_preset(msg: impl Into<String>) -> DbError {
+    DbError::new(DbErrorKind::InvalidPartitionRowPreset(msg.into()))
+}
+
 #[rune::function(instance)]
 pub async fn init_partition_row_distribution_preset(
     ctx: Ref<Context>,
@@ -185,6 +330,82 @@ pub async fn init_partition_row_distribution_preset(
     .await
 }
 
+/// Same as `init_partition_row_distribution_preset`, but generates a unique preset name
+/// and returns it, so that presets created by different scripts can't overwrite each other.
+#[rune::function(instance)]
+pub async fn init_partition_row_distribution_preset_auto(
+    ctx: Ref<Context>,
+    row_count: u64,
+    rows_per_partitions_base: u64,
+    rows_per_partitions_groups: Ref<str>,
+) -> Result<String, DbError> {
+    _init_partition_row_distribution_preset_auto(
+        &ctx,
+        row_count,
+        rows_per_partitions_base,
+        &rows_per_partitions_groups,
+    )
+    .await
+}
+
+/// Creates a preset of `n_partitions` partitions of `rows_per_partition` rows each,
+/// filled round-robin.
+#[rune::function(instance)]
+pub async fn init_round_robin_preset(
+    ctx: Ref<Context>,
+    preset_name: Ref<str>,
+    n_partitions: u64,
+    rows_per_partition: u64,
+) -> Result<(), DbError> {
+    _init_round_robin_preset(&ctx, &preset_name, n_partitions, rows_per_partition).await
+}
+
+/// Removes a preset, freeing its memory. Fails if there is no preset with the given name.
+#[rune::function(instance)]
+pub async fn delete_partition_row_preset(
+    ctx: Ref<Context>,
+    preset_name: Ref<str>,
+) -> Result<(), DbError> {
+    _delete_partition_row_preset(&ctx, &preset_name).await
+}
+
+/// Returns the total number of partitions of a preset, e.g. to cap partition indexes.
+#[rune::function(instance)]
+pub async fn preset_partition_count(
+    ctx: Ref<Context>,
+    preset_name: Ref<str>,
+) -> Result<u64, DbError> {
+    _preset_partition_count(&ctx, &preset_name).await
+}
+
+/// Returns the names of all presets, sorted.
+#[rune::function(instance)]
+pub fn list_partition_row_presets(ctx: Ref<Context>) -> Vec<String> {
+    _list_partition_row_presets(&ctx)
+}
+
+/// Returns an object with the `total_partitions` and `total_rows` of a preset, and its `groups`
+/// of equally sized partitions with their `partitions`, `rows_per_partition`, `rows`
+/// and `percent` of all partitions.
+#[rune::function(instance)]
+pub async fn get_preset_summary(
+    ctx: Ref<Context>,
+    preset_name: Ref<str>,
+) -> Result<Object, DbError> {
+    let summary = ctx
+        .partition_row_presets
+        .lock()
+        .unwrap()
+        .get(preset_name.as_ref())
+        .map(|preset| preset.summary())
+        .ok_or_else(|| {
+            DbError::new(DbErrorKind::PartitionRowPresetNotFound(
+                preset_name.to_string(),
+            ))
+        })?;
+    Ok(summary.to_object()?)
+}
+
 /// This 'Partition' data type is exposed to rune scripts
 #[derive(Any)]
 pub struct Partition {
@@ -196,19 +417,35 @@ pub struct Partition {
 }
 
 #[rune::function(instance)]
-pub async fn get_partition_info(ctx: Ref<Context>, preset_name: Ref<str>, idx: u64) -> Partition {
-    let (idx, rows_num) = _get_partition_info(&ctx, &preset_name, idx)
-        .await
-        .expect("failed to get partition");
-    Partition { idx, rows_num }
+pub async fn get_partition_info(
+    ctx: Ref<Context>,
+    preset_name: Ref<str>,
+    idx: u64,
+) -> Result<Partition, DbError> {
+    let (idx, rows_num) = _get_partition_info(&ctx, &preset_name, idx).await?;
+    Ok(Partition { idx, rows_num })
 }
 
 #[rune::function(instance)]
-pub async fn get_partition_idx(ctx: Ref<Context>, preset_name: Ref<str>, idx: u64) -> u64 {
-    let (idx, _rows_num) = _get_partition_info(&ctx, &preset_name, idx)
-        .await
-        .expect("failed to get partition");
-    idx
+pub async fn get_partition_idx(
+    ctx: Ref<Context>,
+    preset_name: Ref<str>,
+    idx: u64,
+) -> Result<u64, DbError> {
+    let (idx, _rows_num) = _get_partition_info(&ctx, &preset_name, idx).await?;
+    Ok(idx)
+}
+
+/// Returns the row offsets `0..rows_num` of the partition with the given index,
+/// e.g. to use them as clustering keys when writing full partitions.
+#[rune::function(instance)]
+pub async fn partition_rows(
+    ctx: Ref<Context>,
+    preset_name: Ref<str>,
+    partition_idx: u64,
+) -> Result<Vec<u64>, DbError> {
+    let rows_num = _get_partition_size(&ctx, &preset_name, partition_idx).await?;
+    Ok((0..rows_num).collect())
 }
 
 /// Creates a preset for uneven row distribution among partitions
@@ -267,12 +504,18 @@ async fn _init_partition_row_distribution_preset(
             }
         }
     }
-    if (summary_percentage - 100.0).abs() > 0.01 {
+    // NOTE: add a small epsilon to accept sums on the boundary despite floating point errors
+    if (summary_percentage - 100.0).abs() > PERCENTAGE_TOLERANCE + 1e-9 {
         return Err(DbError::new(DbErrorKind::Error(format!(
             "init_partition_row_distribution_preset: \
-            summary of partition percentage must be '100'. Got '{summary_percentage}' instead"
+            summary of partition percentage must be '100' (+/- {PERCENTAGE_TOLERANCE}). \
+            Got '{summary_percentage}' instead"
         ))));
     }
+    // Scale the percentages to sum up to exactly 100
+    for (partn_percent, _partn_multiplier) in partn_multipliers.values_mut() {
+        *partn_percent *= 100.0 / summary_percentage;
+    }
 
     // Calculate values
     let mut partn_sizes: HashMap<String, (f64, u64)> = HashMap::new();
@@ -386,8 +629,8 @@ async fn _init_partition_row_distribution_preset(
         })
         .collect::<Vec<String>>()
         .join(", ");
-    println!(
-        "info: init_partition_row_distribution_preset: \
+    info!(
+        "init_partition_row_distribution_preset: \
             preset_name={preset_name}\
             , total_partitions={partn_count}\
             , total_rows={actual_row_count}\
@@ -411,32 +654,148 @@ async fn _init_partition_row_distribution_preset(
     let mut row_distribution_preset = RowDistributionPreset::new(partition_groups);
     // NOTE: generate row distributions only after the partition groups are finished with changes
     row_distribution_preset.generate_row_distributions();
+    row_distribution_preset.validate()?;
     ctx.partition_row_presets
-        .try_lock()
+        .lock()
         .unwrap()
         .insert(preset_name.to_string(), row_distribution_preset);
 
     Ok(())
 }
 
-/// Returns a partition index and size based on the stress operation index and a preset of values
-async fn _get_partition_info(
+/// Creates a preset for uneven row distribution among partitions under a generated name
+async fn _init_partition_row_distribution_preset_auto(
+    ctx: &Context,
+    row_count: u64,
+    rows_per_partitions_base: u64,
+    rows_per_partitions_groups: &str,
+) -> Result<String, DbError> {
+    let preset_name = format!("preset_{}", uuid::Uuid::new_v4().simple());
+    _init_partition_row_distribution_preset(
+        ctx,
+        &preset_name,
+        row_count,
+        rows_per_partitions_base,
+        rows_per_partitions_groups,
+    )
+    .await?;
+    Ok(preset_name)
+}
+
+/// Creates a preset with a single group of equally sized partitions
+async fn _init_round_robin_preset(
     ctx: &Context,
     preset_name: &str,
-    idx: u64,
-) -> Result<(u64, u64), DbError> {
-    let preset = ctx
-        .partition_row_presets
-        .try_lock()
+    n_partitions: u64,
+    rows_per_partition: u64,
+) -> Result<(), DbError> {
+    if preset_name.is_empty() {
+        return Err(DbError::new(DbErrorKind::Error(
+            "init_round_robin_preset: 'preset_name' cannot be empty".to_string(),
+        )));
+    }
+    if n_partitions < 1 || rows_per_partition < 1 {
+        return Err(DbError::new(DbErrorKind::Error(format!(
+            "init_round_robin_preset: 'n_partitions' ({n_partitions}) and \
+            'rows_per_partition' ({rows_per_partition}) cannot be less than 1"
+        ))));
+    }
+    let n_rows_per_group = n_partitions
+        .checked_mul(rows_per_partition)
+        .ok_or_else(|| {
+            DbError::new(DbErrorKind::Error(format!(
+                "init_round_robin_preset: {n_partitions} partitions of {rows_per_partition} rows \
+            exceed the maximum number of rows"
+            )))
+        })?;
+    let mut row_distribution_preset = RowDistributionPreset::new(vec![PartitionGroup {
+        n_rows_per_group,
+        n_partitions,
+        n_rows_per_partition: rows_per_partition,
+    }]);
+    row_distribution_preset.generate_row_distributions();
+    row_distribution_preset.validate()?;
+    ctx.partition_row_presets
+        .lock()
+        .unwrap()
+        .insert(preset_name.to_string(), row_distribution_preset);
+    Ok(())
+}
+
+/// Returns the total number of partitions of a preset
+async fn _preset_partition_count(ctx: &Context, preset_name: &str) -> Result<u64, DbError> {
+    ctx.partition_row_presets
+        .lock()
         .unwrap()
         .get(preset_name)
+        .map(|preset| preset.partition_count())
+        .ok_or_else(|| {
+            DbError::new(DbErrorKind::PartitionRowPresetNotFound(
+                preset_name.to_string(),
+            ))
+        })
+}
+
+/// Returns the sorted names of the presets of the context
+fn _list_partition_row_presets(ctx: &Context) -> Vec<String> {
+    let mut names: Vec<String> = ctx
+        .partition_row_presets
+        .lock()
+        .unwrap()
+        .keys()
         .cloned()
+        .collect();
+    names.sort();
+    names
+}
+
+/// Removes a preset from the context
+async fn _delete_partition_row_preset(ctx: &Context, preset_name: &str) -> Result<(), DbError> {
+    ctx.partition_row_presets
+        .lock()
+        .unwrap()
+        .remove(preset_name)
+        .map(|_| ())
         .ok_or_else(|| {
             DbError::new(DbErrorKind::PartitionRowPresetNotFound(
                 preset_name.to_string(),
             ))
-        })?;
-    Ok(preset.get_partition_info(idx).await)
+        })
+}
+
+/// Returns a partition index and size based on the stress operation index and a preset of values
+async fn _get_partition_info(
+    ctx: &Context,
+    preset_name: &str,
+    idx: u64,
+) -> Result<(u64, u64), DbError> {
+    let mut presets = ctx.partition_row_presets.lock().unwrap();
+    let preset = presets.get_mut(preset_name).ok_or_else(|| {
+        DbError::new(DbErrorKind::PartitionRowPresetNotFound(
+            preset_name.to_string(),
+        ))
+    })?;
+    preset.get_partition_info(idx)
+}
+
+/// Returns the number of rows in the partition with the given index
+async fn _get_partition_size(
+    ctx: &Context,
+    preset_name: &str,
+    partition_idx: u64,
+) -> Result<u64, DbError> {
+    let presets = ctx.partition_row_presets.lock().unwrap();
+    let preset = presets.get(preset_name).ok_or_else(|| {
+        DbError::new(DbErrorKind::PartitionRowPresetNotFound(
+            preset_name.to_string(),
+        ))
+    })?;
+    preset.partition_size(partition_idx).ok_or_else(|| {
+        DbError::new(DbErrorKind::Error(format!(
+            "Partition index {partition_idx} is out of range, the '{preset_name}' preset has {} partitions",
+            preset.partition_count()
+        )))
+    })
 }
 
 /// Computes the greatest common divisor of 2 numbers, useful for rows distribution among DB partitions
@@ -537,16 +896,16 @@ mod tests {
             let ctxt: Context = create_test_context();
             let preset_name = "foo_name";
 
-            assert!(ctxt.partition_row_presets.try_lock().unwrap().is_empty(), "The 'partition_row_presets' HashMap should not be empty");
+            assert!(ctxt.partition_row_presets.lock().unwrap().is_empty(), "The 'partition_row_presets' HashMap should not be empty");
 
             tokio::runtime::Runtime::new().unwrap().block_on(async {
                 let _ = _init_partition_row_distribution_preset(&ctxt,
                     preset_name, row_count, rows_per_partitions_base, &rows_per_partitions_groups).await;
             });
 
-            assert!(!ctxt.partition_row_presets.try_lock().unwrap().is_empty(), "The 'partition_row_presets' HashMap should not be empty");
+            assert!(!ctxt.partition_row_presets.lock().unwrap().is_empty(), "The 'partition_row_presets' HashMap should not be empty");
             {
-                let binding = ctxt.partition_row_presets.try_lock().unwrap();
+                let binding = ctxt.partition_row_presets.lock().unwrap();
                 let actual_preset = binding.get(preset_name)
                     .unwrap_or_else(|| panic!("Preset with name '{preset_name}' was not found"));
                 assert_eq!(expected_partition_groups, actual_preset.partition_groups);
@@ -716,26 +1075,26 @@ mod tests {
         let name_bar: String = "bar".to_string();
         let ctxt: Context = create_test_context();
 
-        assert!(ctxt.partition_row_presets.try_lock().unwrap().is_empty(), "The 'partition_row_presets' HashMap should be empty");
-        let foo_value = ctxt.partition_row_presets.try_lock().unwrap().get(&name_foo).cloned();
+        assert!(ctxt.partition_row_presets.lock().unwrap().is_empty(), "The 'partition_row_presets' HashMap should be empty");
+        let foo_value = ctxt.partition_row_presets.lock().unwrap().get(&name_foo).cloned();
         assert_eq!(None, foo_value);
 
         tokio::runtime::Runtime::new().unwrap().block_on(async {
             _init_partition_row_distribution_preset(&ctxt,
                 &name_foo, 1000, 10, "100:1").await
         }).unwrap_or_else(|_| panic!("The '{name_foo}' preset must have been created successfully"));
-        assert!(!ctxt.partition_row_presets.try_lock().unwrap().is_empty(), "The 'partition_row_presets' HashMap should not be empty");
-        ctxt.partition_row_presets.try_lock().unwrap().get(&name_foo)
+        assert!(!ctxt.partition_row_presets.lock().unwrap().is_empty(), "The 'partition_row_presets' HashMap should not be empty");
+        ctxt.partition_row_presets.lock().unwrap().get(&name_foo)
             .unwrap_or_else(|| panic!("Preset with name '{name_foo}' was not found"));
 
-        let absent_bar = ctxt.partition_row_presets.try_lock().unwrap().get(&name_bar).cloned();
+        let absent_bar = ctxt.partition_row_presets.lock().unwrap().get(&name_bar).cloned();
         assert_eq!(None, absent_bar, "{}", format_args!("The '{}' preset was expected to be absent", name_bar));
 
         tokio::runtime::Runtime::new().unwrap().block_on(async {
             _init_partition_row_distribution_preset(&ctxt,
                 &name_bar, 1000, 10, "90:1,10:2").await
         }).unwrap_or_else(|_| panic!("The '{name_bar}' preset must have been created successfully"));
-        ctxt.partition_row_presets.try_lock().unwrap().get(&name_bar)
+        ctxt.partition_row_presets.lock().unwrap().get(&name_bar)
             .unwrap_or_else(|| panic!("Preset with name '{name_bar}' was not found"));
     }
 
@@ -771,12 +1130,12 @@ mod tests {
 
     #[test]
     fn test_partition_row_distribution_preset_09_neg_percentage_is_less_than_100() {
-        false_input_for_partition_row_distribution_preset("foo".to_string(), 1000, 10, "90:1,9.989:2".to_string())
+        false_input_for_partition_row_distribution_preset("foo".to_string(), 1000, 10, "90:1,9.899:2".to_string())
     }
 
     #[test]
     fn test_partition_row_distribution_preset_10_neg_percentage_is_more_than_100() {
-        false_input_for_partition_row_distribution_preset("foo".to_string(), 1000, 10, "90:1,10.011:2".to_string())
+        false_input_for_partition_row_distribution_preset("foo".to_string(), 1000, 10, "90:1,10.101:2".to_string())
     }
 
     #[test]
@@ -788,4 +1147,187 @@ mod tests {
     fn test_partition_row_distribution_preset_12_neg_wrong_percentages() {
         false_input_for_partition_row_distribution_preset("foo".to_string(), 1000, 10, "90:1,ten:1".to_string())
     }
+
+    #[test]
+    fn test_partition_size_follows_partition_groups() {
+        let ctxt: Context = create_test_context();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            _init_partition_row_distribution_preset(&ctxt, "foo", 1000, 13, "100:1").await
+        }).expect("Failed to init preset");
+
+        // 76 partitions of 13 rows followed by 1 partition of 12 rows
+        let size = |idx| runtime.block_on(_get_partition_size(&ctxt, "foo", idx)).ok();
+        assert_eq!(size(0), Some(13));
+        assert_eq!(size(75), Some(13));
+        assert_eq!(size(76), Some(12));
+        assert_eq!(size(77), None);
+        assert!(runtime.block_on(_get_partition_size(&ctxt, "bar", 0)).is_err());
+    }
+
+    #[test]
+    fn test_row_distribution_preset_validate_detects_inconsistent_distributions() {
+        let mut preset = RowDistributionPreset::new(vec![
+            PartitionGroup{ n_rows_per_group: 988, n_partitions: 76, n_rows_per_partition: 13},
+            PartitionGroup{ n_rows_per_group: 12, n_partitions: 1, n_rows_per_partition: 12},
+        ]);
+        assert!(preset.validate().is_err(), "distributions are not generated yet");
+        preset.generate_row_distributions();
+        preset.validate().expect("generated distributions must be consistent");
+
+        preset.row_distributions[1].1.n_rows_for_all_cycles += 1;
+        assert!(preset.validate().is_err());
+    }
+
+    #[test]
+    fn test_preset_equality_ignores_lookup_cache() {
+        let mut preset = RowDistributionPreset::new(vec![
+            PartitionGroup{ n_rows_per_group: 100, n_partitions: 10, n_rows_per_partition: 10},
+        ]);
+        preset.generate_row_distributions();
+        let copy = preset.clone();
+        preset.get_partition_info(42).unwrap();
+        assert_eq!(preset, copy);
+    }
+
+    #[test]
+    fn test_partition_info_of_invalid_preset_is_an_error() {
+        let mut empty = RowDistributionPreset::new(vec![]);
+        assert!(empty.get_partition_info(0).is_err());
+
+        let mut not_generated = RowDistributionPreset::new(vec![
+            PartitionGroup{ n_rows_per_group: 100, n_partitions: 10, n_rows_per_partition: 10},
+        ]);
+        assert!(not_generated.get_partition_info(0).is_err());
+    }
+
+    #[test]
+    fn test_memoized_partition_info_matches_computed_one() {
+        let ctxt: Context = create_test_context();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            _init_partition_row_distribution_preset(&ctxt, "foo", 10000, 6, "50:1,30:2,20:4").await
+        }).expect("Failed to init preset");
+        let uncached = ctxt.partition_row_presets.lock().unwrap()["foo"].clone();
+
+        // Indexes colliding in the cache and repeated lookups must give the same results
+        for idx in (0..20000).chain(0..20000).step_by(7) {
+            let expected = uncached.clone().get_partition_info(idx).unwrap();
+            let actual = runtime.block_on(_get_partition_info(&ctxt, "foo", idx)).unwrap();
+            assert_eq!(expected, actual, "idx = {idx}");
+        }
+    }
+
+    #[test]
+    fn test_round_robin_preset_matches_single_group_preset() {
+        let ctxt: Context = create_test_context();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            _init_partition_row_distribution_preset(&ctxt, "percentages", 1000, 25, "100:1").await?;
+            _init_round_robin_preset(&ctxt, "round_robin", 40, 25).await
+        }).expect("Failed to init presets");
+
+        for idx in 0..3000 {
+            assert_eq!(
+                runtime.block_on(_get_partition_info(&ctxt, "percentages", idx)).unwrap(),
+                runtime.block_on(_get_partition_info(&ctxt, "round_robin", idx)).unwrap(),
+            );
+        }
+        assert!(runtime.block_on(_init_round_robin_preset(&ctxt, "foo", 0, 25)).is_err());
+        assert!(runtime.block_on(_init_round_robin_preset(&ctxt, "foo", 40, 0)).is_err());
+        assert!(runtime.block_on(_init_round_robin_preset(&ctxt, "", 40, 25)).is_err());
+    }
+
+    #[test]
+    fn test_partition_row_distribution_preset_with_rounded_percentages_is_normalized() {
+        let ctxt: Context = create_test_context();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(async {
+            _init_partition_row_distribution_preset(&ctxt, "thirds", 1000, 10, "33.3:1,33.3:2,33.3:4").await
+        }).expect("Failed to init preset");
+
+        let presets = ctxt.partition_row_presets.lock().unwrap();
+        let preset = &presets["thirds"];
+        assert_eq!(preset.total_rows, 1000);
+        let partition_counts: Vec<u64> = preset.partition_groups.iter().map(|pg| pg.n_partitions).collect();
+        assert!(partition_counts.iter().max().unwrap() - partition_counts.iter().min().unwrap() <= 1,
+            "partition groups should be even: {:?}", preset.partition_groups);
+    }
+
+    #[test]
+    fn test_preset_summary_lists_partition_groups() {
+        let ctxt: Context = create_test_context();
+        tokio::runtime::Runtime::new().unwrap().block_on(async {
+            _init_partition_row_distribution_preset(&ctxt, "foo", 1000, 13, "100:1").await
+        }).expect("Failed to init preset");
+
+        let summary = ctxt.partition_row_presets.lock().unwrap()["foo"].summary();
+        assert_eq!(summary.total_partitions, 77);
+        assert_eq!(summary.total_rows, 1000);
+        let object = summary.to_object().unwrap();
+        assert_eq!(object.get("total_partitions").unwrap().as_integer::<i64>().unwrap(), 77);
+        let groups = rune::from_value::<Vec<Object>>(object.get("groups").unwrap().clone()).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].get("partitions").unwrap().as_integer::<i64>().unwrap(), 76);
+        assert_eq!(groups[1].get("rows_per_partition").unwrap().as_integer::<i64>().unwrap(), 12);
+    }
+
+    #[test]
+    fn test_auto_named_presets_do_not_collide() {
+        let ctxt: Context = create_test_context();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        let name_foo = runtime
+            .block_on(_init_partition_row_distribution_preset_auto(&ctxt, 1000, 10, "100:1"))
+            .expect("Failed to init preset");
+        let name_bar = runtime
+            .block_on(_init_partition_row_distribution_preset_auto(&ctxt, 500, 5, "50:1,50:2"))
+            .expect("Failed to init preset");
+        assert_ne!(name_foo, name_bar);
+
+        let presets = ctxt.partition_row_presets.lock().unwrap();
+        assert_eq!(presets.len(), 2);
+        assert_eq!(presets[&name_foo].total_rows, 1000);
+        assert_eq!(presets[&name_bar].total_rows, 500);
+    }
+
+    #[test]
+    fn test_deleted_preset_is_absent() {
+        let ctxt: Context = create_test_context();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(_init_round_robin_preset(&ctxt, "foo", 10, 5)).expect("Failed to init preset");
+        runtime.block_on(_init_round_robin_preset(&ctxt, "bar", 10, 5)).expect("Failed to init preset");
+
+        runtime.block_on(_delete_partition_row_preset(&ctxt, "foo")).expect("Failed to delete preset");
+        assert!(!ctxt.partition_row_presets.lock().unwrap().contains_key("foo"));
+        assert!(ctxt.partition_row_presets.lock().unwrap().contains_key("bar"));
+        assert!(runtime.block_on(_get_partition_info(&ctxt, "foo", 0)).is_err());
+
+        let err = runtime.block_on(_delete_partition_row_preset(&ctxt, "foo")).unwrap_err();
+        assert!(matches!(err.0, DbErrorKind::PartitionRowPresetNotFound(ref name) if name == "foo"));
+    }
+
+    #[test]
+    fn test_list_partition_row_presets_is_sorted() {
+        let ctxt: Context = create_test_context();
+        assert!(_list_partition_row_presets(&ctxt).is_empty());
+
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(_init_round_robin_preset(&ctxt, "foo", 10, 5)).expect("Failed to init preset");
+        runtime.block_on(_init_round_robin_preset(&ctxt, "bar", 10, 5)).expect("Failed to init preset");
+        assert_eq!(_list_partition_row_presets(&ctxt), vec!["bar", "foo"]);
+    }
+
+    #[test]
+    fn test_preset_partition_count_sums_partition_groups() {
+        let ctxt: Context = create_test_context();
+        let runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime.block_on(_init_partition_row_distribution_preset(&ctxt, "foo", 10000, 6, "50:1,30:2,20:4"))
+            .expect("Failed to init preset");
+
+        let expected: u64 = ctxt.partition_row_presets.lock().unwrap()["foo"]
+            .partition_groups.iter().map(|pg| pg.n_partitions).sum();
+        let count = runtime.block_on(_preset_partition_count(&ctxt, "foo")).expect("Failed to count partitions");
+        assert_eq!(count, expected);
+        assert!(runtime.block_on(_preset_partition_count(&ctxt, "bar")).is_err());
+    }
 }
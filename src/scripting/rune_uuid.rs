@@ -1,10 +1,19 @@
 use metrohash::MetroHash128;
 use rune::alloc::fmt::TryWrite;
 use rune::runtime::VmResult;
-use rune::{vm_write, Any};
+use rune::{vm_try, vm_write, Any, Value};
 use std::hash::Hash;
 use uuid::{Variant, Version};
 
+/// Number of 100 ns intervals between the start of the Gregorian calendar (1582-10-15)
+/// and the Unix epoch, which is the timestamp offset of version 1 UUIDs.
+const GREGORIAN_TO_UNIX_TICKS: u64 = 0x01B2_1DD2_1381_4000;
+
+/// Lowest and highest clock sequence and node of a timeuuid, in the order used by Cassandra,
+/// which compares these bytes as signed. Same as in Cassandra's `minTimeuuid` and `maxTimeuuid`.
+const MIN_CLOCK_SEQ_AND_NODE: u64 = 0x8080_8080_8080_8080;
+const MAX_CLOCK_SEQ_AND_NODE: u64 = 0x7f7f_7f7f_7f7f_7f7f;
+
 #[derive(Clone, Debug, Any)]
 pub struct Uuid(pub uuid::Uuid);
 
@@ -20,9 +29,214 @@ impl Uuid {
         Uuid(builder.into_uuid())
     }
 
+    /// Creates a version 1 UUID from a 60-bit timestamp counted in 100 ns intervals
+    /// since the start of the Gregorian calendar.
+    fn from_timestamp_ticks(ticks: u64, clock_seq_and_node: u64) -> Uuid {
+        let msb = ((ticks & 0xffff_ffff) << 32)
+            | (((ticks >> 32) & 0xffff) << 16)
+            | 0x1000
+            | ((ticks >> 48) & 0x0fff);
+        Uuid(uuid::Uuid::from_u128(
+            ((msb as u128) << 64) | clock_seq_and_node as u128,
+        ))
+    }
+
+    /// Returns the creation time embedded in a time-based (v1) UUID, such as a `timeuuid`,
+    /// in milliseconds since the Unix epoch, or `None` for other UUID versions.
+    pub fn unix_timestamp(&self) -> Option<i64> {
+        if self.0.get_version() != Some(Version::Mac) {
+            return None;
+        }
+        let (time_low, time_mid, time_hi_and_version, _) = self.0.as_fields();
+        let ticks = ((time_hi_and_version as u64 & 0x0fff) << 48)
+            | ((time_mid as u64) << 32)
+            | time_low as u64;
+        Some((ticks as i64 - GREGORIAN_TO_UNIX_TICKS as i64).div_euclid(10_000))
+    }
+
     #[rune::function(protocol = DISPLAY_FMT)]
     pub fn string_display(&self, f: &mut rune::runtime::Formatter) -> VmResult<()> {
         let _ = vm_write!(f, "{}", self.0);
         VmResult::Ok(())
     }
 }
+
+fn uuid_to_unix_timestamp_inner(uuid: &Value) -> VmResult<Option<i64>> {
+    if let Ok(uuid) = uuid.borrow_ref::<Uuid>() {
+        return VmResult::Ok(uuid.unix_timestamp());
+    }
+    // Timeuuid columns are returned from queries as strings
+    if let Ok(s) = uuid.borrow_ref::<rune::alloc::String>() {
+        return match uuid::Uuid::parse_str(s.as_str()) {
+            Ok(uuid) => VmResult::Ok(Uuid(uuid).unix_timestamp()),
+            Err(e) => VmResult::panic(format!("uuid_to_unix_timestamp: invalid UUID {s:?}: {e}")),
+        };
+    }
+    VmResult::panic(format!(
+        "uuid_to_unix_timestamp: expected a UUID or a string, got {uuid:?}"
+    ))
+}
+
+/// Extracts the creation time of a version 1 UUID (`timeuuid`) given as a UUID or a string,
+/// as milliseconds since the Unix epoch. Returns `None` for UUIDs of other versions.
+#[rune::function]
+pub fn uuid_to_unix_timestamp(uuid: Value) -> VmResult<Option<i64>> {
+    uuid_to_unix_timestamp_inner(&uuid)
+}
+
+/// Converts a Unix timestamp in milliseconds to the range of version 1 UUID timestamps
+/// created within that millisecond.
+fn unix_millis_to_ticks(fn_name: &str, ts_ms: i64) -> VmResult<(u64, u64)> {
+    match ts_ms
+        .checked_mul(10_000)
+        .and_then(|t| t.checked_add(GREGORIAN_TO_UNIX_TICKS as i64))
+        .filter(|t| (0..1 << 60).contains(t))
+    {
+        Some(t) => VmResult::Ok((t as u64, (t as u64 + 9_999).min((1 << 60) - 1))),
+        None => VmResult::panic(format!(
+            "{fn_name}: timestamp {ts_ms} cannot be stored in a timeuuid"
+        )),
+    }
+}
+
+fn uuid_min_for_timestamp_ms_inner(ts_ms: i64) -> VmResult<Uuid> {
+    let (min_ticks, _) = vm_try!(unix_millis_to_ticks("uuid_min_for_timestamp_ms", ts_ms));
+    VmResult::Ok(Uuid::from_timestamp_ticks(
+        min_ticks,
+        MIN_CLOCK_SEQ_AND_NODE,
+    ))
+}
+
+fn uuid_max_for_timestamp_ms_inner(ts_ms: i64) -> VmResult<Uuid> {
+    let (_, max_ticks) = vm_try!(unix_millis_to_ticks("uuid_max_for_timestamp_ms", ts_ms));
+    VmResult::Ok(Uuid::from_timestamp_ticks(
+        max_ticks,
+        MAX_CLOCK_SEQ_AND_NODE,
+    ))
+}
+
+/// Returns the lowest timeuuid with the given Unix timestamp in milliseconds,
+/// the same as CQL `minTimeuuid`.
+#[rune::function]
+pub fn uuid_min_for_timestamp_ms(ts_ms: i64) -> VmResult<Uuid> {
+    uuid_min_for_timestamp_ms_inner(ts_ms)
+}
+
+/// Returns the highest timeuuid with the given Unix timestamp in milliseconds,
+/// the same as CQL `maxTimeuuid`.
+#[rune::function]
+pub fn uuid_max_for_timestamp_ms(ts_ms: i64) -> VmResult<Uuid> {
+    uuid_max_for_timestamp_ms_inner(ts_ms)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    #[test]
+    fn extracts_timestamp_from_v1_uuid() {
+        // Created at 2004-08-15T13:09:31.981Z
+        let uuid = Uuid(uuid::Uuid::parse_str("58e0a7d7-eebc-11d8-9669-0800200c9a66").unwrap());
+        assert_eq!(uuid.unix_timestamp(), Some(1_092_575_371_981));
+
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as i64;
+        let ticks = now as u64 * 10_000 + GREGORIAN_TO_UNIX_TICKS;
+        let uuid =
+            uuid::Builder::from_gregorian_timestamp(ticks, 0, &[1, 2, 3, 4, 5, 6]).into_uuid();
+        let value = Value::new(Uuid(uuid)).unwrap();
+        let timestamp = uuid_to_unix_timestamp_inner(&value)
+            .into_result()
+            .unwrap()
+            .unwrap();
+        assert!((timestamp - now).abs() < 1000);
+
+        let value = rune::to_value(uuid.to_string()).unwrap();
+        let timestamp = uuid_to_unix_timestamp_inner(&value)
+            .into_result()
+            .unwrap()
+            .unwrap();
+        assert!((timestamp - now).abs() < 1000);
+    }
+
+    /// Orders timeuuids like Cassandra does: by timestamp, then by the signed bytes of
+    /// the clock sequence and node.
+    fn timeuuid_cmp(a: &uuid::Uuid, b: &uuid::Uuid) -> std::cmp::Ordering {
+        let key = |u: &uuid::Uuid| {
+            let (low, mid, hi, rest) = u.as_fields();
+            let ticks = ((hi as u64 & 0x0fff) << 48) | ((mid as u64) << 32) | low as u64;
+            let rest: Vec<i8> = rest.iter().map(|&b| b as i8).collect();
+            (ticks, rest)
+        };
+        key(a).cmp(&key(b))
+    }
+
+    #[test]
+    fn min_and_max_timeuuids_bound_the_millisecond() {
+        let ts = 1_700_000_000_123;
+        let min = uuid_min_for_timestamp_ms_inner(ts).into_result().unwrap();
+        let max = uuid_max_for_timestamp_ms_inner(ts).into_result().unwrap();
+        assert_eq!(min.unix_timestamp(), Some(ts));
+        assert_eq!(max.unix_timestamp(), Some(ts));
+
+        let ticks = ts as u64 * 10_000 + GREGORIAN_TO_UNIX_TICKS;
+        let nodes = [[0x00; 6], [0x80; 6], [0x7f; 6], [0xff; 6]];
+        for tick in [ticks, ticks + 5_000, ticks + 9_999] {
+            for counter in [0, 0x3fff] {
+                for node in &nodes {
+                    let uuid =
+                        uuid::Builder::from_gregorian_timestamp(tick, counter, node).into_uuid();
+                    assert_eq!(Uuid(uuid).unix_timestamp(), Some(ts));
+                    assert!(timeuuid_cmp(&min.0, &uuid).is_lt());
+                    assert!(timeuuid_cmp(&max.0, &uuid).is_gt());
+                }
+            }
+        }
+        // Timeuuids of the neighbouring milliseconds fall outside the range
+        let before =
+            uuid::Builder::from_gregorian_timestamp(ticks - 1, 0x3fff, &[0x7f; 6]).into_uuid();
+        let after =
+            uuid::Builder::from_gregorian_timestamp(ticks + 10_000, 0, &[0x80; 6]).into_uuid();
+        assert!(timeuuid_cmp(&before, &min.0).is_lt());
+        assert!(timeuuid_cmp(&after, &max.0).is_gt());
+
+        // Same values as returned by Cassandra for minTimeuuid(0) and maxTimeuuid(0)
+        assert_eq!(
+            uuid_min_for_timestamp_ms_inner(0)
+                .into_result()
+                .unwrap()
+                .0
+                .to_string(),
+            "13814000-1dd2-11b2-8080-808080808080"
+        );
+        assert_eq!(
+            uuid_max_for_timestamp_ms_inner(0)
+                .into_result()
+                .unwrap()
+                .0
+                .to_string(),
+            "1381670f-1dd2-11b2-7f7f-7f7f7f7f7f7f"
+        );
+        assert!(uuid_min_for_timestamp_ms_inner(i64::MIN)
+            .into_result()
+            .is_err());
+        assert!(uuid_max_for_timestamp_ms_inner(i64::MAX)
+            .into_result()
+            .is_err());
+    }
+
+    #[test]
+    fn returns_none_for_other_versions() {
+        assert_eq!(Uuid::new(1).unix_timestamp(), None);
+        let value = Value::new(Uuid::new(1)).unwrap();
+        assert_eq!(
+            uuid_to_unix_timestamp_inner(&value).into_result().unwrap(),
+            None
+        );
+        let value = rune::to_value("not a uuid".to_string()).unwrap();
+        assert!(uuid_to_unix_timestamp_inner(&value).into_result().is_err());
+    }
+}
@@ -0,0 +1,198 @@
+use rune::runtime::{Object, VmError, VmResult};
+use rune::{Any, Value};
+use std::time::Duration;
+
+use super::context::Context;
+use crate::stats::session::SessionStats;
+
+/// A snapshot of the request statistics collected by a context
+/// since the beginning of the current sampling interval.
+#[derive(Any, Clone, Debug)]
+pub struct Stats {
+    stats: SessionStats,
+    elapsed: Duration,
+}
+
+impl Stats {
+    pub fn new(stats: SessionStats) -> Stats {
+        let elapsed = stats.start_time.elapsed();
+        Stats { stats, elapsed }
+    }
+}
+
+/// Returns the request statistics of the current sampling interval.
+fn interval_stats(ctx: &Context) -> Result<SessionStats, VmError> {
+    ctx.interval_session_stats()
+        .map_err(|e| VmError::panic(format!("Failed to compute the interval stats: {e}")))
+}
+
+/// Returns a snapshot of the request statistics without resetting them.
+#[rune::function(instance)]
+pub fn stats(ctx: &Context) -> VmResult<Stats> {
+    VmResult::Ok(Stats::new(rune::vm_try!(interval_stats(ctx))))
+}
+
+fn stats_snapshot_inner(stats: &SessionStats) -> Result<Object, rune::alloc::Error> {
+    let fields = [
+        ("total_requests", Value::from(stats.req_count as i64)),
+        ("errors", Value::from(stats.req_error_count as i64)),
+        ("retries", Value::from(stats.req_retry_count as i64)),
+        ("rows", Value::from(stats.row_count as i64)),
+        (
+            "mean_latency_us",
+            Value::from(stats.resp_times_ns.mean_duration().as_secs_f64() * 1e6),
+        ),
+        ("p99_latency_us", Value::from(stats.p99_us() as i64)),
+    ];
+    let mut object = Object::new();
+    for (key, value) in fields {
+        object.insert(rune::alloc::String::try_from(key)?, value)?;
+    }
+    Ok(object)
+}
+
+/// Returns an object with the request counters and latencies of the current sampling
+/// interval: `total_requests`, `errors`, `retries`, `rows`, `mean_latency_us` and
+/// `p99_latency_us`. Reading the snapshot does not reset the counters.
+#[rune::function(instance)]
+pub fn stats_snapshot(ctx: &Context) -> VmResult<Object> {
+    let stats = rune::vm_try!(interval_stats(ctx));
+    VmResult::Ok(rune::vm_try!(stats_snapshot_inner(&stats)))
+}
+
+fn stats_by_category_inner(stats: &SessionStats) -> Result<Object, rune::alloc::Error> {
+    let counts = &stats.errors_by_category;
+    let fields = [
+        ("timeout", counts.timeout),
+        ("unavailable", counts.unavailable),
+        ("overloaded", counts.overloaded),
+        ("validation", counts.validation),
+        ("other", counts.other),
+    ];
+    let mut object = Object::new();
+    for (key, value) in fields {
+        object.insert(
+            rune::alloc::String::try_from(key)?,
+            Value::from(value as i64),
+        )?;
+    }
+    Ok(object)
+}
+
+/// Returns an object with the numbers of failed request attempts of the current sampling
+/// interval by cause: `timeout`, `unavailable`, `overloaded`, `validation` and `other`.
+/// Retried attempts are counted too.
+#[rune::function(instance)]
+pub fn stats_by_category(ctx: &Context) -> VmResult<Object> {
+    let stats = rune::vm_try!(interval_stats(ctx));
+    VmResult::Ok(rune::vm_try!(stats_by_category_inner(&stats)))
+}
+
+/// Returns the number of query results that did not pass validation in the current
+/// sampling interval, regardless of the validation strategy.
+/// The total for the whole run is printed in the report.
+#[rune::function(instance)]
+pub fn validation_failures(ctx: &Context) -> VmResult<u64> {
+    let stats = rune::vm_try!(interval_stats(ctx));
+    VmResult::Ok(stats.validation_failure_count)
+}
+
+/// Returns the fraction of request attempts that failed, including the retried ones,
+/// or 0.0 if no requests were made.
+#[rune::function(instance)]
+pub fn error_rate(stats: &Stats) -> f64 {
+    stats.stats.error_rate()
+}
+
+/// Returns the number of requests per second over the time covered by the snapshot.
+#[rune::function(instance)]
+pub fn elapsed_throughput(stats: &Stats) -> f64 {
+    stats.stats.throughput(stats.elapsed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{RetryInterval, ValidationStrategy};
+    use crate::scripting::db_error::{DbError, DbErrorKind};
+    use crate::scripting::retry_error::handle_retry_error;
+    use crate::stats::session::ErrorCategory;
+
+    #[cfg(feature = "cql")]
+    fn test_context() -> Context {
+        Context::new(
+            None,
+            501,
+            "dc".to_string(),
+            "rack".to_string(),
+            0,
+            RetryInterval::new("1,2").expect("failed to parse retry interval"),
+            ValidationStrategy::Ignore,
+        )
+    }
+
+    #[cfg(all(feature = "alternator", not(feature = "cql")))]
+    fn test_context() -> Context {
+        Context::new(
+            None,
+            0,
+            RetryInterval::new("1,2").expect("failed to parse retry interval"),
+            ValidationStrategy::Ignore,
+            501,
+        )
+    }
+
+    #[tokio::test]
+    async fn failed_requests_count_as_errors() {
+        let ctx = test_context();
+        for _ in 0..3 {
+            let mut stats = ctx.stats.try_lock().unwrap();
+            stats.start_request();
+            stats.complete_request("execute", Duration::from_millis(1), 1);
+        }
+        let err = DbError::new(DbErrorKind::CustomError("rejected".to_string()));
+        handle_retry_error(&ctx, 0, err).await;
+
+        let stats = Stats::new(ctx.interval_session_stats().unwrap());
+        assert_eq!(stats.stats.error_rate(), 0.25);
+        let snapshot = stats_snapshot_inner(&stats.stats).unwrap();
+        let errors = snapshot.get("errors").unwrap().as_integer::<i64>().unwrap();
+        assert_eq!(errors, 1);
+    }
+
+    #[test]
+    fn stats_snapshot_does_not_reset_counters() {
+        let mut stats = SessionStats::new();
+        stats.start_request();
+        stats.complete_request("execute", Duration::from_millis(2), 3);
+        stats.start_request();
+        stats.complete_request("execute", Duration::from_millis(4), 0);
+        stats.record_error(ErrorCategory::Overloaded);
+
+        let snapshot = stats_snapshot_inner(&stats).unwrap();
+        let field = |name: &str| snapshot.get(name).unwrap().clone();
+        assert_eq!(field("total_requests").as_integer::<i64>().unwrap(), 2);
+        assert_eq!(field("errors").as_integer::<i64>().unwrap(), 1);
+        assert_eq!(field("rows").as_integer::<i64>().unwrap(), 3);
+        let mean = field("mean_latency_us").as_float().unwrap();
+        assert!((mean - 3000.0).abs() < 10.0, "mean = {mean}");
+        assert_eq!(stats.req_count, 2);
+    }
+
+    #[test]
+    fn stats_by_category_counts_each_category() {
+        let mut stats = SessionStats::new();
+        stats.record_error(ErrorCategory::Timeout);
+        stats.record_error(ErrorCategory::Timeout);
+        stats.record_error(ErrorCategory::Unavailable);
+        stats.record_error(ErrorCategory::Other);
+
+        let by_category = stats_by_category_inner(&stats).unwrap();
+        let field = |name: &str| by_category.get(name).unwrap().as_integer::<i64>().unwrap();
+        assert_eq!(field("timeout"), 2);
+        assert_eq!(field("unavailable"), 1);
+        assert_eq!(field("overloaded"), 0);
+        assert_eq!(field("validation"), 0);
+        assert_eq!(field("other"), 1);
+    }
+}
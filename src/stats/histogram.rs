@@ -31,15 +31,18 @@ impl Serialize for SerializableHistogram {
     where
         S: serde::Serializer,
     {
-        let mut serialized_histogram = Vec::new();
-        V2DeflateSerializer::new()
-            .serialize(&self.0, &mut serialized_histogram)
-            .unwrap();
-        let encoded = base64_engine::STANDARD.encode(serialized_histogram);
-        serializer.serialize_str(encoded.as_str())
+        serializer.serialize_str(encode(&self.0).as_str())
     }
 }
 
+fn encode(histogram: &Histogram<u64>) -> String {
+    let mut serialized_histogram = Vec::new();
+    V2DeflateSerializer::new()
+        .serialize(histogram, &mut serialized_histogram)
+        .unwrap();
+    base64_engine::STANDARD.encode(serialized_histogram)
+}
+
 struct HistogramVisitor;
 
 impl Visitor<'_> for HistogramVisitor {
@@ -75,6 +78,30 @@ impl<'de> Deserialize<'de> for SerializableHistogram {
     }
 }
 
+/// Serde adapter for plain `Histogram<u64>` fields, using the same encoding as
+/// `SerializableHistogram`. Deserialized histograms resize automatically,
+/// so more values can be recorded into them.
+pub mod serde_histogram {
+    use super::{encode, HistogramVisitor};
+    use hdrhistogram::Histogram;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        histogram: &Histogram<u64>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(encode(histogram).as_str())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Histogram<u64>, D::Error> {
+        let mut histogram = deserializer.deserialize_str(HistogramVisitor)?.0;
+        histogram.auto(true);
+        Ok(histogram)
+    }
+}
+
 pub trait HistogramWriter {
     fn write_histogram(
         &mut self,
@@ -1,8 +1,8 @@
-use crate::stats::histogram::SerializableHistogram;
+use crate::stats::histogram::{serde_histogram, SerializableHistogram};
 use crate::stats::percentiles::Percentiles;
 use crate::stats::timeseries::TimeSeriesStats;
 use crate::stats::Mean;
-use hdrhistogram::Histogram;
+use hdrhistogram::{Histogram, SubtractionError};
 use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
@@ -15,8 +15,9 @@ pub struct LatencyDistribution {
 }
 
 /// Builds TimeDistribution from a stream of durations.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct LatencyDistributionRecorder {
+    #[serde(with = "serde_histogram")]
     histogram_ns: Histogram<u64>,
     ess_estimator: TimeSeriesStats,
 }
@@ -34,6 +35,33 @@ impl LatencyDistributionRecorder {
         self.ess_estimator.add(&other.ess_estimator);
     }
 
+    /// Removes the latencies recorded by `other`, which must be an earlier snapshot of this recorder.
+    /// The effective sample size estimator is kept as is, as it cannot be rolled back;
+    /// use `restart_sample_size_estimate` at the snapshot to estimate it for the difference only.
+    pub fn subtract(
+        &mut self,
+        other: &LatencyDistributionRecorder,
+    ) -> Result<(), SubtractionError> {
+        self.histogram_ns.subtract(&other.histogram_ns)
+    }
+
+    /// Forgets the latencies recorded so far by the effective sample size estimator,
+    /// but keeps them in the histogram.
+    pub fn restart_sample_size_estimate(&mut self) {
+        self.ess_estimator.clear();
+    }
+
+    /// Returns the recorded latency at the given quantile (0.0..=1.0).
+    /// Returns zero if nothing was recorded.
+    pub fn value_at_quantile(&self, quantile: f64) -> Duration {
+        Duration::from_nanos(self.histogram_ns.value_at_quantile(quantile))
+    }
+
+    /// Returns the mean of the recorded latencies, or zero if nothing was recorded.
+    pub fn mean_duration(&self) -> Duration {
+        Duration::from_nanos(self.histogram_ns.mean() as u64)
+    }
+
     pub fn clear(&mut self) {
         self.histogram_ns.clear();
         self.ess_estimator.clear();
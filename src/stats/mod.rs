@@ -6,6 +6,7 @@ use std::time::{Duration, Instant, SystemTime};
 
 use crate::exec::workload::WorkloadStats;
 use crate::stats::latency::{LatencyDistribution, LatencyDistributionRecorder};
+use crate::stats::session::SessionStats;
 use crate::stats::value::{ValueDistribution, ValueDistributionRecorder};
 use cpu_time::ProcessTime;
 use hdrhistogram::serialization::interval_log;
@@ -227,6 +228,8 @@ pub struct Sample {
     pub req_retry_count: u64,
     pub req_errors: HashSet<String>,
     pub req_error_count: u64,
+    #[serde(default)]
+    pub validation_failure_count: u64,
     pub row_count: u64,
     pub mean_queue_len: f32,
     pub cycle_throughput: f32,
@@ -246,39 +249,17 @@ impl Sample {
 
         let mut cycle_count = 0;
         let mut cycle_error_count = 0;
-        let mut request_count = 0;
-        let mut req_retry_errors = HashSet::new();
-        let mut req_retry_count = 0;
-        let mut row_count = 0;
-        let mut errors = HashSet::new();
-        let mut req_error_count = 0;
-        let mut mean_queue_len = 0.0;
         let mut duration_s = 0.0;
 
-        let mut request_latency = LatencyDistributionRecorder::default();
         let mut cycle_latency = LatencyDistributionRecorder::default();
         let mut cycle_latency_per_fn = HashMap::<String, LatencyDistributionRecorder>::new();
-        let mut custom_metrics = HashMap::<String, ValueDistributionRecorder>::new();
+
+        let session_stats = SessionStats::merge_all(stats.iter().map(|s| &s.session_stats));
+        let request_count = session_stats.req_count;
+        let row_count = session_stats.row_count;
 
         for s in stats {
-            let ss = &s.session_stats;
-            request_count += ss.req_count;
-            row_count += ss.row_count;
-            for (name, recorder) in &ss.custom_metrics {
-                custom_metrics
-                    .entry(name.clone())
-                    .or_default()
-                    .add(recorder);
-            }
-            if errors.len() < MAX_KEPT_ERRORS {
-                errors.extend(ss.req_errors.iter().cloned());
-            }
-            req_retry_errors.extend(ss.req_retry_errors.iter().cloned());
-            req_error_count += ss.req_error_count;
-            req_retry_count += ss.req_retry_count;
-            mean_queue_len += ss.mean_queue_length / stats.len() as f32;
             duration_s += (s.end_time - s.start_time).as_secs_f32() / stats.len() as f32;
-            request_latency.add(&ss.resp_times_ns);
 
             for fs in &s.function_stats {
                 cycle_count += fs.call_count;
@@ -297,12 +278,17 @@ impl Sample {
             cycle_count,
             cycle_error_count,
             request_count,
-            req_retry_errors,
-            req_retry_count,
-            req_errors: errors,
-            req_error_count,
+            req_retry_errors: session_stats.req_retry_errors,
+            req_retry_count: session_stats.req_retry_count,
+            req_errors: session_stats
+                .req_errors
+                .into_iter()
+                .take(MAX_KEPT_ERRORS)
+                .collect(),
+            req_error_count: session_stats.req_error_count,
+            validation_failure_count: session_stats.validation_failure_count,
             row_count,
-            mean_queue_len: not_nan_f32(mean_queue_len).unwrap_or(0.0),
+            mean_queue_len: not_nan_f32(session_stats.mean_queue_length).unwrap_or(0.0),
 
             cycle_throughput: cycle_count as f32 / duration_s,
             req_throughput: request_count as f32 / duration_s,
@@ -314,9 +300,10 @@ impl Sample {
                 .map(|(k, v)| (k, v.distribution()))
                 .collect(),
 
-            request_latency: request_latency.distribution(),
+            request_latency: session_stats.resp_times_ns.distribution(),
 
-            custom_metrics: custom_metrics
+            custom_metrics: session_stats
+                .custom_metrics
                 .into_iter()
                 .map(|(k, v)| (k, v.distribution()))
                 .collect(),
@@ -340,6 +327,8 @@ pub struct BenchmarkStats {
     pub errors: Vec<String>,
     pub error_count: u64,
     pub errors_ratio: Option<f64>,
+    #[serde(default)]
+    pub validation_failure_count: u64,
     pub row_count: u64,
     pub row_count_per_req: Option<f64>,
     pub cycle_throughput: Mean,
@@ -355,6 +344,11 @@ pub struct BenchmarkStats {
     pub concurrency_ratio: f64,
     #[serde(default)]
     pub run_metadata: HashMap<String, String>,
+    /// Request stats of all workers merged, as exported by `SessionStats::to_json`,
+    /// e.g. the error breakdown by category and the latencies per operation.
+    /// Null in reports of older versions.
+    #[serde(default)]
+    pub session_stats: serde_json::Value,
     pub log: Vec<Sample>,
 }
 
@@ -458,6 +452,7 @@ pub struct Recorder<'a> {
     pub request_count: u64,
     pub request_retry_count: u64,
     pub request_error_count: u64,
+    pub validation_failure_count: u64,
     pub throughput_meter: ThroughputMeter,
     pub errors: HashSet<String>,
     pub cycle_error_count: u64,
@@ -467,6 +462,9 @@ pub struct Recorder<'a> {
     pub request_latency: LatencyDistributionRecorder,
     pub custom_metrics: HashMap<String, ValueDistributionRecorder>,
     pub concurrency_meter: TimeSeriesStats,
+    pub session_stats: SessionStats,
+    /// Set after a sample in which the script ended the measurement window.
+    measurement_ended: bool,
     log: Vec<Sample>,
     rate_limit: Option<f64>,
     concurrency_limit: NonZeroUsize,
@@ -500,6 +498,7 @@ impl Recorder<'_> {
             request_count: 0,
             request_retry_count: 0,
             request_error_count: 0,
+            validation_failure_count: 0,
             row_count: 0,
             errors: HashSet::new(),
             cycle_error_count: 0,
@@ -509,6 +508,8 @@ impl Recorder<'_> {
             custom_metrics: HashMap::new(),
             throughput_meter: ThroughputMeter::default(),
             concurrency_meter: TimeSeriesStats::default(),
+            session_stats: SessionStats::new(),
+            measurement_ended: false,
             keep_log,
             hdrh_writer,
         }
@@ -516,8 +517,14 @@ impl Recorder<'_> {
 
     /// Adds the statistics of the completed request to the already collected statistics.
     /// Called on completion of each sample.
-    pub fn record(&mut self, workload_stats: &[WorkloadStats]) -> &Sample {
+    /// Returns `None` if the sample is outside the measurement window set by the script.
+    pub fn record(&mut self, workload_stats: &[WorkloadStats]) -> Option<&Sample> {
         assert!(!workload_stats.is_empty());
+        if workload_stats.iter().any(|s| s.session_stats.discarded) {
+            self.restart(workload_stats);
+        } else if self.measurement_ended {
+            return None;
+        }
         let mut current_sample_latency_recorder: Option<
             HashMap<String, LatencyDistributionRecorder>,
         > = if self.hdrh_writer.is_some() {
@@ -526,6 +533,7 @@ impl Recorder<'_> {
             None
         };
         for s in workload_stats.iter() {
+            self.session_stats.merge(&s.session_stats);
             self.request_latency.add(&s.session_stats.resp_times_ns);
             for (name, recorder) in &s.session_stats.custom_metrics {
                 self.custom_metrics
@@ -553,6 +561,7 @@ impl Recorder<'_> {
         self.request_count += sample.request_count;
         self.request_retry_count += sample.req_retry_count;
         self.request_error_count += sample.req_error_count;
+        self.validation_failure_count += sample.validation_failure_count;
         self.row_count += sample.row_count;
         self.throughput_meter.record(sample.cycle_count);
         self.concurrency_meter
@@ -582,8 +591,43 @@ impl Recorder<'_> {
             }
         }
 
+        if workload_stats
+            .iter()
+            .any(|s| s.session_stats.measurement_end_marked)
+        {
+            self.measurement_ended = true;
+            self.end_instant = workload_stats.iter().map(|s| s.end_time).max().unwrap();
+            self.end_time = SystemTime::now() - self.end_instant.elapsed();
+            self.end_cpu_time = ProcessTime::now();
+        }
+
         self.log.push(sample);
-        self.log.last().unwrap()
+        self.log.last()
+    }
+
+    /// Drops the statistics collected so far and starts collecting them again
+    /// from the beginning of the given sample.
+    fn restart(&mut self, workload_stats: &[WorkloadStats]) {
+        self.start_instant = workload_stats.iter().map(|s| s.start_time).min().unwrap();
+        self.start_time = SystemTime::now() - self.start_instant.elapsed();
+        self.start_cpu_time = ProcessTime::now();
+        self.cycle_count = 0;
+        self.request_count = 0;
+        self.request_retry_count = 0;
+        self.request_error_count = 0;
+        self.validation_failure_count = 0;
+        self.throughput_meter.clear();
+        self.errors.clear();
+        self.cycle_error_count = 0;
+        self.row_count = 0;
+        self.cycle_latency.clear();
+        self.cycle_latency_by_fn.clear();
+        self.request_latency.clear();
+        self.custom_metrics.clear();
+        self.concurrency_meter.clear();
+        self.session_stats = SessionStats::new();
+        self.measurement_ended = false;
+        self.log.clear();
     }
 
     /// Stops the recording, computes the statistics and returns them as the new object.
@@ -592,9 +636,11 @@ impl Recorder<'_> {
         run_metadata: HashMap<String, String>,
         metric_orientations: HashMap<String, i8>,
     ) -> BenchmarkStats {
-        self.end_time = SystemTime::now();
-        self.end_instant = Instant::now();
-        self.end_cpu_time = ProcessTime::now();
+        if !self.measurement_ended {
+            self.end_time = SystemTime::now();
+            self.end_instant = Instant::now();
+            self.end_cpu_time = ProcessTime::now();
+        }
 
         let elapsed_time_s = (self.end_instant - self.start_instant).as_secs_f64();
         let cpu_time_s = self
@@ -625,6 +671,7 @@ impl Recorder<'_> {
             errors: self.errors.into_iter().collect(),
             error_count: self.cycle_error_count,
             errors_ratio: not_nan(100.0 * self.cycle_error_count as f64 / self.cycle_count as f64),
+            validation_failure_count: self.validation_failure_count,
             request_count: self.request_count,
             request_retry_count: self.request_retry_count,
             request_retry_per_request: not_nan(
@@ -663,6 +710,7 @@ impl Recorder<'_> {
             concurrency,
             concurrency_ratio,
             run_metadata,
+            session_stats: self.session_stats.to_json(),
             log: self.log,
         }
     }
@@ -693,6 +741,7 @@ mod test {
             errors: Vec::new(),
             error_count: 0,
             errors_ratio: Some(0.0),
+            validation_failure_count: 0,
             row_count: 0,
             row_count_per_req: Some(0.0),
             cycle_throughput: mean,
@@ -706,6 +755,7 @@ mod test {
             concurrency: mean,
             concurrency_ratio: 100.0,
             run_metadata: std::collections::HashMap::new(),
+            session_stats: serde_json::Value::Null,
             log: Vec::new(),
         }
     }
@@ -733,9 +783,11 @@ mod test {
         let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
         value.as_object_mut().unwrap().remove("run_metadata");
         value.as_object_mut().unwrap().remove("custom_metrics");
+        value.as_object_mut().unwrap().remove("session_stats");
         let parsed: super::BenchmarkStats = serde_json::from_value(value).unwrap();
         assert!(parsed.run_metadata.is_empty());
         assert!(parsed.custom_metrics.is_empty());
+        assert!(parsed.session_stats.is_null());
     }
 
     #[test]
@@ -767,6 +819,46 @@ mod test {
         assert_eq!(parsed.custom_metrics.get("recall").unwrap().orientation, 0);
     }
 
+    #[test]
+    fn recorder_exports_merged_session_stats() {
+        use crate::exec::workload::WorkloadStats;
+        use crate::stats::session::{ErrorCategory, SessionStats};
+        use std::time::{Duration, Instant};
+
+        let mut session_stats = SessionStats::new();
+        for _ in 0..3 {
+            session_stats.start_request();
+            session_stats.complete_request("execute", Duration::from_millis(1), 1);
+        }
+        session_stats.record_error(ErrorCategory::Timeout);
+        let workload_stats = |session_stats: &SessionStats| WorkloadStats {
+            start_time: Instant::now(),
+            end_time: Instant::now(),
+            function_stats: Vec::new(),
+            session_stats: session_stats.clone(),
+        };
+
+        let mut hdrh_writer = None;
+        let mut recorder =
+            super::Recorder::start(None, std::num::NonZeroUsize::MIN, false, &mut hdrh_writer);
+        recorder.record(&[
+            workload_stats(&session_stats),
+            workload_stats(&session_stats),
+        ]);
+        recorder.record(&[workload_stats(&session_stats)]);
+        let stats = recorder.finish(Default::default(), Default::default());
+
+        assert_eq!(stats.session_stats["req_count"], 9);
+        assert_eq!(stats.session_stats["errors_by_category"]["timeout"], 3);
+        assert!(stats.session_stats["per_op_latency"]["execute"].is_object());
+        assert!(
+            stats.session_stats["latency_percentiles_us"]["p99"]
+                .as_u64()
+                .unwrap()
+                > 0
+        );
+    }
+
     #[test]
     fn cmp_custom_metric_computes_significance() {
         let recall_metric = |values: &[f64]| {
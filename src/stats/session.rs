@@ -2,22 +2,104 @@ use crate::config::PRINT_RETRY_ERROR_LIMIT;
 use crate::stats::latency::LatencyDistributionRecorder;
 use crate::stats::value::MetricValue;
 use crate::stats::value::ValueDistributionRecorder;
+use hdrhistogram::SubtractionError;
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use std::time::Duration;
 use tokio::time::Instant;
 
-#[derive(Clone, Debug)]
+/// Category of a failed request attempt, used to break down the errors by their cause.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ErrorCategory {
+    Timeout,
+    Unavailable,
+    Overloaded,
+    Validation,
+    Other,
+}
+
+/// Numbers of failed request attempts in each error category.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ErrorCounts {
+    pub timeout: u64,
+    pub unavailable: u64,
+    pub overloaded: u64,
+    pub validation: u64,
+    pub other: u64,
+}
+
+impl ErrorCounts {
+    pub fn record(&mut self, category: ErrorCategory) {
+        match category {
+            ErrorCategory::Timeout => self.timeout += 1,
+            ErrorCategory::Unavailable => self.unavailable += 1,
+            ErrorCategory::Overloaded => self.overloaded += 1,
+            ErrorCategory::Validation => self.validation += 1,
+            ErrorCategory::Other => self.other += 1,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.timeout + self.unavailable + self.overloaded + self.validation + self.other
+    }
+
+    fn add(&mut self, other: &ErrorCounts) {
+        self.timeout += other.timeout;
+        self.unavailable += other.unavailable;
+        self.overloaded += other.overloaded;
+        self.validation += other.validation;
+        self.other += other.other;
+    }
+
+    fn saturating_sub(&self, other: &ErrorCounts) -> ErrorCounts {
+        ErrorCounts {
+            timeout: self.timeout.saturating_sub(other.timeout),
+            unavailable: self.unavailable.saturating_sub(other.unavailable),
+            overloaded: self.overloaded.saturating_sub(other.overloaded),
+            validation: self.validation.saturating_sub(other.validation),
+            other: self.other.saturating_sub(other.other),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SessionStats {
     pub req_count: u64,
     pub req_errors: HashSet<String>,
+    /// Failed request attempts, including the retried ones.
     pub req_error_count: u64,
     pub req_retry_errors: HashSet<String>,
     pub req_retry_count: u64,
+    /// Failed request attempts, including the retried ones, by error category.
+    #[serde(default)]
+    pub errors_by_category: ErrorCounts,
+    /// Number of query results which did not pass validation, regardless of the validation strategy.
+    pub validation_failure_count: u64,
     pub row_count: u64,
     pub queue_length: u64,
     pub mean_queue_length: f32,
     pub resp_times_ns: LatencyDistributionRecorder,
+    /// Response times split by the kind of the operation, e.g. `execute` or `batch`.
+    #[serde(default)]
+    pub per_op_latency: HashMap<String, LatencyDistributionRecorder>,
     pub custom_metrics: HashMap<String, ValueDistributionRecorder>,
+    /// Start of the current interval, i.e. the time of the creation, the last reset
+    /// or the last `take_interval`.
+    /// Not exported, as it is meaningful only within the running process.
+    #[serde(skip, default = "Instant::now")]
+    pub start_time: Instant,
+    /// Set when the script ended the measurement window; requests completed afterwards
+    /// are not recorded. Kept across resets.
+    #[serde(skip)]
+    pub measurement_ended: bool,
+    /// Set when the script discarded the stats, e.g. by `begin_measurement`, since they
+    /// were last taken. Tells the recorder to drop the samples taken before, too.
+    #[serde(skip)]
+    pub discarded: bool,
+    /// Set when the script ended the measurement window since the stats were last taken.
+    /// Tells the recorder to drop the samples taken afterwards.
+    #[serde(skip)]
+    pub measurement_end_marked: bool,
 }
 
 impl SessionStats {
@@ -34,14 +116,29 @@ impl SessionStats {
         Instant::now()
     }
 
-    pub fn complete_request(&mut self, duration: Duration, row_count: u64) {
+    pub fn complete_request(&mut self, op: &str, duration: Duration, row_count: u64) {
         self.queue_length -= 1;
+        if self.measurement_ended {
+            return;
+        }
         self.resp_times_ns.record(duration);
+        // Called for every request: avoid allocating the key when the operation is known.
+        if let Some(recorder) = self.per_op_latency.get_mut(op) {
+            recorder.record(duration);
+        } else {
+            self.per_op_latency
+                .entry(op.to_string())
+                .or_default()
+                .record(duration);
+        }
         self.req_count += 1;
         self.row_count += row_count;
     }
 
     pub fn record_metric(&mut self, name: &str, value: f64) {
+        if self.measurement_ended {
+            return;
+        }
         // Called every cycle: avoid allocating the key on the hot path when the
         // metric already exists.
         if let Some(recorder) = self.custom_metrics.get_mut(name) {
@@ -54,24 +151,285 @@ impl SessionStats {
         }
     }
 
+    pub fn record_validation_failure(&mut self) {
+        if self.measurement_ended {
+            return;
+        }
+        self.validation_failure_count += 1;
+    }
+
+    pub fn record_error(&mut self, category: ErrorCategory) {
+        if self.measurement_ended {
+            return;
+        }
+        self.req_error_count += 1;
+        self.errors_by_category.record(category);
+    }
+
     pub fn store_retry_error(&mut self, error_str: String) {
+        if self.measurement_ended {
+            return;
+        }
         self.req_retry_count += 1;
-        if self.req_retry_count <= PRINT_RETRY_ERROR_LIMIT {
+        if (self.req_retry_errors.len() as u64) < PRINT_RETRY_ERROR_LIMIT {
             self.req_retry_errors.insert(error_str);
         }
     }
 
+    /// Response time percentiles in microseconds, based on the requests completed
+    /// since the last reset.
+    pub fn p50_us(&self) -> u64 {
+        self.resp_time_percentile_us(0.5)
+    }
+
+    pub fn p95_us(&self) -> u64 {
+        self.resp_time_percentile_us(0.95)
+    }
+
+    pub fn p99_us(&self) -> u64 {
+        self.resp_time_percentile_us(0.99)
+    }
+
+    pub fn p999_us(&self) -> u64 {
+        self.resp_time_percentile_us(0.999)
+    }
+
+    /// Response time percentiles in milliseconds, or `None` if no request completed
+    /// since the last reset.
+    pub fn p50_latency_ms(&self) -> Option<f64> {
+        self.resp_time_percentile_ms(0.5)
+    }
+
+    pub fn p95_latency_ms(&self) -> Option<f64> {
+        self.resp_time_percentile_ms(0.95)
+    }
+
+    pub fn p99_latency_ms(&self) -> Option<f64> {
+        self.resp_time_percentile_ms(0.99)
+    }
+
+    /// Fraction of request attempts that failed, including the retried ones;
+    /// 0.0 if no requests were made.
+    pub fn error_rate(&self) -> f64 {
+        let attempt_count = self.req_count + self.req_error_count;
+        if attempt_count == 0 {
+            0.0
+        } else {
+            self.req_error_count as f64 / attempt_count as f64
+        }
+    }
+
+    /// Number of requests per second completed over the given duration;
+    /// 0.0 if the duration is zero.
+    pub fn throughput(&self, duration: Duration) -> f64 {
+        let secs = duration.as_secs_f64();
+        if secs > 0.0 {
+            self.req_count as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    fn resp_time_percentile_ms(&self, quantile: f64) -> Option<f64> {
+        (self.req_count > 0)
+            .then(|| self.resp_times_ns.value_at_quantile(quantile).as_secs_f64() * 1000.0)
+    }
+
+    /// The 99th percentile of response times of the given operation in microseconds,
+    /// or `None` if no such operation completed since the last reset.
+    pub fn op_p99_us(&self, op: &str) -> Option<u64> {
+        self.per_op_latency
+            .get(op)
+            .map(|recorder| recorder.value_at_quantile(0.99).as_micros() as u64)
+    }
+
+    fn resp_time_percentile_us(&self, quantile: f64) -> u64 {
+        self.resp_times_ns.value_at_quantile(quantile).as_micros() as u64
+    }
+
+    /// Exports all the stats as JSON, for consumption by CI pipelines and monitoring tools.
+    /// Besides the serialized fields, the result holds the response time percentiles
+    /// under `latency_percentiles_us`, which are ignored when deserializing it back.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut json = serde_json::to_value(self).expect("SessionStats must serialize to JSON");
+        json["latency_percentiles_us"] = serde_json::json!({
+            "p50": self.p50_us(),
+            "p95": self.p95_us(),
+            "p99": self.p99_us(),
+            "p999": self.p999_us(),
+        });
+        json
+    }
+
+    /// Adds the counters, errors, latencies and custom metrics collected by `other`
+    /// to this session, e.g. to aggregate the stats of all workers into a global report.
+    /// The mean queue length is averaged with weights proportional to the request counts.
+    pub fn merge(&mut self, other: &SessionStats) {
+        let total_req_count = self.req_count + other.req_count;
+        if total_req_count > 0 {
+            self.mean_queue_length = (self.mean_queue_length * self.req_count as f32
+                + other.mean_queue_length * other.req_count as f32)
+                / total_req_count as f32;
+        }
+        self.req_count = total_req_count;
+        self.discarded |= other.discarded;
+        self.measurement_end_marked |= other.measurement_end_marked;
+        self.req_errors.extend(other.req_errors.iter().cloned());
+        self.req_error_count += other.req_error_count;
+        for error in &other.req_retry_errors {
+            if self.req_retry_errors.len() as u64 >= PRINT_RETRY_ERROR_LIMIT {
+                break;
+            }
+            self.req_retry_errors.insert(error.clone());
+        }
+        self.req_retry_count += other.req_retry_count;
+        self.errors_by_category.add(&other.errors_by_category);
+        self.validation_failure_count += other.validation_failure_count;
+        self.row_count += other.row_count;
+        self.queue_length += other.queue_length;
+        self.start_time = self.start_time.min(other.start_time);
+        self.resp_times_ns.add(&other.resp_times_ns);
+        for (op, recorder) in &other.per_op_latency {
+            self.per_op_latency
+                .entry(op.clone())
+                .or_default()
+                .add(recorder);
+        }
+        for (name, recorder) in &other.custom_metrics {
+            self.custom_metrics
+                .entry(name.clone())
+                .or_default()
+                .add(recorder);
+        }
+    }
+
+    /// Merges the stats of many sessions into one.
+    pub fn merge_all<'a>(stats: impl IntoIterator<Item = &'a SessionStats>) -> SessionStats {
+        stats
+            .into_iter()
+            .fold(SessionStats::new(), |mut merged, stats| {
+                merged.merge(stats);
+                merged
+            })
+    }
+
+    /// Returns the stats collected since `baseline`, which must be an earlier snapshot
+    /// of these stats, e.g. to report the activity of the last checkpoint interval.
+    /// Counters are subtracted, latency histograms use HDR histogram subtraction,
+    /// and only the errors not seen in the baseline are kept.
+    /// The current queue length is taken from `self`.
+    /// Fails if a histogram of `baseline` holds values not present in `self`.
+    pub fn diff(&self, baseline: &SessionStats) -> Result<SessionStats, SubtractionError> {
+        let mut result = self.clone();
+        result.req_count = self.req_count.saturating_sub(baseline.req_count);
+        result.req_error_count = self
+            .req_error_count
+            .saturating_sub(baseline.req_error_count);
+        result.req_retry_count = self
+            .req_retry_count
+            .saturating_sub(baseline.req_retry_count);
+        result.errors_by_category = self
+            .errors_by_category
+            .saturating_sub(&baseline.errors_by_category);
+        result.validation_failure_count = self
+            .validation_failure_count
+            .saturating_sub(baseline.validation_failure_count);
+        result.row_count = self.row_count.saturating_sub(baseline.row_count);
+        result.mean_queue_length = if result.req_count > 0 {
+            (self.mean_queue_length * self.req_count as f32
+                - baseline.mean_queue_length * baseline.req_count as f32)
+                / result.req_count as f32
+        } else {
+            0.0
+        };
+        result.req_errors = &self.req_errors - &baseline.req_errors;
+        result.req_retry_errors = &self.req_retry_errors - &baseline.req_retry_errors;
+        result.resp_times_ns.subtract(&baseline.resp_times_ns)?;
+        for (op, recorder) in &baseline.per_op_latency {
+            if let Some(result_recorder) = result.per_op_latency.get_mut(op) {
+                result_recorder.subtract(recorder)?;
+            }
+        }
+        for (name, recorder) in &baseline.custom_metrics {
+            if let Some(result_recorder) = result.custom_metrics.get_mut(name) {
+                result_recorder.subtract(recorder)?;
+            }
+        }
+        Ok(result)
+    }
+
+    /// Returns the stats collected since the `baseline` snapshot made by `take_interval`,
+    /// or all of them if they were reset after the snapshot.
+    pub fn since(&self, baseline: &SessionStats) -> Result<SessionStats, SubtractionError> {
+        if self.start_time == baseline.start_time {
+            self.diff(baseline)
+        } else {
+            Ok(self.clone())
+        }
+    }
+
+    /// Returns the stats collected since the previous call, computed by `since` from
+    /// the snapshot kept in `baseline`, and replaces the snapshot with the current stats.
+    /// The retry errors and the effective sample size estimators cannot be subtracted,
+    /// so they start over instead.
+    pub fn take_interval(
+        &mut self,
+        baseline: &mut SessionStats,
+    ) -> Result<SessionStats, SubtractionError> {
+        let interval = self.since(baseline)?;
+        self.req_retry_errors.clear();
+        self.discarded = false;
+        self.measurement_end_marked = false;
+        self.resp_times_ns.restart_sample_size_estimate();
+        self.per_op_latency
+            .values_mut()
+            .for_each(LatencyDistributionRecorder::restart_sample_size_estimate);
+        self.custom_metrics
+            .values_mut()
+            .for_each(ValueDistributionRecorder::restart_sample_size_estimate);
+        self.start_time = Instant::now();
+        *baseline = self.clone();
+        Ok(interval)
+    }
+
+    /// Discards the stats collected so far and starts recording again,
+    /// also after `end_measurement`.
+    pub fn begin_measurement(&mut self) {
+        self.discard();
+        self.measurement_ended = false;
+    }
+
+    /// Stops recording requests until the next `begin_measurement`.
+    /// The stats collected so far are kept until they are taken or reset.
+    pub fn end_measurement(&mut self) {
+        self.measurement_ended = true;
+        self.measurement_end_marked = true;
+    }
+
+    /// Resets all accumulators and marks the stats as discarded,
+    /// so the samples taken before get dropped from the final stats.
+    pub fn discard(&mut self) {
+        self.reset();
+        self.discarded = true;
+    }
+
     /// Resets all accumulators
     pub fn reset(&mut self) {
         self.req_error_count = 0;
         self.row_count = 0;
         self.req_count = 0;
         self.req_retry_count = 0;
+        self.errors_by_category = ErrorCounts::default();
+        self.validation_failure_count = 0;
         self.mean_queue_length = 0.0;
         self.req_errors.clear();
         self.req_retry_errors.clear();
         self.resp_times_ns.clear();
+        self.per_op_latency.clear();
         self.custom_metrics.clear();
+        self.start_time = Instant::now();
+        self.discarded = false;
+        self.measurement_end_marked = false;
 
         // note that current queue_length is *not* reset to zero because there
         // might be pending requests and if we set it to zero, that would underflow
@@ -86,11 +444,301 @@ impl Default for SessionStats {
             req_error_count: 0,
             req_retry_errors: HashSet::new(),
             req_retry_count: 0,
+            errors_by_category: ErrorCounts::default(),
+            validation_failure_count: 0,
             row_count: 0,
             queue_length: 0,
             mean_queue_length: 0.0,
             resp_times_ns: LatencyDistributionRecorder::default(),
+            per_op_latency: HashMap::new(),
             custom_metrics: HashMap::new(),
+            start_time: Instant::now(),
+            measurement_ended: false,
+            discarded: false,
+            measurement_end_marked: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentiles_follow_recorded_latencies() {
+        let mut stats = SessionStats::new();
+        for us in 1..=1000 {
+            stats.start_request();
+            stats.complete_request("execute", Duration::from_micros(us), 1);
+        }
+        let within = |actual: u64, expected: u64| actual.abs_diff(expected) <= expected / 100 + 1;
+        assert!(within(stats.p50_us(), 500), "p50 = {}", stats.p50_us());
+        assert!(within(stats.p95_us(), 950), "p95 = {}", stats.p95_us());
+        assert!(within(stats.p99_us(), 990), "p99 = {}", stats.p99_us());
+        assert!(within(stats.p999_us(), 999), "p999 = {}", stats.p999_us());
+
+        stats.reset();
+        assert_eq!(stats.p99_us(), 0);
+    }
+
+    #[test]
+    fn merging_identical_stats_doubles_counts() {
+        let mut stats = SessionStats::new();
+        for us in 1..=100 {
+            stats.start_request();
+            stats.complete_request("execute", Duration::from_micros(us * 10), 2);
+        }
+        stats.req_errors.insert("error".to_string());
+        stats.store_retry_error("retry".to_string());
+        stats.record_error(ErrorCategory::Timeout);
+        stats.record_validation_failure();
+        stats.record_metric("metric", 1.0);
+
+        let merged = SessionStats::merge_all([&stats, &stats]);
+        assert_eq!(merged.req_count, 200);
+        assert_eq!(merged.row_count, 400);
+        assert_eq!(merged.req_error_count, 2);
+        assert_eq!(merged.req_retry_count, 2);
+        assert_eq!(merged.errors_by_category.timeout, 2);
+        assert_eq!(merged.errors_by_category.total(), 2);
+        assert_eq!(merged.validation_failure_count, 2);
+        assert_eq!(merged.req_errors.len(), 1);
+        assert_eq!(merged.req_retry_errors.len(), 1);
+        assert_eq!(merged.mean_queue_length, stats.mean_queue_length);
+        assert_eq!(merged.resp_times_ns.distribution().histogram.0.len(), 200);
+        assert_eq!(
+            merged.custom_metrics["metric"]
+                .distribution()
+                .histogram
+                .0
+                .len(),
+            2
+        );
+        assert!(merged.p50_us() <= merged.p95_us());
+        assert!(merged.p95_us() <= merged.p99_us());
+        assert!(merged.p99_us() <= merged.p999_us());
+        assert_eq!(merged.p50_us(), stats.p50_us());
+    }
+
+    #[test]
+    fn error_rate_and_throughput() {
+        let mut stats = SessionStats::new();
+        assert_eq!(stats.error_rate(), 0.0);
+        assert_eq!(stats.throughput(Duration::ZERO), 0.0);
+        assert_eq!(stats.p99_latency_ms(), None);
+
+        for _ in 0..4 {
+            stats.start_request();
+            stats.complete_request("execute", Duration::from_millis(2), 1);
+        }
+        stats.record_error(ErrorCategory::Timeout);
+        assert_eq!(stats.req_error_count, 1);
+        assert_eq!(stats.error_rate(), 0.2);
+        assert_eq!(stats.throughput(Duration::from_secs(2)), 2.0);
+        let p50 = stats.p50_latency_ms().unwrap();
+        assert!((p50 - 2.0).abs() < 0.01, "p50 = {p50}");
+    }
+
+    #[test]
+    fn json_export_roundtrips() {
+        let mut stats = SessionStats::new();
+        for us in 1..=100 {
+            stats.start_request();
+            stats.complete_request("execute", Duration::from_micros(us), 1);
+        }
+        stats.req_error_count = 2;
+        stats.req_errors.insert("timeout".to_string());
+        stats.store_retry_error("overloaded".to_string());
+        stats.record_metric("metric", -1.5);
+
+        let json = stats.to_json();
+        for key in [
+            "req_count",
+            "req_errors",
+            "req_error_count",
+            "req_retry_errors",
+            "req_retry_count",
+            "validation_failure_count",
+            "row_count",
+            "resp_times_ns",
+            "custom_metrics",
+            "latency_percentiles_us",
+        ] {
+            assert!(json.get(key).is_some(), "missing key {key}");
+        }
+        assert_eq!(json["req_count"], 100);
+        assert_eq!(json["latency_percentiles_us"]["p99"], stats.p99_us());
+
+        let restored: SessionStats = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(restored.req_count, stats.req_count);
+        assert_eq!(restored.req_errors, stats.req_errors);
+        assert_eq!(restored.req_retry_errors, stats.req_retry_errors);
+        assert_eq!(restored.p50_us(), stats.p50_us());
+        assert_eq!(restored.p999_us(), stats.p999_us());
+        assert_eq!(restored.to_json(), json);
+    }
+
+    #[test]
+    fn latency_is_tracked_per_operation() {
+        let mut stats = SessionStats::new();
+        for _ in 0..10 {
+            stats.start_request();
+            stats.complete_request("execute", Duration::from_micros(100), 1);
+            stats.start_request();
+            stats.complete_request("batch", Duration::from_micros(5000), 0);
+        }
+        let execute_p99 = stats.op_p99_us("execute").unwrap();
+        let batch_p99 = stats.op_p99_us("batch").unwrap();
+        assert!(
+            execute_p99.abs_diff(100) <= 1,
+            "execute p99 = {execute_p99}"
+        );
+        assert!(batch_p99.abs_diff(5000) <= 5, "batch p99 = {batch_p99}");
+        assert_eq!(stats.op_p99_us("query"), None);
+        assert!(stats.p99_us() >= batch_p99);
+
+        let merged = SessionStats::merge_all([&stats, &stats]);
+        assert_eq!(merged.op_p99_us("batch"), Some(batch_p99));
+    }
+
+    #[test]
+    fn diff_returns_stats_since_baseline() {
+        let mut stats = SessionStats::new();
+        for _ in 0..10 {
+            stats.start_request();
+            stats.complete_request("execute", Duration::from_micros(100), 1);
+        }
+        stats.req_errors.insert("early".to_string());
+        stats.record_error(ErrorCategory::Overloaded);
+        stats.record_metric("metric", 1.0);
+
+        let same = stats.diff(&stats.clone()).unwrap();
+        assert_eq!(same.req_count, 0);
+        assert_eq!(same.req_error_count, 0);
+        assert_eq!(same.row_count, 0);
+        assert_eq!(same.validation_failure_count, 0);
+        assert!(same.req_errors.is_empty());
+        assert_eq!(same.p99_us(), 0);
+        assert_eq!(same.mean_queue_length, 0.0);
+
+        let baseline = stats.clone();
+        for _ in 0..5 {
+            stats.start_request();
+            stats.complete_request("batch", Duration::from_millis(5), 2);
         }
+        stats.req_errors.insert("late".to_string());
+        stats.record_error(ErrorCategory::Unavailable);
+        let delta = stats.diff(&baseline).unwrap();
+        assert_eq!(
+            delta.errors_by_category,
+            ErrorCounts {
+                unavailable: 1,
+                ..Default::default()
+            }
+        );
+        assert_eq!(delta.req_count, 5);
+        assert_eq!(delta.row_count, 10);
+        assert_eq!(delta.req_errors.len(), 1);
+        assert!(delta.req_errors.contains("late"));
+        assert!(
+            delta.p50_us().abs_diff(5000) <= 5,
+            "p50 = {}",
+            delta.p50_us()
+        );
+        assert!(delta.op_p99_us("batch").is_some());
+
+        assert!(baseline.diff(&stats).is_err());
+    }
+
+    #[test]
+    fn take_interval_returns_stats_since_previous_take() {
+        let mut stats = SessionStats::new();
+        let mut baseline = SessionStats::new();
+        for _ in 0..10 {
+            stats.start_request();
+            stats.complete_request("execute", Duration::from_micros(100), 1);
+        }
+        stats.store_retry_error("early".to_string());
+        let first = stats.take_interval(&mut baseline).unwrap();
+        assert_eq!(first.req_count, 10);
+        assert!(first.req_retry_errors.contains("early"));
+
+        for _ in 0..5 {
+            stats.start_request();
+            stats.complete_request("execute", Duration::from_millis(5), 1);
+        }
+        stats.store_retry_error("early".to_string());
+        let second = stats.take_interval(&mut baseline).unwrap();
+        assert_eq!(stats.req_count, 15);
+        assert_eq!(second.req_count, 5);
+        assert_eq!(second.req_retry_count, 1);
+        assert!(second.req_retry_errors.contains("early"));
+        assert!(
+            second.p50_us().abs_diff(5000) <= 5,
+            "p50 = {}",
+            second.p50_us()
+        );
+
+        stats.reset();
+        stats.start_request();
+        stats.complete_request("execute", Duration::from_micros(100), 1);
+        let after_reset = stats.take_interval(&mut baseline).unwrap();
+        assert_eq!(after_reset.req_count, 1);
+    }
+
+    #[test]
+    fn requests_outside_measurement_window_are_not_recorded() {
+        let mut stats = SessionStats::new();
+        stats.start_request();
+        stats.complete_request("execute", Duration::from_millis(100), 1);
+        stats.record_error(ErrorCategory::Timeout);
+
+        stats.begin_measurement();
+        assert_eq!(stats.req_count, 0);
+        assert_eq!(stats.errors_by_category.total(), 0);
+        stats.start_request();
+        stats.complete_request("execute", Duration::from_millis(1), 1);
+
+        stats.start_request();
+        stats.end_measurement();
+        stats.complete_request("execute", Duration::from_millis(100), 1);
+        stats.record_error(ErrorCategory::Other);
+        stats.record_metric("metric", 1.0);
+        assert_eq!(stats.req_count, 1);
+        assert_eq!(stats.queue_length, 0);
+        assert_eq!(stats.errors_by_category.total(), 0);
+        assert!(stats.custom_metrics.is_empty());
+        assert!(stats.p99_us() < 2000, "p99 = {}", stats.p99_us());
+
+        stats.reset();
+        stats.start_request();
+        stats.complete_request("execute", Duration::from_millis(1), 1);
+        assert_eq!(stats.req_count, 0, "reset must not restart the measurement");
+    }
+
+    #[test]
+    fn measurement_markers_are_kept_until_taken() {
+        let mut stats = SessionStats::new();
+        let mut baseline = SessionStats::new();
+        stats.begin_measurement();
+        stats.end_measurement();
+        let merged = SessionStats::merge_all([&SessionStats::new(), &stats]);
+        assert!(merged.discarded);
+        assert!(merged.measurement_end_marked);
+
+        let taken = stats.take_interval(&mut baseline).unwrap();
+        assert!(taken.discarded);
+        assert!(taken.measurement_end_marked);
+        let taken = stats.take_interval(&mut baseline).unwrap();
+        assert!(!taken.discarded);
+        assert!(!taken.measurement_end_marked);
+        assert!(taken.measurement_ended);
+
+        stats.discard();
+        assert!(stats.discarded);
+        assert!(
+            stats.measurement_ended,
+            "discarding must not restart the measurement"
+        );
     }
 }
@@ -1,6 +1,8 @@
+use crate::stats::histogram::serde_histogram;
 use crate::stats::percentiles::{bootstrap_from_total, PercentileSource};
-use hdrhistogram::{AdditionError, CreationError, Histogram, RecordError};
+use hdrhistogram::{AdditionError, CreationError, Histogram, RecordError, SubtractionError};
 use rand::rngs::SmallRng;
+use serde::{Deserialize, Serialize};
 
 /// A histogram over signed values built from two HDR histograms.
 ///
@@ -9,9 +11,11 @@ use rand::rngs::SmallRng;
 /// — a DDSketch-style sign split that keeps HDR's relative precision symmetric
 /// around zero. It mirrors the `Histogram` interface the stats code uses, so a
 /// recorder can hold it wherever it would hold a plain histogram.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct SignedHistogram {
+    #[serde(with = "serde_histogram")]
     positive: Histogram<u64>,
+    #[serde(with = "serde_histogram")]
     negative: Histogram<u64>,
 }
 
@@ -42,6 +46,11 @@ impl SignedHistogram {
         self.negative.add(&other.negative)
     }
 
+    pub fn subtract(&mut self, other: &SignedHistogram) -> Result<(), SubtractionError> {
+        self.positive.subtract(&other.positive)?;
+        self.negative.subtract(&other.negative)
+    }
+
     pub fn clear(&mut self) {
         self.positive.clear();
         self.negative.clear();
@@ -29,6 +29,13 @@ impl ThroughputMeter {
         self.last_record_time = now;
     }
 
+    /// Forgets the recorded events, but not the time of the last record,
+    /// which the next record is measured from.
+    pub fn clear(&mut self) {
+        self.count = 0;
+        self.stats.clear();
+    }
+
     /// Returns mean throughput in events per second
     pub fn throughput(&self) -> Mean {
         self.stats.mean()
@@ -1,6 +1,7 @@
 use crate::stats::Mean;
 use more_asserts::assert_le;
 use rand_distr::num_traits::Pow;
+use serde::{Deserialize, Serialize};
 
 /// Estimates the mean and effective size of the sample, by taking account for
 /// autocorrelation between measurements.
@@ -14,13 +15,13 @@ use rand_distr::num_traits::Pow;
 /// The algorithm used for computing autocorrelation matrix is quite inaccurate as it doesn't compute
 /// the full covariance matrix, but approximates it by pre-merging data points.
 /// However, it is fairly fast (O(n log log n) and works in O(log n) memory incrementally.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct TimeSeriesStats {
     n: u64,
     levels: Vec<Level>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 struct Level {
     level: usize,
     buf: Vec<(f64, f64)>,
@@ -162,7 +163,7 @@ impl Level {
     }
 }
 
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 struct Stats {
     mean: f64,
     var: f64,
@@ -3,6 +3,7 @@ use crate::stats::percentiles::Percentiles;
 use crate::stats::signed_histogram::SignedHistogram;
 use crate::stats::timeseries::TimeSeriesStats;
 use crate::stats::Mean;
+use hdrhistogram::SubtractionError;
 use serde::{Deserialize, Serialize};
 
 /// A dimensionless metric value.
@@ -52,7 +53,7 @@ pub struct ValueDistribution {
 }
 
 /// Builds ValueDistribution from a stream of measured values.
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Debug, Default, Serialize, Deserialize)]
 pub struct ValueDistributionRecorder {
     histogram: SignedHistogram,
     ess_estimator: TimeSeriesStats,
@@ -73,6 +74,19 @@ impl ValueDistributionRecorder {
         self.ess_estimator.add(&other.ess_estimator);
     }
 
+    /// Removes the values recorded by `other`, which must be an earlier snapshot of this recorder.
+    /// The effective sample size estimator is kept as is, as it cannot be rolled back;
+    /// use `restart_sample_size_estimate` at the snapshot to estimate it for the difference only.
+    pub fn subtract(&mut self, other: &ValueDistributionRecorder) -> Result<(), SubtractionError> {
+        self.histogram.subtract(&other.histogram)
+    }
+
+    /// Forgets the values recorded so far by the effective sample size estimator,
+    /// but keeps them in the histogram.
+    pub fn restart_sample_size_estimate(&mut self) {
+        self.ess_estimator.clear();
+    }
+
     pub fn clear(&mut self) {
         self.histogram.clear();
         self.ess_estimator.clear();
@@ -343,3 +343,91 @@ async fn test_rune_return_in_diverging_branches() {
     assert_latte_success(&read_result);
     assert_has_throughput_metrics(&read_result);
 }
+
+/// Tests that the `custom:<function>` validation strategy passes the rows of every
+/// `select` query to the workload function and honors the result it returns.
+#[tokio::test]
+#[ignore]
+async fn test_latte_cql_custom_validation_strategy() {
+    let db = start_scylla().await.expect("Failed to start ScyllaDB");
+
+    let latte = LatteVariant::Cql;
+    let workload = workload_path("integration_tests/custom_validation.rn");
+    let duration = "1000";
+    let custom = ["--validation-strategy", "custom:check_rows"];
+
+    println!("\n[TEST-INFO] Phase 1: Create the schema ({:?})", latte);
+    latte.schema(&db, &workload, &[]);
+
+    println!("\n[TEST-INFO] Phase 2: Data population");
+    let write_result = latte.run(&db, &workload, duration, &["-f", "write"]);
+    assert_latte_success(&write_result);
+
+    println!("\n[TEST-INFO] Phase 3: Read rows accepted by the validation function");
+    let read_result = latte.run(
+        &db,
+        &workload,
+        duration,
+        &[&["-f", "read"], &custom[..]].concat(),
+    );
+    assert_latte_success(&read_result);
+    assert_has_throughput_metrics(&read_result);
+
+    println!("\n[TEST-INFO] Phase 4: Read missing rows without the validation function");
+    let unchecked_result = latte.run(&db, &workload, duration, &["-f", "read_missing"]);
+    assert_latte_success(&unchecked_result);
+
+    println!("\n[TEST-INFO] Phase 5: Read missing rows rejected by the validation function");
+    let rejected_result = latte.run(
+        &db,
+        &workload,
+        duration,
+        &[&["-f", "read_missing"], &custom[..]].concat(),
+    );
+    assert!(
+        !rejected_result.status.success(),
+        "latte should fail when the validation function rejects the rows:\n{}",
+        rejected_result.output
+    );
+    assert!(
+        rejected_result
+            .output
+            .contains("Validation function 'check_rows' rejected the result"),
+        "Expected the rejection of the validation function in latte output:\n{}",
+        rejected_result.output
+    );
+}
+
+/// Tests that `compute_token` returns the token of the partition key bound to a prepared
+/// statement and fails for statements which don't bind the partition key.
+#[tokio::test]
+#[ignore]
+async fn test_latte_cql_compute_token() {
+    let db = start_scylla().await.expect("Failed to start ScyllaDB");
+
+    let latte = LatteVariant::Cql;
+    let workload = workload_path("integration_tests/compute_token.rn");
+    let duration = "100";
+
+    println!("\n[TEST-INFO] Phase 1: Create the schema ({:?})", latte);
+    latte.schema(&db, &workload, &[]);
+
+    println!("\n[TEST-INFO] Phase 2: Compute the token of a known partition key");
+    let known_result = latte.run(&db, &workload, duration, &["-f", "known_token"]);
+    assert_latte_success(&known_result);
+
+    println!("\n[TEST-INFO] Phase 3: Compute the token without the partition key");
+    let partial_result = latte.run(&db, &workload, duration, &["-f", "partial_partition_key"]);
+    assert!(
+        !partial_result.status.success(),
+        "latte should fail when the statement doesn't bind the partition key:\n{}",
+        partial_result.output
+    );
+    assert!(
+        partial_result
+            .output
+            .contains("Statement 'by_val' does not bind the full partition key"),
+        "Expected the partition key error in latte output:\n{}",
+        partial_result.output
+    );
+}